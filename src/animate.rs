@@ -0,0 +1,117 @@
+//! Redraws a simulation in the terminal one step at a time. The days that can
+//! be watched step their own state and hand each picture to an `Animation`,
+//! which decides whether the step is in the requested range and how long to
+//! wait before the next one.
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use p11;
+use p20;
+use p4;
+use p6;
+use parser::ParseError;
+
+/// Clears the screen and moves the cursor to the top left.
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+pub struct Animation {
+    pub delay: Duration,
+    /// The first step to draw, where step 0 is the parsed input.
+    pub from: usize,
+    /// The last step to draw, or `None` for the day's own default.
+    pub to: Option<usize>,
+    drawn: usize,
+}
+
+impl Animation {
+    pub fn new(delay: Duration, from: usize, to: Option<usize>) -> Animation {
+        Animation {
+            delay,
+            from,
+            to,
+            drawn: 0,
+        }
+    }
+
+    /// The last step to run, given the step the day would stop at by itself.
+    pub fn last(&self, default: usize) -> usize {
+        self.to.unwrap_or(default)
+    }
+
+    pub fn wants(&self, step: usize) -> bool {
+        self.from <= step && self.to.is_none_or(|to| step <= to)
+    }
+
+    /// Draws `picture` as the frame for `step` if it is in range. The picture
+    /// is only built when it is drawn.
+    pub fn frame<F: FnOnce() -> String>(&mut self, step: usize, picture: F) {
+        if !self.wants(step) {
+            return;
+        }
+        if self.drawn > 0 {
+            thread::sleep(self.delay);
+        }
+        let mut out = io::stdout().lock();
+        let _ = write!(out, "{}step {}\n\n{}\n", CLEAR, step, picture());
+        let _ = out.flush();
+        self.drawn += 1;
+    }
+}
+
+pub type Animator = fn(&str, &mut Animation) -> Result<(), ParseError>;
+
+/// The days that can be watched.
+pub const ANIMATED: &[(u32, Animator)] = &[
+    (4, p4::animate),
+    (6, p6::animate),
+    (11, p11::animate),
+    (20, p20::animate),
+];
+
+pub fn find(day: u32) -> Option<Animator> {
+    ANIMATED.iter().find(|(d, _)| *d == day).map(|(_, a)| *a)
+}
+
+/// `from..to` with either end left out, both ends included, or a single step.
+pub fn parse_steps(s: &str) -> Option<(usize, Option<usize>)> {
+    let (from, to) = match s.split_once("..") {
+        Some(ends) => ends,
+        None => {
+            let step = s.parse().ok()?;
+            return Some((step, Some(step)));
+        }
+    };
+    let from = if from.is_empty() { 0 } else { from.parse().ok()? };
+    let to = if to.is_empty() { None } else { Some(to.parse().ok()?) };
+    if to.is_some_and(|to| to < from) {
+        return None;
+    }
+    Some((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_step_ranges() {
+        assert_eq!(parse_steps("7"), Some((7, Some(7))));
+        assert_eq!(parse_steps("10..50"), Some((10, Some(50))));
+        assert_eq!(parse_steps("3..3"), Some((3, Some(3))));
+        assert_eq!(parse_steps("10.."), Some((10, None)));
+        assert_eq!(parse_steps("..50"), Some((0, Some(50))));
+        assert_eq!(parse_steps(".."), Some((0, None)));
+    }
+
+    #[test]
+    fn rejects_bad_step_ranges() {
+        assert_eq!(parse_steps(""), None);
+        assert_eq!(parse_steps("50..10"), None);
+        assert_eq!(parse_steps("1..0"), None);
+        for bad in ["x", "1..x", "a..2", "1...3", "-1..3", "1,3", " 1..3"] {
+            assert_eq!(parse_steps(bad), None, "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::fs;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use parser::ParseError;
+use registry::Day;
+
+pub const PHASES: [&str; 3] = ["parse", "part1", "part2"];
+
+/// How long one phase of one solver took over a number of runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+    pub solver: String,
+    pub input: String,
+    pub phase: String,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    fn key(&self) -> (String, String, String) {
+        (self.solver.clone(), self.input.clone(), self.phase.clone())
+    }
+}
+
+fn summarize(d: &Day, input: &str, phase: &str, mut samples: Vec<Duration>) -> Timing {
+    samples.sort();
+    Timing {
+        solver: d.name(),
+        input: input.to_string(),
+        phase: phase.to_string(),
+        min: samples[0],
+        median: samples[samples.len() / 2],
+        max: samples[samples.len() - 1],
+    }
+}
+
+/// Parses and answers both parts `runs` times, timing each phase separately.
+/// The answers go through `black_box` so the work can't be optimised away.
+pub fn bench_day(d: &Day, input: &str, text: &str, runs: usize) -> Result<Vec<Timing>, ParseError> {
+    let mut samples: Vec<Vec<Duration>> = vec![vec![]; PHASES.len()];
+    for _ in 0..runs {
+        let start = Instant::now();
+        let parsed = d.prepare(text)?;
+        samples[0].push(start.elapsed());
+
+        let start = Instant::now();
+        black_box(parsed.part1());
+        samples[1].push(start.elapsed());
+
+        let start = Instant::now();
+        black_box(parsed.part2());
+        samples[2].push(start.elapsed());
+    }
+    Ok(PHASES
+        .iter()
+        .zip(samples)
+        .map(|(phase, s)| summarize(d, input, phase, s))
+        .collect())
+}
+
+/// A CSV field, in quotes with its own quotes doubled if it has a comma, a
+/// quote or a line break in it.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", s.replace('"', "\"\""));
+    }
+    return s.to_string();
+}
+
+/// The fields of a CSV line, undoing `csv_field`.
+fn csv_fields(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = vec![];
+    let mut chars = line.chars().peekable();
+    loop {
+        let mut field = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err("unterminated quote".to_string()),
+                }
+            }
+            if !matches!(chars.peek(), None | Some(',')) {
+                return Err("text after a closing quote".to_string());
+            }
+        } else {
+            while let Some(c) = chars.peek().filter(|c| **c != ',') {
+                field.push(*c);
+                chars.next();
+            }
+        }
+        fields.push(field);
+        if chars.next().is_none() {
+            return Ok(fields);
+        }
+    }
+}
+
+pub fn to_csv(timings: &[Timing]) -> String {
+    let mut s = "solver,input,phase,min_ns,median_ns,max_ns\n".to_string();
+    for t in timings {
+        s += &format!(
+            "{},{},{},{},{},{}\n",
+            csv_field(&t.solver),
+            csv_field(&t.input),
+            csv_field(&t.phase),
+            t.min.as_nanos(),
+            t.median.as_nanos(),
+            t.max.as_nanos()
+        );
+    }
+    return s;
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = "\"".to_string();
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
+
+pub fn to_json(timings: &[Timing]) -> String {
+    let rows: Vec<String> = timings
+        .iter()
+        .map(|t| {
+            format!(
+                "  {{\"solver\": {}, \"input\": {}, \"phase\": {}, \"min_ns\": {}, \"median_ns\": {}, \"max_ns\": {}}}",
+                json_string(&t.solver),
+                json_string(&t.input),
+                json_string(&t.phase),
+                t.min.as_nanos(),
+                t.median.as_nanos(),
+                t.max.as_nanos()
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", rows.join(",\n"))
+}
+
+/// Reads timings back from what `to_csv` wrote.
+pub fn from_csv(s: &str) -> Result<Vec<Timing>, String> {
+    let mut timings = vec![];
+    for (i, line) in s.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = csv_fields(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        if fields.len() != 6 {
+            return Err(format!("line {}: expected 6 fields", i + 1));
+        }
+        let nanos = |f: &str| {
+            f.parse::<u64>()
+                .map(Duration::from_nanos)
+                .map_err(|_| format!("line {}: bad duration {:?}", i + 1, f))
+        };
+        timings.push(Timing {
+            solver: fields[0].clone(),
+            input: fields[1].clone(),
+            phase: fields[2].clone(),
+            min: nanos(&fields[3])?,
+            median: nanos(&fields[4])?,
+            max: nanos(&fields[5])?,
+        });
+    }
+    return Ok(timings);
+}
+
+pub fn load_baseline(path: &str) -> Result<Vec<Timing>, String> {
+    let s = fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+    from_csv(&s).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// How much slower (positive) or faster (negative) each timing's median is than
+/// the baseline's, as a fraction. Timings missing from the baseline get `None`.
+pub fn compare(timings: &[Timing], baseline: &[Timing]) -> Vec<Option<f64>> {
+    let old: HashMap<(String, String, String), Duration> =
+        baseline.iter().map(|t| (t.key(), t.median)).collect();
+    timings
+        .iter()
+        .map(|t| {
+            old.get(&t.key()).map(|before| {
+                let before = before.as_secs_f64().max(1e-9);
+                (t.median.as_secs_f64() - before) / before
+            })
+        })
+        .collect()
+}
+
+pub fn fmt_duration(d: Duration) -> String {
+    let nanos = d.as_nanos();
+    if nanos < 1_000 {
+        format!("{}ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.1}us", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.1}ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2}s", nanos as f64 / 1e9)
+    }
+}
+
+/// Reports every timing whose median got slower than `threshold` allows on
+/// stderr, returning whether there were any.
+pub fn report_regressions(timings: &[Timing], changes: &[Option<f64>], threshold: f64) -> bool {
+    let mut regressed = false;
+    for (t, change) in timings.iter().zip(changes) {
+        if let Some(c) = change {
+            if *c > threshold {
+                eprintln!("regression: {} {} {} {:+.0}%", t.solver, t.input, t.phase, c * 100.0);
+                regressed = true;
+            }
+        }
+    }
+    return regressed;
+}
+
+/// Prints the timings, and when there is a baseline, how each median moved.
+/// Returns whether anything got slower than `threshold` allows.
+pub fn print_table(timings: &[Timing], changes: Option<&[Option<f64>]>, threshold: f64) -> bool {
+    let mut regressed = false;
+    println!(
+        "{:<20} {:<10} {:<6} {:>10} {:>10} {:>10}",
+        "solver", "input", "phase", "min", "median", "max"
+    );
+    for (i, t) in timings.iter().enumerate() {
+        let change = match changes.map(|c| c[i]) {
+            None => "".to_string(),
+            Some(None) => "  (new)".to_string(),
+            Some(Some(c)) if c > threshold => {
+                regressed = true;
+                format!("  {:+.0}% regression", c * 100.0)
+            }
+            Some(Some(c)) => format!("  {:+.0}%", c * 100.0),
+        };
+        println!(
+            "{:<20} {:<10} {:<6} {:>10} {:>10} {:>10}{}",
+            t.solver,
+            t.input,
+            t.phase,
+            fmt_duration(t.min),
+            fmt_duration(t.median),
+            fmt_duration(t.max),
+            change
+        );
+    }
+    return regressed;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(input: &str, phase: &str, median: u64) -> Timing {
+        Timing {
+            solver: "day 1".to_string(),
+            input: input.to_string(),
+            phase: phase.to_string(),
+            min: Duration::from_nanos(median / 2),
+            median: Duration::from_nanos(median),
+            max: Duration::from_nanos(median * 2),
+        }
+    }
+
+    #[test]
+    fn writes_csv_and_json() {
+        let timings = vec![timing("puzzle", "parse", 100), timing("in, \"quotes\".txt", "part1", 2000)];
+        assert_eq!(
+            to_csv(&timings),
+            "solver,input,phase,min_ns,median_ns,max_ns\nday 1,puzzle,parse,50,100,200\nday 1,\"in, \"\"quotes\"\".txt\",part1,1000,2000,4000\n"
+        );
+        assert_eq!(
+            to_json(&timings[1..]),
+            "[\n  {\"solver\": \"day 1\", \"input\": \"in, \\\"quotes\\\".txt\", \"phase\": \"part1\", \"min_ns\": 1000, \"median_ns\": 2000, \"max_ns\": 4000}\n]\n"
+        );
+    }
+
+    #[test]
+    fn reads_back_csv() {
+        let timings = vec![timing("puzzle", "parse", 100), timing("a,b", "part2", 7), timing("\"", "part1", 1)];
+        assert_eq!(from_csv(&to_csv(&timings)), Ok(timings));
+        assert_eq!(from_csv("header\n\nday 1,puzzle,parse,1,2,3\n").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejects_bad_csv() {
+        assert_eq!(from_csv("header\nday 1,puzzle,parse,1,2"), Err("line 2: expected 6 fields".to_string()));
+        assert_eq!(from_csv("header\nday 1,puzzle,parse,1,x,3"), Err("line 2: bad duration \"x\"".to_string()));
+        assert_eq!(from_csv("header\nday 1,\"puzzle,parse,1,2,3"), Err("line 2: unterminated quote".to_string()));
+        assert_eq!(from_csv("header\nday 1,\"a\"b,parse,1,2,3"), Err("line 2: text after a closing quote".to_string()));
+    }
+
+    #[test]
+    fn compares_medians_with_the_baseline() {
+        let baseline = vec![timing("puzzle", "part1", 100), timing("puzzle", "part2", 100)];
+        let timings = vec![timing("puzzle", "part1", 150), timing("puzzle", "part2", 50), timing("sample", "part1", 10)];
+        let changes = compare(&timings, &baseline);
+        assert_eq!(changes, vec![Some(0.5), Some(-0.5), None]);
+        assert!(report_regressions(&timings, &changes, 0.1));
+        assert!(!report_regressions(&timings, &changes, 0.6));
+    }
+}
@@ -0,0 +1,279 @@
+//! Runs the different implementations of a day on the same inputs to find
+//! where they disagree. Every registered variant of a day is compared part by
+//! part, along with the alternatives below that are not solvers of their own.
+//! The first input they disagree on is cut down to as few lines as still show
+//! the same disagreement.
+
+use generate;
+use input::{self, Source};
+use p17;
+use parser::ParseError;
+use registry::{self, Day};
+use solution::Answer;
+
+pub type Alternative = fn(&str) -> Result<Answer, ParseError>;
+
+/// Other ways of answering a part, as (day, part, name, implementation).
+pub const ALTERNATIVES: &[(u32, usize, &str, Alternative)] = &[
+    (17, 1, "check_rect", p17::highest_by_search),
+];
+
+enum Runner {
+    Registered(&'static Day),
+    Alternative(Alternative),
+}
+
+pub struct Implementation {
+    pub name: String,
+    runner: Runner,
+}
+
+impl Implementation {
+    /// The answer for `part`, or why there is none.
+    pub fn answer(&self, part: usize, input: &str) -> Result<Answer, String> {
+        match &self.runner {
+            Runner::Registered(d) => {
+                let parsed = d.prepare(input).map_err(|e| e.to_string())?;
+                Ok(if part == 1 { parsed.part1() } else { parsed.part2() })
+            }
+            Runner::Alternative(f) => f(input).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Two or more implementations of the same part of a day.
+pub struct Comparison {
+    pub day: u32,
+    pub part: usize,
+    pub implementations: Vec<Implementation>,
+}
+
+impl Comparison {
+    pub fn name(&self) -> String {
+        format!("day {} part {}", self.day, self.part)
+    }
+
+    pub fn outcomes(&self, input: &str) -> Vec<Result<Answer, String>> {
+        self.implementations.iter().map(|i| i.answer(self.part, input)).collect()
+    }
+
+    /// The stored inputs every registered variant of the day handles.
+    pub fn stored_inputs(&self) -> Vec<&'static str> {
+        let days: Vec<&Day> = registry::DAYS.iter().filter(|d| d.day == self.day).collect();
+        let mut names: Vec<&'static str> = days.first().map(|d| d.inputs.to_vec()).unwrap_or_default();
+        names.retain(|name| days.iter().all(|d| d.inputs.contains(name)));
+        return names;
+    }
+}
+
+/// Every part of every day that has more than one implementation, in day
+/// order.
+pub fn comparisons() -> Vec<Comparison> {
+    let mut all = vec![];
+    let mut days: Vec<u32> = registry::DAYS.iter().map(|d| d.day).collect();
+    days.dedup();
+    for day in days {
+        for part in 1..=2 {
+            let mut implementations: Vec<Implementation> = registry::DAYS
+                .iter()
+                .filter(|d| d.day == day)
+                .map(|d| Implementation {
+                    name: d.variant.unwrap_or("default").to_string(),
+                    runner: Runner::Registered(d),
+                })
+                .collect();
+            for (_, _, name, f) in ALTERNATIVES.iter().filter(|a| a.0 == day && a.1 == part) {
+                implementations.push(Implementation {
+                    name: name.to_string(),
+                    runner: Runner::Alternative(*f),
+                });
+            }
+            if implementations.len() > 1 {
+                all.push(Comparison { day, part, implementations });
+            }
+        }
+    }
+    return all;
+}
+
+/// The implementations disagree unless they all give the same answer or all
+/// fail.
+fn disagrees(outcomes: &[Result<Answer, String>]) -> bool {
+    if outcomes.iter().all(|o| o.is_err()) {
+        return false;
+    }
+    return outcomes.windows(2).any(|w| w[0].is_err() || w[0] != w[1]);
+}
+
+/// Which implementations failed, so a cut down input can be held to failing
+/// the same way as the one it came from.
+fn failures(outcomes: &[Result<Answer, String>]) -> Vec<bool> {
+    outcomes.iter().map(|o| o.is_err()).collect()
+}
+
+/// Drops lines from `input` while the implementations keep disagreeing in the
+/// same way, in halves, then quarters and so on down to single lines.
+pub fn minimize(c: &Comparison, input: &str) -> String {
+    let wanted = failures(&c.outcomes(input));
+    let still_fails = |candidate: &str| {
+        let outcomes = c.outcomes(candidate);
+        disagrees(&outcomes) && failures(&outcomes) == wanted
+    };
+    let mut lines: Vec<&str> = input.lines().collect();
+    let mut chunk = (lines.len() / 2).max(1);
+    loop {
+        let mut shrunk = false;
+        let mut start = 0;
+        while start < lines.len() {
+            let end = (start + chunk).min(lines.len());
+            if end - start < lines.len() {
+                let candidate: Vec<&str> = lines[..start].iter().chain(&lines[end..]).copied().collect();
+                if still_fails(&candidate.join("\n")) {
+                    trace!("dropped lines {}..{}, {} left", start, end, candidate.len());
+                    lines = candidate;
+                    shrunk = true;
+                    continue;
+                }
+            }
+            start = end;
+        }
+        if chunk == 1 && !shrunk {
+            break;
+        }
+        if !shrunk {
+            chunk /= 2;
+        }
+    }
+    return lines.join("\n");
+}
+
+pub struct Disagreement {
+    /// Where the input came from.
+    pub source: String,
+    pub lines: usize,
+    pub input: String,
+    pub outcomes: Vec<Result<Answer, String>>,
+}
+
+/// How many generated inputs to try and where their seeds start. Their sizes
+/// grow from 1 to the day's usual size, so the first disagreement found is
+/// already a small one.
+#[derive(Debug, Clone, Copy)]
+pub struct Plan {
+    pub cases: usize,
+    pub seed: u64,
+}
+
+/// Runs a comparison on the stored inputs and then on generated ones, stopping
+/// at the first disagreement.
+pub fn find_disagreement(c: &Comparison, plan: Plan) -> Option<Disagreement> {
+    let mut inputs: Vec<(String, String)> = vec![];
+    for name in c.stored_inputs() {
+        let source = Source::Named(name.to_string());
+        match input::load(c.day, &source) {
+            Ok(text) => inputs.push((source.describe(c.day), text)),
+            Err(e) => warn!("could not read {}: {}", source.describe(c.day), e),
+        }
+    }
+    let usual = generate::find(c.day).map_or(0, |g| g.size);
+    for i in 0..plan.cases {
+        let seed = plan.seed + i as u64;
+        let size = (usual * (i + 1) / plan.cases).max(1);
+        if let Some(text) = generate::input(c.day, seed, Some(size)) {
+            inputs.push((format!("generated input (seed {}, size {})", seed, size), text));
+        }
+    }
+    for (source, text) in inputs {
+        info!("{}: trying {}", c.name(), source);
+        if disagrees(&c.outcomes(&text)) {
+            let input = minimize(c, &text);
+            return Some(Disagreement {
+                source,
+                lines: text.lines().count(),
+                outcomes: c.outcomes(&input),
+                input,
+            });
+        }
+    }
+    return None;
+}
+
+pub fn print_disagreement(c: &Comparison, d: &Disagreement) {
+    println!(
+        "{}: disagreement on {}, cut down to {} of its {} lines",
+        c.name(),
+        d.source,
+        d.input.lines().count(),
+        d.lines
+    );
+    for (i, outcome) in c.implementations.iter().zip(&d.outcomes) {
+        match outcome {
+            Ok(answer) => println!("  {:<12} {}", i.name, answer),
+            Err(e) => println!("  {:<12} failed: {}", i.name, e.replace('\n', "\n               ")),
+        }
+    }
+    println!("input:\n{}\n", d.input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has(input: &str, line: &str) -> bool {
+        input.lines().any(|l| l == line)
+    }
+
+    fn one(_: &str) -> Result<Answer, ParseError> {
+        Ok(Answer::Int(1))
+    }
+
+    fn two_on_culprit(input: &str) -> Result<Answer, ParseError> {
+        Ok(Answer::Int(if has(input, "culprit") { 2 } else { 1 }))
+    }
+
+    fn fails_on_bad(input: &str) -> Result<Answer, ParseError> {
+        if has(input, "bad") {
+            return Err(ParseError::new(1, 1, "nothing bad"));
+        }
+        Ok(Answer::Int(1))
+    }
+
+    fn fails_without_worse(input: &str) -> Result<Answer, ParseError> {
+        if !has(input, "worse") {
+            return Err(ParseError::new(1, 1, "something worse"));
+        }
+        Ok(Answer::Int(1))
+    }
+
+    fn comparison(fs: &[Alternative]) -> Comparison {
+        Comparison {
+            day: 0,
+            part: 1,
+            implementations: fs
+                .iter()
+                .enumerate()
+                .map(|(i, f)| Implementation { name: i.to_string(), runner: Runner::Alternative(*f) })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn cuts_down_to_the_line_that_disagrees() {
+        let c = comparison(&[one, two_on_culprit]);
+        let lines: Vec<String> = (0..20).map(|i| i.to_string()).collect();
+        let mut input = lines.clone();
+        input.insert(13, "culprit".to_string());
+        assert_eq!(minimize(&c, &input.join("\n")), "culprit");
+        assert!(!disagrees(&c.outcomes(&lines.join("\n"))));
+    }
+
+    #[test]
+    fn keeps_failing_the_same_way() {
+        let c = comparison(&[fails_on_bad, fails_without_worse]);
+        assert!(!disagrees(&c.outcomes("bad")));
+        let small = minimize(&c, "1\nbad\n2\nworse\n3");
+        assert_eq!(small, "bad\nworse");
+        assert!(disagrees(&c.outcomes(&small)));
+        assert_eq!(failures(&c.outcomes(&small)), vec![true, false]);
+    }
+}
@@ -0,0 +1,341 @@
+//! Writes grids and point sets out as pictures. A day draws an `Image` of
+//! palette indices, and the format, scale and palette only come in when it is
+//! written, so every day can be exported as PBM, PPM or SVG.
+
+use std::fmt::Write;
+
+use grid::{Dense, Pos, Sparse};
+use p13;
+use p20;
+use p5;
+use p9;
+use parser::ParseError;
+
+/// A picture as one palette index per pixel, where 0 is the background.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<usize>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> usize {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: usize) {
+        self.pixels[y * self.width + x] = colour;
+    }
+
+    pub fn from_dense<T, F: Fn(&T) -> usize>(g: &Dense<T>, f: F) -> Image {
+        Image {
+            width: g.width(),
+            height: g.height(),
+            pixels: g.values().map(f).collect(),
+        }
+    }
+
+    /// The box around every set cell, with `f` getting `None` for the cells
+    /// that were never set.
+    pub fn from_sparse<T, F: Fn(Option<&T>) -> usize>(g: &Sparse<T>, f: F) -> Image {
+        let ((min_x, min_y), (max_x, max_y)) = match g.bounds() {
+            Some(b) => b,
+            None => return Image::new(0, 0),
+        };
+        let mut image = Image::new((max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize);
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                image.set((x - min_x) as usize, (y - min_y) as usize, f(g.get((x, y))));
+            }
+        }
+        return image;
+    }
+
+    /// Colour 1 on every point, in the box from the origin to the furthest
+    /// point. Points left of or above the origin stretch the box that way.
+    pub fn from_points<I: IntoIterator<Item = Pos>>(points: I) -> Image {
+        let points: Vec<Pos> = points.into_iter().collect();
+        let min_x = points.iter().map(|p| p.0).min().unwrap_or(0).min(0);
+        let min_y = points.iter().map(|p| p.1).min().unwrap_or(0).min(0);
+        let width = points.iter().map(|p| p.0 - min_x + 1).max().unwrap_or(0);
+        let height = points.iter().map(|p| p.1 - min_y + 1).max().unwrap_or(0);
+        let mut image = Image::new(width as usize, height as usize);
+        for (x, y) in points {
+            image.set((x - min_x) as usize, (y - min_y) as usize, 1);
+        }
+        return image;
+    }
+}
+
+pub type Rgb = [u8; 3];
+
+/// The colours for each palette index. Indices past the end cycle through
+/// the colours from the third on, so labels like basin numbers stay distinct
+/// from the background and the first foreground colour.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette(Vec<Rgb>);
+
+impl Default for Palette {
+    fn default() -> Palette {
+        Palette(vec![
+            [0xff, 0xff, 0xff],
+            [0x00, 0x00, 0x00],
+            [0xd6, 0x27, 0x28],
+            [0x1f, 0x77, 0xb4],
+            [0x2c, 0xa0, 0x2c],
+            [0xff, 0x7f, 0x0e],
+            [0x94, 0x67, 0xbd],
+            [0x8c, 0x56, 0x4b],
+            [0xe3, 0x77, 0xc2],
+            [0x17, 0xbe, 0xcf],
+        ])
+    }
+}
+
+impl Palette {
+    /// Comma separated `rrggbb` colours, each with an optional `#`, starting
+    /// with the background.
+    pub fn parse(s: &str) -> Result<Palette, String> {
+        let mut colours = vec![];
+        for part in s.split(',') {
+            let hex = part.trim().trim_start_matches('#');
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("bad colour {:?}, expected rrggbb", part));
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+            colours.push([channel(0), channel(2), channel(4)]);
+        }
+        if colours.len() < 2 {
+            return Err("a palette needs a background and at least one colour".to_string());
+        }
+        Ok(Palette(colours))
+    }
+
+    pub fn colour(&self, index: usize) -> Rgb {
+        let n = self.0.len();
+        if index < n {
+            self.0[index]
+        } else if n > 2 {
+            self.0[2 + (index - 2) % (n - 2)]
+        } else {
+            self.0[n - 1]
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Black on white, whatever the palette says.
+    Pbm,
+    Ppm,
+    Svg,
+}
+
+impl Format {
+    pub fn from_path(path: &str) -> Option<Format> {
+        let ext = path.rsplit('.').next()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "pbm" => Some(Format::Pbm),
+            "ppm" => Some(Format::Ppm),
+            "svg" => Some(Format::Svg),
+            _ => None,
+        }
+    }
+}
+
+/// Binary PBM, with every index but the background drawn black.
+pub fn to_pbm(image: &Image, scale: usize) -> Vec<u8> {
+    let (w, h) = (image.width * scale, image.height * scale);
+    let mut out = format!("P4\n{} {}\n", w, h).into_bytes();
+    for y in 0..h {
+        let mut row = vec![0u8; w.div_ceil(8)];
+        for x in 0..w {
+            if image.get(x / scale, y / scale) != 0 {
+                row[x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        out.append(&mut row);
+    }
+    return out;
+}
+
+/// Binary PPM.
+pub fn to_ppm(image: &Image, scale: usize, palette: &Palette) -> Vec<u8> {
+    let (w, h) = (image.width * scale, image.height * scale);
+    let mut out = format!("P6\n{} {}\n255\n", w, h).into_bytes();
+    for y in 0..h {
+        for x in 0..w {
+            out.extend_from_slice(&palette.colour(image.get(x / scale, y / scale)));
+        }
+    }
+    return out;
+}
+
+/// One rectangle per run of same coloured pixels in a row, over a
+/// background rectangle.
+pub fn to_svg(image: &Image, scale: usize, palette: &Palette) -> String {
+    let hex = |c: Rgb| format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2]);
+    let (w, h) = (image.width * scale, image.height * scale);
+    let mut s = String::new();
+    let _ = writeln!(s, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" shape-rendering="crispEdges">"#, w, h);
+    let _ = writeln!(s, r#"<rect width="{}" height="{}" fill="{}"/>"#, w, h, hex(palette.colour(0)));
+    for y in 0..image.height {
+        let mut x = 0;
+        while x < image.width {
+            let colour = image.get(x, y);
+            let start = x;
+            while x < image.width && image.get(x, y) == colour {
+                x += 1;
+            }
+            if colour != 0 {
+                let _ = writeln!(
+                    s,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                    start * scale,
+                    y * scale,
+                    (x - start) * scale,
+                    scale,
+                    hex(palette.colour(colour))
+                );
+            }
+        }
+    }
+    s += "</svg>\n";
+    return s;
+}
+
+pub fn encode(image: &Image, format: Format, scale: usize, palette: &Palette) -> Vec<u8> {
+    match format {
+        Format::Pbm => to_pbm(image, scale),
+        Format::Ppm => to_ppm(image, scale, palette),
+        Format::Svg => to_svg(image, scale, palette).into_bytes(),
+    }
+}
+
+pub type Drawer = fn(&str) -> Result<Image, ParseError>;
+
+/// The days that can be exported.
+pub const EXPORTABLE: &[(u32, Drawer)] = &[
+    (5, p5::image),
+    (9, p9::image),
+    (13, p13::image),
+    (20, p20::image),
+];
+
+pub fn find(day: u32) -> Option<Drawer> {
+    EXPORTABLE.iter().find(|(d, _)| *d == day).map(|(_, f)| *f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 10x2 image, wider than a byte, with three colours.
+    fn sample() -> Image {
+        let mut image = Image::new(10, 2);
+        image.set(0, 0, 1);
+        image.set(8, 0, 2);
+        image.set(9, 1, 1);
+        image.set(1, 1, 3);
+        return image;
+    }
+
+    #[test]
+    fn packs_pbm_rows() {
+        let mut expected = b"P4\n10 2\n".to_vec();
+        expected.extend_from_slice(&[0b1000_0000, 0b1000_0000, 0b0100_0000, 0b0100_0000]);
+        assert_eq!(to_pbm(&sample(), 1), expected);
+
+        let mut scaled = b"P4\n20 4\n".to_vec();
+        let row = [0b1100_0000, 0, 0b1100_0000];
+        let other = [0b0011_0000, 0, 0b0011_0000];
+        for r in [row, row, other, other] {
+            scaled.extend_from_slice(&r);
+        }
+        assert_eq!(to_pbm(&sample(), 2), scaled);
+    }
+
+    #[test]
+    fn writes_ppm_pixels() {
+        let palette = Palette::parse("ffffff,000000,ff0000").unwrap();
+        let out = to_ppm(&sample(), 1, &palette);
+        let header = b"P6\n10 2\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        let pixels = &out[header.len()..];
+        assert_eq!(pixels.len(), 10 * 2 * 3);
+        assert_eq!(&pixels[..6], &[0, 0, 0, 0xff, 0xff, 0xff]);
+        assert_eq!(&pixels[8 * 3..10 * 3], &[0xff, 0, 0, 0xff, 0xff, 0xff]);
+        // Index 3 wraps round to the third colour.
+        assert_eq!(&pixels[11 * 3..12 * 3], &[0xff, 0, 0]);
+    }
+
+    #[test]
+    fn writes_svg_runs() {
+        let mut image = Image::new(9, 1);
+        for x in 2..5 {
+            image.set(x, 0, 1);
+        }
+        image.set(5, 0, 2);
+        let svg = to_svg(&image, 3, &Palette::default());
+        let lines: Vec<&str> = svg.lines().collect();
+        assert_eq!(
+            lines[1..],
+            [
+                r##"<rect width="27" height="3" fill="#ffffff"/>"##,
+                r##"<rect x="6" y="0" width="9" height="3" fill="#000000"/>"##,
+                r##"<rect x="15" y="0" width="3" height="3" fill="#d62728"/>"##,
+                "</svg>",
+            ]
+        );
+        assert!(lines[0].contains(r#"width="27" height="3""#));
+    }
+
+    #[test]
+    fn parses_palettes() {
+        let p = Palette::parse("#102030, a0b0c0").unwrap();
+        assert_eq!((p.colour(0), p.colour(1), p.colour(5)), ([0x10, 0x20, 0x30], [0xa0, 0xb0, 0xc0], [0xa0, 0xb0, 0xc0]));
+        let p = Palette::parse("000000,111111,222222,333333").unwrap();
+        assert_eq!((p.colour(4), p.colour(5)), ([0x22; 3], [0x33; 3]));
+        assert!(Palette::parse("ffffff").is_err());
+        assert!(Palette::parse("ffffff,12345g").is_err());
+        assert!(Palette::parse("ffffff,fff").is_err());
+    }
+
+    #[test]
+    fn boxes_sparse_grids() {
+        let grid: Sparse<usize> = vec![((-2, 5), 1), ((0, 6), 2)].into_iter().collect();
+        let image = Image::from_sparse(&grid, |v| v.map_or(0, |v| *v + 1));
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!((image.get(0, 0), image.get(2, 1), image.get(1, 0)), (2, 3, 0));
+        assert_eq!(Image::from_sparse(&Sparse::<usize>::new(), |_| 1), Image::new(0, 0));
+    }
+
+    #[test]
+    fn draws_points_from_the_origin() {
+        let image = Image::from_points(vec![(1, 1), (3, 0)]);
+        assert_eq!((image.width(), image.height()), (4, 2));
+        assert_eq!((image.get(1, 1), image.get(3, 0), image.get(0, 0)), (1, 1, 0));
+
+        let image = Image::from_points(vec![(-2, 1), (1, -1)]);
+        assert_eq!((image.width(), image.height()), (4, 3));
+        assert_eq!((image.get(0, 2), image.get(3, 0), image.get(2, 1)), (1, 1, 0));
+        assert_eq!(Image::from_points(vec![]), Image::new(0, 0));
+    }
+}
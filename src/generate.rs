@@ -0,0 +1,155 @@
+//! Random puzzle inputs. Every day has a generator that writes an input in
+//! the same format as the real one and with the properties the solvers rely
+//! on, e.g. bingo draws that let every board win or a MONAD program with a
+//! model number to find. The same seed and size always give the same input.
+
+use p1;
+use p10;
+use p11;
+use p12;
+use p13;
+use p14;
+use p15;
+use p16;
+use p17;
+use p18;
+use p19;
+use p2;
+use p20;
+use p21;
+use p22;
+use p23;
+use p24;
+use p3;
+use p4;
+use p5;
+use p6;
+use p7;
+use p8;
+use p9;
+
+/// A small seedable generator (splitmix64), so inputs can be reproduced
+/// without pulling in a dependency.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from `0` up to but not including `n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "nothing below 0");
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    pub fn index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    /// A number from `lo` to `hi`, both included.
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        lo + self.below((hi - lo + 1) as u64) as i64
+    }
+
+    /// True `num` times out of `den`.
+    pub fn chance(&mut self, num: u64, den: u64) -> bool {
+        self.below(den) < num
+    }
+
+    pub fn pick<'a, T>(&mut self, xs: &'a [T]) -> &'a T {
+        &xs[self.index(xs.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, xs: &mut [T]) {
+        for i in (1..xs.len()).rev() {
+            xs.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+/// How to make inputs for a day, and the size that is about as big as the
+/// real puzzle input. What the size counts depends on the day.
+pub struct Generator {
+    pub day: u32,
+    pub size: usize,
+    pub generate: fn(&mut Rng, usize) -> String,
+}
+
+#[rustfmt::skip]
+pub static GENERATORS: &[Generator] = &[
+    Generator { day: 1, size: 2000, generate: p1::generate },
+    Generator { day: 2, size: 1000, generate: p2::generate },
+    Generator { day: 3, size: 1000, generate: p3::generate },
+    Generator { day: 4, size: 100, generate: p4::generate },
+    Generator { day: 5, size: 500, generate: p5::generate },
+    Generator { day: 6, size: 300, generate: p6::generate },
+    Generator { day: 7, size: 1000, generate: p7::generate },
+    Generator { day: 8, size: 200, generate: p8::generate },
+    Generator { day: 9, size: 100, generate: p9::generate },
+    Generator { day: 10, size: 100, generate: p10::generate },
+    Generator { day: 11, size: 10, generate: p11::generate },
+    Generator { day: 12, size: 6, generate: p12::generate },
+    Generator { day: 13, size: 8, generate: p13::generate },
+    Generator { day: 14, size: 20, generate: p14::generate },
+    Generator { day: 15, size: 100, generate: p15::generate },
+    Generator { day: 16, size: 60, generate: p16::generate },
+    Generator { day: 17, size: 1, generate: p17::generate },
+    Generator { day: 18, size: 100, generate: p18::generate },
+    Generator { day: 19, size: 12, generate: p19::generate },
+    Generator { day: 20, size: 100, generate: p20::generate },
+    Generator { day: 21, size: 1, generate: p21::generate },
+    Generator { day: 22, size: 420, generate: p22::generate },
+    Generator { day: 23, size: 1, generate: p23::generate },
+    Generator { day: 24, size: 1, generate: p24::generate },
+];
+
+pub fn find(day: u32) -> Option<&'static Generator> {
+    GENERATORS.iter().find(|g| g.day == day)
+}
+
+/// An input for `day`, at the day's usual size unless one is given.
+pub fn input(day: u32, seed: u64, size: Option<usize>) -> Option<String> {
+    let g = find(day)?;
+    Some((g.generate)(&mut Rng::new(seed), size.unwrap_or(g.size)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use registry;
+    use std::thread;
+
+    /// Every generator at its usual size for a few seeds, a thread per day
+    /// since some of the days are slow without optimisations.
+    #[test]
+    fn generates_inputs_every_day_can_answer() {
+        thread::scope(|s| {
+            for g in GENERATORS {
+                s.spawn(move || {
+                    let day = registry::find(g.day, None).unwrap();
+                    for seed in 0..3 {
+                        let text = input(g.day, seed, None).unwrap();
+                        let parsed = match day.prepare(&text) {
+                            Ok(parsed) => parsed,
+                            Err(e) => panic!("seed {}: {}", seed, e.render(&text)),
+                        };
+                        parsed.part1();
+                        parsed.part2();
+                    }
+                });
+            }
+        });
+    }
+}
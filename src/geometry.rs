@@ -0,0 +1,275 @@
+//! Integer points and boxes. The same point type stands in for positions and
+//! for the offsets between them. Boxes are inclusive on both ends, like the
+//! `x=10..12` ranges the puzzles use.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// The integer types a point can be made of.
+pub trait Scalar:
+    Copy
+    + Ord
+    + fmt::Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn to_i64(self) -> i64;
+
+    fn abs(self) -> Self {
+        if self < Self::ZERO {
+            -self
+        } else {
+            self
+        }
+    }
+
+    fn signum(self) -> Self {
+        match self.cmp(&Self::ZERO) {
+            std::cmp::Ordering::Less => -Self::ONE,
+            std::cmp::Ordering::Equal => Self::ZERO,
+            std::cmp::Ordering::Greater => Self::ONE,
+        }
+    }
+}
+
+impl Scalar for i32 {
+    const ZERO: i32 = 0;
+    const ONE: i32 = 1;
+
+    fn to_i64(self) -> i64 {
+        self as i64
+    }
+}
+
+impl Scalar for i64 {
+    const ZERO: i64 = 0;
+    const ONE: i64 = 1;
+
+    fn to_i64(self) -> i64 {
+        self
+    }
+}
+
+/// Writes out a point type with `$field`s, its operators and a box type
+/// spanning it.
+macro_rules! point {
+    ($point:ident, $boxed:ident, $($field:ident),+) => {
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+        pub struct $point<T = i32> {
+            $(pub $field: T,)+
+        }
+
+        impl<T: Scalar> $point<T> {
+            pub const ZERO: $point<T> = $point { $($field: T::ZERO,)+ };
+
+            pub fn new($($field: T),+) -> $point<T> {
+                $point { $($field,)+ }
+            }
+
+            /// Applies `f` to every coordinate.
+            pub fn map<F: Fn(T) -> T>(self, f: F) -> $point<T> {
+                $point { $($field: f(self.$field),)+ }
+            }
+
+            /// Applies `f` to every pair of matching coordinates.
+            pub fn zip<F: Fn(T, T) -> T>(self, other: $point<T>, f: F) -> $point<T> {
+                $point { $($field: f(self.$field, other.$field),)+ }
+            }
+
+            pub fn scale(self, k: T) -> $point<T> {
+                self.map(|v| v * k)
+            }
+
+            pub fn abs(self) -> $point<T> {
+                self.map(Scalar::abs)
+            }
+
+            /// A step of at most one along every axis towards the same
+            /// direction as this point.
+            pub fn signum(self) -> $point<T> {
+                self.map(Scalar::signum)
+            }
+
+            /// The sum of the coordinates.
+            pub fn sum(self) -> T {
+                T::ZERO $(+ self.$field)+
+            }
+
+            /// The largest coordinate, which is the distance to the origin
+            /// when diagonal steps are allowed.
+            pub fn max_component(self) -> T {
+                let mut m = None;
+                $(m = Some(m.map_or(self.$field, |m: T| m.max(self.$field)));)+
+                m.unwrap()
+            }
+
+            pub fn manhattan(self, other: $point<T>) -> T {
+                (self - other).abs().sum()
+            }
+
+            /// The squared euclidean distance, which stays an integer.
+            pub fn distance_squared(self, other: $point<T>) -> T {
+                let d = self - other;
+                (d * d).sum()
+            }
+
+            pub fn euclidean(self, other: $point<T>) -> f64 {
+                (self.distance_squared(other).to_i64() as f64).sqrt()
+            }
+        }
+
+        impl<T: Scalar> Add for $point<T> {
+            type Output = $point<T>;
+
+            fn add(self, other: $point<T>) -> $point<T> {
+                self.zip(other, |a, b| a + b)
+            }
+        }
+
+        impl<T: Scalar> Sub for $point<T> {
+            type Output = $point<T>;
+
+            fn sub(self, other: $point<T>) -> $point<T> {
+                self.zip(other, |a, b| a - b)
+            }
+        }
+
+        /// Coordinate by coordinate, which flips axes when multiplied by
+        /// a point of ones and minus ones.
+        impl<T: Scalar> Mul for $point<T> {
+            type Output = $point<T>;
+
+            fn mul(self, other: $point<T>) -> $point<T> {
+                self.zip(other, |a, b| a * b)
+            }
+        }
+
+        impl<T: Scalar> Neg for $point<T> {
+            type Output = $point<T>;
+
+            fn neg(self) -> $point<T> {
+                self.map(|v| -v)
+            }
+        }
+
+        impl<T: Scalar> AddAssign for $point<T> {
+            fn add_assign(&mut self, other: $point<T>) {
+                *self = *self + other;
+            }
+        }
+
+        impl<T: Scalar> SubAssign for $point<T> {
+            fn sub_assign(&mut self, other: $point<T>) {
+                *self = *self - other;
+            }
+        }
+
+        impl<T: fmt::Display> fmt::Debug for $point<T> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let parts: Vec<String> = vec![$(self.$field.to_string()),+];
+                write!(f, "({})", parts.join(","))
+            }
+        }
+
+        /// Every point from `min` to `max`, both included.
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $boxed<T = i32> {
+            pub min: $point<T>,
+            pub max: $point<T>,
+        }
+
+        impl<T: fmt::Display> fmt::Debug for $boxed<T> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{:?}..={:?}", self.min, self.max)
+            }
+        }
+
+        impl<T: Scalar> $boxed<T> {
+            /// The box with `a` and `b` as opposite corners, in either order.
+            pub fn new(a: $point<T>, b: $point<T>) -> $boxed<T> {
+                $boxed {
+                    min: a.zip(b, Ord::min),
+                    max: a.zip(b, Ord::max),
+                }
+            }
+
+            pub fn contains(&self, p: $point<T>) -> bool {
+                true $(&& self.min.$field <= p.$field && p.$field <= self.max.$field)+
+            }
+
+            /// Whether all of `other` is inside this box.
+            pub fn contains_box(&self, other: &$boxed<T>) -> bool {
+                self.contains(other.min) && self.contains(other.max)
+            }
+
+            pub fn intersection(&self, other: &$boxed<T>) -> Option<$boxed<T>> {
+                let min = self.min.zip(other.min, Ord::max);
+                let max = self.max.zip(other.max, Ord::min);
+                if true $(&& min.$field <= max.$field)+ {
+                    Some($boxed { min, max })
+                } else {
+                    None
+                }
+            }
+
+            /// The number of points in the box.
+            pub fn volume(&self) -> i64 {
+                1 $(* (self.max.$field.to_i64() - self.min.$field.to_i64() + 1))+
+            }
+        }
+    };
+}
+
+point!(Point2, Rect, x, y);
+point!(Point3, Cuboid, x, y, z);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x1: i32, y1: i32, x2: i32, y2: i32) -> Rect {
+        Rect::new(Point2::new(x1, y1), Point2::new(x2, y2))
+    }
+
+    fn cuboid(a: (i32, i32, i32), b: (i32, i32, i32)) -> Cuboid {
+        Cuboid::new(Point3::new(a.0, a.1, a.2), Point3::new(b.0, b.1, b.2))
+    }
+
+    #[test]
+    fn intersects_rects() {
+        let r = rect(0, 0, 3, 3);
+        assert_eq!(rect(3, 3, 0, 0), r);
+        assert_eq!(r.intersection(&r), Some(r));
+        assert_eq!(r.intersection(&rect(2, 1, 5, 2)), Some(rect(2, 1, 3, 2)));
+        assert_eq!(r.intersection(&rect(3, 3, 9, 9)), Some(rect(3, 3, 3, 3)));
+        assert_eq!(r.intersection(&rect(4, 0, 9, 3)), None);
+        assert_eq!(r.intersection(&rect(0, -2, 3, -1)), None);
+        assert_eq!(r.intersection(&rect(1, 1, 2, 2)), Some(rect(1, 1, 2, 2)));
+        assert!(r.contains_box(&rect(1, 1, 2, 2)) && !rect(1, 1, 2, 2).contains_box(&r));
+    }
+
+    #[test]
+    fn intersects_cuboids() {
+        let c = cuboid((-1, -1, -1), (1, 1, 1));
+        assert_eq!(c.volume(), 27);
+        assert_eq!(c.intersection(&cuboid((1, 1, 1), (5, 5, 5))), Some(cuboid((1, 1, 1), (1, 1, 1))));
+        assert_eq!(c.intersection(&cuboid((1, -1, -1), (5, 1, 1))).map(|i| i.volume()), Some(9));
+        assert_eq!(c.intersection(&cuboid((-1, -1, 2), (1, 1, 5))), None);
+        // Overlapping in two axes is not enough.
+        assert_eq!(c.intersection(&cuboid((0, 0, 5), (0, 0, 6))), None);
+        assert_eq!(c.intersection(&cuboid((-5, -5, -5), (5, 5, 5))), Some(c));
+    }
+
+    #[test]
+    fn measures_big_boxes() {
+        let r: Rect<i64> = Rect::new(Point2::new(-(1 << 40), 0), Point2::new(1 << 40, 0));
+        assert_eq!(r.volume(), (1 << 41) + 1);
+        let c = cuboid((i32::MIN, 0, 0), (i32::MAX, 0, 0));
+        assert_eq!(c.volume(), 1 << 32);
+    }
+}
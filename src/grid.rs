@@ -0,0 +1,363 @@
+//! Two dimensional grids. `Dense` keeps every cell of a rectangle in a `Vec`,
+//! `Sparse` keeps only the cells that were set in a hash map and can grow in any
+//! direction. Positions are `(x, y)` with `x` going right and `y` going down.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use rustc_hash::FxHashMap;
+
+use parser::{self, Cursor, ParseError};
+
+pub type Pos = (i32, i32);
+
+/// Up, left, right and down.
+pub const ORTHOGONAL: [Pos; 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Every position touching another, diagonals included, in reading order.
+pub const SURROUNDING: [Pos; 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// The four positions sharing an edge with `p`.
+pub fn neighbours4(p: Pos) -> impl Iterator<Item = Pos> {
+    ORTHOGONAL.iter().map(move |d| (p.0 + d.0, p.1 + d.1))
+}
+
+/// The eight positions sharing an edge or a corner with `p`.
+pub fn neighbours8(p: Pos) -> impl Iterator<Item = Pos> {
+    SURROUNDING.iter().map(move |d| (p.0 + d.0, p.1 + d.1))
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Dense<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Dense<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Dense<T> {
+        Dense {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Dense<T> {
+    /// Builds a grid out of rows, which all have to be as long as the first.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Dense<T> {
+        let width = rows.first().map_or(0, |r| r.len());
+        let height = rows.len();
+        assert!(rows.iter().all(|r| r.len() == width), "rows of different lengths");
+        Dense {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        0 <= x && (x as usize) < self.width && 0 <= y && (y as usize) < self.height
+    }
+
+    fn offset(&self, p: Pos) -> Option<usize> {
+        if self.contains(p) {
+            Some(p.1 as usize * self.width + p.0 as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, p: Pos) -> Option<&T> {
+        self.offset(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Pos) -> Option<&mut T> {
+        match self.offset(p) {
+            Some(i) => Some(&mut self.cells[i]),
+            None => None,
+        }
+    }
+
+    /// Every position in reading order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width as i32;
+        (0..self.height as i32).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every position and its value in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    /// The positions around `p` that are on the grid, without diagonals.
+    pub fn neighbours4(&self, p: Pos) -> impl Iterator<Item = Pos> + '_ {
+        neighbours4(p).filter(move |n| self.contains(*n))
+    }
+
+    /// The positions around `p` that are on the grid, diagonals included.
+    pub fn neighbours8(&self, p: Pos) -> impl Iterator<Item = Pos> + '_ {
+        neighbours8(p).filter(move |n| self.contains(*n))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Dense<U> {
+        Dense {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// One line per row, with each cell drawn by `f`.
+    pub fn render<F: Fn(Pos, &T) -> String>(&self, f: F) -> String {
+        let mut s = String::new();
+        for (p, v) in self.iter() {
+            if p.0 == 0 && p.1 > 0 {
+                s += "\n";
+            }
+            s += &f(p, v);
+        }
+        return s;
+    }
+}
+
+impl<T> Index<Pos> for Dense<T> {
+    type Output = T;
+
+    fn index(&self, p: Pos) -> &T {
+        match self.offset(p) {
+            Some(i) => &self.cells[i],
+            None => panic!("{:?} is off a {}x{} grid", p, self.width, self.height),
+        }
+    }
+}
+
+impl<T> IndexMut<Pos> for Dense<T> {
+    fn index_mut(&mut self, p: Pos) -> &mut T {
+        match self.offset(p) {
+            Some(i) => &mut self.cells[i],
+            None => panic!("{:?} is off a {}x{} grid", p, self.width, self.height),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Debug for Dense<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\n{}", self.render(|_, v| v.to_string()))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sparse<T> {
+    cells: FxHashMap<Pos, T>,
+}
+
+impl<T> Default for Sparse<T> {
+    fn default() -> Sparse<T> {
+        Sparse { cells: FxHashMap::default() }
+    }
+}
+
+impl<T> Sparse<T> {
+    pub fn new() -> Sparse<T> {
+        Sparse::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, p: Pos) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    pub fn get(&self, p: Pos) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn insert(&mut self, p: Pos, v: T) -> Option<T> {
+        self.cells.insert(p, v)
+    }
+
+    pub fn entry(&mut self, p: Pos) -> std::collections::hash_map::Entry<'_, Pos, T> {
+        self.cells.entry(p)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.cells.keys().copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.cells.iter().map(|(p, v)| (*p, v))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.values()
+    }
+
+    /// The smallest and largest corner of the box around every set cell.
+    pub fn bounds(&self) -> Option<(Pos, Pos)> {
+        let mut ps = self.positions();
+        let first = ps.next()?;
+        Some(ps.fold((first, first), |(lo, hi), p| {
+            ((lo.0.min(p.0), lo.1.min(p.1)), (hi.0.max(p.0), hi.1.max(p.1)))
+        }))
+    }
+
+    /// The box around every set cell, one line per row, with each cell drawn by
+    /// `f`, which gets `None` for the cells that were never set.
+    pub fn render<F: Fn(Pos, Option<&T>) -> String>(&self, f: F) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = match self.bounds() {
+            Some(b) => b,
+            None => return String::new(),
+        };
+        let mut lines = vec![];
+        for y in min_y..=max_y {
+            lines.push((min_x..=max_x).map(|x| f((x, y), self.get((x, y)))).collect::<String>());
+        }
+        return lines.join("\n");
+    }
+}
+
+impl<T> From<Dense<T>> for Sparse<T> {
+    fn from(d: Dense<T>) -> Sparse<T> {
+        Sparse {
+            cells: d.positions().zip(d.cells).collect(),
+        }
+    }
+}
+
+impl<T> std::iter::FromIterator<(Pos, T)> for Sparse<T> {
+    fn from_iter<I: IntoIterator<Item = (Pos, T)>>(iter: I) -> Sparse<T> {
+        Sparse {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Reads a rectangle of characters, one row per line, turning each into a cell
+/// with `f`. Characters `f` turns down are reported as `expected`.
+pub fn parse_cells<T, F>(s: &str, expected: &str, f: F) -> Result<Dense<T>, ParseError>
+where
+    F: Fn(char) -> Option<T>,
+{
+    let mut rows: Vec<Vec<T>> = vec![];
+    for mut c in parser::lines(s.trim_end()) {
+        let row = cells(&mut c, expected, &f)?;
+        if let Some(first) = rows.first() {
+            if row.len() != first.len() {
+                let column = row.len().min(first.len()) + 1;
+                let expected = format!("a row of {} cells", first.len());
+                return Err(ParseError::new(c.line(), column, &expected));
+            }
+        }
+        rows.push(row);
+    }
+    return Ok(Dense::from_rows(rows));
+}
+
+/// The rest of a line as cells.
+pub fn cells<T, F>(c: &mut Cursor, expected: &str, f: F) -> Result<Vec<T>, ParseError>
+where
+    F: Fn(char) -> Option<T>,
+{
+    let mut row = vec![];
+    while let Some(ch) = c.peek() {
+        match f(ch) {
+            Some(v) => row.push(v),
+            None => return Err(c.error(expected)),
+        }
+        c.char_where(|_| true, expected)?;
+    }
+    return Ok(row);
+}
+
+/// A rectangle of single digits like the heightmaps and risk levels.
+pub fn parse_digits(s: &str) -> Result<Dense<i32>, ParseError> {
+    parse_cells(s, "a digit", |c| c.to_digit(10).map(|d| d as i32))
+}
+
+pub fn render_digits(g: &Dense<i32>) -> String {
+    g.render(|_, v| v.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_at_ragged_rows() {
+        let short = parse_digits("123\n12\n123").unwrap_err();
+        assert_eq!(short, ParseError::new(2, 3, "a row of 3 cells"));
+        let long = parse_digits("12\n123").unwrap_err();
+        assert_eq!(long, ParseError::new(2, 3, "a row of 2 cells"));
+        let bad = parse_digits("12\n1x").unwrap_err();
+        assert_eq!(bad, ParseError::new(2, 2, "a digit"));
+    }
+
+    #[test]
+    fn parses_and_renders_digits() {
+        let g = parse_digits("123\n456\n").unwrap();
+        assert_eq!((g.width(), g.height()), (3, 2));
+        assert_eq!(g[(2, 1)], 6);
+        assert_eq!(g.get((3, 0)), None);
+        assert_eq!(render_digits(&g), "123\n456");
+        let empty = parse_digits("").unwrap();
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+    }
+
+    #[test]
+    fn bounds_the_set_cells() {
+        let mut g: Sparse<char> = Sparse::new();
+        assert_eq!(g.bounds(), None);
+        assert_eq!(g.render(|_, _| "#".to_string()), "");
+
+        g.insert((-1, 2), 'a');
+        assert_eq!(g.bounds(), Some(((-1, 2), (-1, 2))));
+        g.insert((1, 0), 'b');
+        assert_eq!(g.bounds(), Some(((-1, 0), (1, 2))));
+        assert_eq!(g.render(|_, c| c.map_or(".".to_string(), |c| c.to_string())), "..b\n...\na..");
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+
+/// Where a day's input comes from: one of the files in `inputs/` picked by
+/// name (`puzzle`, `sample`, `sample2`, ...), a path, or stdin.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Named(String),
+    Path(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    /// Reads a `--input` argument: `-` is stdin, anything that exists on disk
+    /// is a path, everything else names a file in `inputs/`.
+    pub fn from_arg(arg: &str) -> Source {
+        if arg == "-" {
+            return Source::Stdin;
+        }
+        let path = PathBuf::from(arg);
+        if path.is_file() {
+            return Source::Path(path);
+        }
+        return Source::Named(arg.to_string());
+    }
+
+    fn path(&self, day: u32) -> Option<PathBuf> {
+        match self {
+            Source::Named(name) => Some(named_path(day, name)),
+            Source::Path(path) => Some(path.clone()),
+            Source::Stdin => None,
+        }
+    }
+
+    pub fn describe(&self, day: u32) -> String {
+        match self.path(day) {
+            Some(path) => path.display().to_string(),
+            None => "stdin".to_string(),
+        }
+    }
+}
+
+pub fn inputs_dir() -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/inputs"))
+}
+
+/// `inputs/day{day}{name}.txt`, e.g. `inputs/day9sample.txt`.
+pub fn named_path(day: u32, name: &str) -> PathBuf {
+    inputs_dir().join(format!("day{}{}.txt", day, name))
+}
+
+pub fn load(day: u32, source: &Source) -> io::Result<String> {
+    match source.path(day) {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut s = String::new();
+            io::stdin().read_to_string(&mut s)?;
+            Ok(s)
+        }
+    }
+}
+
+/// Opens the input for reading a line at a time, for solvers that stream it
+/// rather than load it whole.
+pub fn open(day: u32, source: &Source) -> io::Result<Box<dyn BufRead>> {
+    match source.path(day) {
+        Some(path) => Ok(Box::new(BufReader::new(fs::File::open(path)?))),
+        None => Ok(Box::new(BufReader::new(io::stdin()))),
+    }
+}
+
+/// Loads one of the files in `inputs/` by name.
+pub fn named(day: u32, name: &str) -> io::Result<String> {
+    load(day, &Source::Named(name.to_string()))
+}
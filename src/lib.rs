@@ -0,0 +1,64 @@
+//! Advent of Code 2021. Every day is a module, `p1` to `p24`, with its
+//! parser, the functions that answer each part and a `Solver` tying them
+//! together; days solved more than one way have extra modules like
+//! `p16_take2`. `registry` lists every solver, and the other modules hold
+//! what the days share (grids, geometry, parsing, memo tables) and what the
+//! `adoc` command line is built from.
+
+#![allow(clippy::needless_return)]
+
+extern crate colored; // not needed in Rust 2018
+extern crate rustc_hash; // not needed in Rust 2018
+extern crate rayon;
+extern crate maplit;
+extern crate itertools;
+extern crate num_format;
+extern crate nom;
+#[cfg(test)]
+extern crate proptest;
+
+#[macro_use]
+pub mod log;
+pub mod animate;
+pub mod bench;
+pub mod differential;
+pub mod export;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod manifest;
+pub mod memo;
+pub mod ocr;
+pub mod parallel;
+pub mod parser;
+pub mod p1;
+pub mod p2;
+pub mod p3;
+pub mod p3radix;
+pub mod p4;
+pub mod p5;
+pub mod p6;
+pub mod p7;
+pub mod p8;
+pub mod p9;
+pub mod p10;
+pub mod p11;
+pub mod p12;
+pub mod p13;
+pub mod p14;
+pub mod p15;
+pub mod p16;
+pub mod p16_take2;
+pub mod p17;
+pub mod p18;
+pub mod p19;
+pub mod p20;
+pub mod p21;
+pub mod p22;
+pub mod p22cc;
+pub mod p23;
+pub mod p24;
+pub mod registry;
+pub mod solution;
+pub mod verify;
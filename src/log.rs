@@ -0,0 +1,242 @@
+//! Leveled logging for the solvers. Everything goes to stderr and is off below
+//! `warn` unless asked for, so solvers are silent by default.
+//!
+//! What gets logged is picked with a spec like `debug` or `warn,16=trace,p9=debug`:
+//! a bare level sets the default, and `target=level` overrides it for one module
+//! (`p16_take2`) or for every module of a day (`16`).
+
+use std::env;
+use std::fmt;
+use std::sync::OnceLock;
+
+use colored::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn from_name(s: &str) -> Option<Level> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Some(Level::Off),
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+
+    /// The level `-v` given `n` times asks for.
+    pub fn from_verbosity(n: usize) -> Level {
+        match n {
+            0 => Level::Warn,
+            1 => Level::Info,
+            2 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+
+    fn label(self) -> ColoredString {
+        match self {
+            Level::Off => "".normal(),
+            Level::Error => "ERROR".red(),
+            Level::Warn => "WARN".yellow(),
+            Level::Info => "INFO".green(),
+            Level::Debug => "DEBUG".blue(),
+            Level::Trace => "TRACE".dimmed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Day(u32),
+    Module(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    default: Level,
+    rules: Vec<(Target, Level)>,
+}
+
+impl Default for Filter {
+    fn default() -> Filter {
+        Filter { default: Level::Warn, rules: vec![] }
+    }
+}
+
+/// The day a solver module belongs to: `p16_take2` is day 16.
+fn module_day(module: &str) -> Option<u32> {
+    let digits: String = module
+        .strip_prefix('p')?
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+impl Filter {
+    pub fn parse(spec: &str) -> Result<Filter, String> {
+        let mut filter = Filter::default();
+        for part in spec.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let (target, level) = match part.split_once('=') {
+                Some((target, level)) => (Some(target), level),
+                None => (None, part),
+            };
+            let level = Level::from_name(level).ok_or(format!("unknown log level {:?}", level))?;
+            match target {
+                None => filter.default = level,
+                Some(t) => {
+                    let t = t.strip_prefix("day").unwrap_or(t);
+                    let target = match t.parse::<u32>() {
+                        Ok(day) => Target::Day(day),
+                        Err(_) => Target::Module(t.to_string()),
+                    };
+                    filter.rules.push((target, level));
+                }
+            }
+        }
+        return Ok(filter);
+    }
+
+    /// The same filter with a different default level.
+    pub fn with_default(self, default: Level) -> Filter {
+        Filter { default, ..self }
+    }
+
+    /// The most verbose level `module` logs at. Module rules beat day rules,
+    /// which beat the default.
+    pub fn level(&self, module: &str) -> Level {
+        let name = module.rsplit("::").next().unwrap_or(module);
+        let by_module = self.rules.iter().rev().find(|(t, _)| match t {
+            Target::Module(m) => m == name,
+            Target::Day(_) => false,
+        });
+        if let Some((_, level)) = by_module {
+            return *level;
+        }
+        let day = module_day(name);
+        let by_day = self.rules.iter().rev().find(|(t, _)| match t {
+            Target::Day(d) => Some(*d) == day,
+            Target::Module(_) => false,
+        });
+        match by_day {
+            Some((_, level)) => *level,
+            None => self.default,
+        }
+    }
+}
+
+static FILTER: OnceLock<Filter> = OnceLock::new();
+
+/// Sets the filter for the rest of the run. Only the first call has any effect.
+pub fn init(filter: Filter) {
+    let _ = FILTER.set(filter);
+}
+
+/// The filter from `ADOC_LOG`, or the default if it is unset.
+pub fn from_env() -> Result<Filter, String> {
+    match env::var("ADOC_LOG") {
+        Ok(spec) => Filter::parse(&spec).map_err(|e| format!("ADOC_LOG: {}", e)),
+        Err(_) => Ok(Filter::default()),
+    }
+}
+
+pub fn enabled(level: Level, module: &str) -> bool {
+    level != Level::Off && level <= FILTER.get_or_init(Filter::default).level(module)
+}
+
+pub fn write(level: Level, module: &str, args: fmt::Arguments) {
+    let name = module.rsplit("::").next().unwrap_or(module);
+    eprintln!("{:<5} {}: {}", level.label(), name, args);
+}
+
+/// Logs at `level` from the calling module. The message is only formatted
+/// when the filter lets it through, so it is fine to log in hot loops.
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::log::enabled($level, module_path!()) {
+            $crate::log::write($level, module_path!(), format_args!($($arg)+));
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_day_of_a_module() {
+        assert_eq!(module_day("p16_take2"), Some(16));
+        assert_eq!(module_day("p3radix"), Some(3));
+        assert_eq!(module_day("parser"), None);
+        assert_eq!(module_day("p"), None);
+    }
+
+    #[test]
+    fn picks_levels_by_module_then_day() {
+        let f = Filter::parse("info, 16=trace, p9=debug, day3=error, p3radix=off").unwrap();
+        assert_eq!(f.level("adoc::p16"), Level::Trace);
+        assert_eq!(f.level("adoc::p16_take2"), Level::Trace);
+        assert_eq!(f.level("adoc::p9"), Level::Debug);
+        assert_eq!(f.level("adoc::p3"), Level::Error);
+        assert_eq!(f.level("adoc::p3radix"), Level::Off);
+        assert_eq!(f.level("adoc::p160"), Level::Info);
+        assert_eq!(f.level("adoc::verify"), Level::Info);
+
+        let f = Filter::parse("p16_take2=trace,16=info").unwrap();
+        assert_eq!((f.level("p16_take2"), f.level("p16")), (Level::Trace, Level::Info));
+        let f = Filter::parse("p9=debug,p9=ERROR,DEBUG").unwrap();
+        assert_eq!((f.level("p9"), f.level("p1")), (Level::Error, Level::Debug));
+    }
+
+    #[test]
+    fn defaults_to_warn() {
+        assert_eq!(Filter::parse("").unwrap(), Filter::default());
+        assert_eq!(Filter::default().level("p1"), Level::Warn);
+        let f = Filter::parse("p9=trace").unwrap().with_default(Level::Off);
+        assert_eq!((f.level("p9"), f.level("p1")), (Level::Trace, Level::Off));
+    }
+
+    #[test]
+    fn rejects_bad_specs() {
+        assert_eq!(Filter::parse("loud"), Err("unknown log level \"loud\"".to_string()));
+        assert_eq!(Filter::parse("warn,p9=").unwrap_err(), "unknown log level \"\"");
+        assert!(Filter::parse("p9=debug=trace").is_err());
+    }
+}
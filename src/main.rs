@@ -1,22 +1,702 @@
-#![feature(box_patterns)]
-#![feature(str_split_once)]
-#![feature(destructuring_assignment)]
-extern crate colored; // not needed in Rust 2018
-extern crate rustc_hash; // not needed in Rust 2018
-extern crate rayon;
-extern crate cached;
-#[macro_use] extern crate maplit;
-extern crate itertools;
-extern crate num_format;
-
-//pub mod p22;
-//pub mod p22cc;
-//pub mod p23;
-pub mod p24;
+#![allow(clippy::needless_return)]
+
+#[macro_use]
+extern crate adoc;
+
+use std::env;
+use std::process;
+use std::time::{Duration, Instant};
+
+use adoc::input::{self, Source};
+use adoc::{animate, bench, differential, export, generate, log, manifest, p1, p2, parallel, registry, verify};
+
+const USAGE: &str = "usage:
+    adoc [-v|-vv|-vvv] [--log <spec>] <command> ...
+
+    adoc list
+    adoc run <day> [--part 1|2] [--variant <name>] [--input <name>|<path>|-]
+             [--export <file>.pbm|.ppm|.svg [--scale <n>] [--palette <rrggbb,...>]]
+    adoc run --all [--part 1|2] [--input <name>] [--threads <n>] [--format table|json]
+    adoc verify [<day>]
+    adoc bench <day>|--all [--variant <name>] [--input <name>|<path>|-] [--runs <n>]
+               [--format table|csv|json] [--save <file>] [--baseline <file>] [--threshold <percent>]
+    adoc animate 4|6|11|20 [--input <name>|<path>|-] [--delay <ms>] [--steps <from>..<to>|<step>]
+    adoc generate <day> [--seed <n>] [--size <n>]
+    adoc diff <day>|--all [--cases <n>] [--seed <n>]
+    adoc sonar [--window <n>] [--positions] [--input <name>|<path>|-]
+    adoc dive [--model depth|aim] [--input <name>|<path>|-]
+
+inputs are read from inputs/day<N><name>.txt, where <name> defaults to puzzle
+
+--export draws days 5, 9, 13 and 20 as pictures; the palette starts with the background
+
+run --all runs every day at once, one thread per core unless --threads says otherwise,
+and prints each day's answers, time and any panic in a table or as JSON
+
+generate writes a random input to stdout, e.g. `adoc generate 5 | adoc run 5 --input -`
+
+diff runs every implementation of a day on its stored inputs and --cases generated ones
+(20 by default) and prints the first input they disagree on, cut down
+
+sonar streams day 1 depths a line at a time and counts the windows of --window depths
+(1 by default) whose sum went up, listing where each one starts with --positions
+
+dive prints every position of the day 2 submarine as CSV, steered by depth or by aim
+(the default)
+
+solvers log to stderr at warn and above; -v raises that to info, debug or trace,
+and --log (or ADOC_LOG) takes a spec like `warn,16=trace,p9=debug`";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{}\n\n{}", msg, USAGE);
+    process::exit(2);
+}
+
+fn list() {
+    for d in registry::DAYS {
+        println!("{}", d.name());
+    }
+}
+
+/// Reads the input a solver should run on, which is its default one unless
+/// another was asked for.
+fn load_input(d: &registry::Day, source: &Option<Source>) -> Result<(Source, String), String> {
+    let source = match source {
+        Some(source) => source.clone(),
+        None => Source::Named(d.inputs[0].to_string()),
+    };
+    match input::load(d.day, &source) {
+        Ok(text) => Ok((source, text)),
+        Err(e) => Err(format!("could not read {}: {}", source.describe(d.day), e)),
+    }
+}
+
+/// Whether `--all` should skip a solver because it has no such named input.
+fn skip_for_all(d: &registry::Day, source: &Option<Source>) -> bool {
+    if let Some(Source::Named(name)) = source {
+        if !d.inputs.contains(&name.as_str()) {
+            eprintln!("skipping {}: no {} input", d.name(), name);
+            return true;
+        }
+    }
+    return false;
+}
+
+fn run_day(d: &registry::Day, part: Option<usize>, source: &Option<Source>) -> Result<(), String> {
+    let (_, text) = load_input(d, source)?;
+    let parsed = d.prepare(&text).map_err(|e| e.render(&text))?;
+    if part != Some(2) {
+        println!("Part 1: {}", parsed.part1());
+    }
+    if part != Some(1) {
+        println!("Part 2: {}", parsed.part2());
+    }
+    Ok(())
+}
+
+fn run(args: &[String]) {
+    let mut day: Option<u32> = None;
+    let mut part: Option<usize> = None;
+    let mut variant: Option<&str> = None;
+    let mut source: Option<Source> = None;
+    let mut all = false;
+    let mut export: Option<(&str, export::Format)> = None;
+    let mut scale = 1;
+    let mut palette = export::Palette::default();
+    let mut threads: Option<usize> = None;
+    let mut format: Option<&str> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--all" => all = true,
+            "--export" => {
+                i += 1;
+                export = match args.get(i) {
+                    Some(path) => match export::Format::from_path(path) {
+                        Some(format) => Some((path, format)),
+                        None => fail("--export writes .pbm, .ppm or .svg files"),
+                    },
+                    None => fail("--export takes a file"),
+                };
+            }
+            "--scale" => {
+                i += 1;
+                scale = match args.get(i).map(|s| s.parse::<usize>()) {
+                    Some(Ok(n)) if n > 0 => n,
+                    _ => fail("--scale takes a positive number"),
+                };
+            }
+            "--palette" => {
+                i += 1;
+                palette = match args.get(i).map(|s| export::Palette::parse(s)) {
+                    Some(Ok(p)) => p,
+                    Some(Err(e)) => fail(&e),
+                    None => fail("--palette takes a list of colours"),
+                };
+            }
+            "--threads" => {
+                i += 1;
+                threads = match args.get(i).map(|s| s.parse::<usize>()) {
+                    Some(Ok(n)) if n > 0 => Some(n),
+                    _ => fail("--threads takes a positive number"),
+                };
+            }
+            "--format" => {
+                i += 1;
+                format = match args.get(i).map(|s| s.as_str()) {
+                    Some(f @ "table") | Some(f @ "json") => Some(f),
+                    _ => fail("--format takes table or json"),
+                };
+            }
+            "--part" => {
+                i += 1;
+                part = match args.get(i).map(|s| s.as_str()) {
+                    Some("1") => Some(1),
+                    Some("2") => Some(2),
+                    _ => fail("--part takes 1 or 2"),
+                };
+            }
+            "--variant" => {
+                i += 1;
+                match args.get(i) {
+                    Some(v) => variant = Some(v),
+                    None => fail("--variant takes a name"),
+                }
+            }
+            "--input" => {
+                i += 1;
+                match args.get(i) {
+                    Some(s) => source = Some(Source::from_arg(s)),
+                    None => fail("--input takes a name, a path or -"),
+                }
+            }
+            d => match d.parse::<u32>() {
+                Ok(n) if day.is_none() => day = Some(n),
+                _ => fail(&format!("unexpected argument: {}", d)),
+            },
+        }
+        i += 1;
+    }
+
+    if all {
+        if day.is_some() || variant.is_some() {
+            fail("--all cannot be combined with a day or --variant");
+        }
+        if export.is_some() {
+            fail("--all cannot be combined with --export");
+        }
+        if let Some(Source::Path(_)) | Some(Source::Stdin) = source {
+            fail("--all only takes a named --input");
+        }
+        run_all(part, &source, threads, format.unwrap_or("table"));
+        return;
+    }
+    if threads.is_some() || format.is_some() {
+        fail("--threads and --format only go with --all");
+    }
+
+    let day = match day {
+        Some(day) => day,
+        None => fail("run needs a day or --all"),
+    };
+    let drawer = export.map(|_| match export::find(day) {
+        Some(drawer) => drawer,
+        None => fail(&format!("day {} cannot be exported", day)),
+    });
+    match registry::find(day, variant) {
+        Some(d) => {
+            if let Err(e) = run_day(d, part, &source) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+            if let (Some((path, format)), Some(drawer)) = (export, drawer) {
+                if let Err(e) = export_day(d, drawer, &source, path, format, scale, &palette) {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        None => fail(&format!("no solver registered for day {} {}", day, variant.unwrap_or(""))),
+    }
+}
+
+/// Runs every day's default solver in parallel and prints a summary, exiting
+/// non-zero if any of them panicked or could not parse its input.
+fn run_all(part: Option<usize>, source: &Option<Source>, threads: Option<usize>, format: &str) {
+    let mut jobs = vec![];
+    for d in registry::DAYS.iter().filter(|d| d.variant.is_none()) {
+        if skip_for_all(d, source) {
+            continue;
+        }
+        match load_input(d, source) {
+            Ok((source, text)) => {
+                let input = match source {
+                    Source::Named(name) => name,
+                    other => other.describe(d.day),
+                };
+                jobs.push(parallel::Job { day: d, input, text });
+            }
+            Err(e) => eprintln!("skipping {}: {}", d.name(), e),
+        }
+    }
+    let start = Instant::now();
+    let reports = match parallel::run_all(&jobs, part, threads) {
+        Ok(reports) => reports,
+        Err(e) => fail(&e),
+    };
+    match format {
+        "json" => print!("{}", parallel::to_json(&reports)),
+        _ => parallel::print_table(&reports, start.elapsed()),
+    }
+    if reports.iter().any(|r| r.outcome != parallel::Outcome::Answered) {
+        process::exit(1);
+    }
+}
+
+fn export_day(
+    d: &registry::Day,
+    drawer: export::Drawer,
+    source: &Option<Source>,
+    path: &str,
+    format: export::Format,
+    scale: usize,
+    palette: &export::Palette,
+) -> Result<(), String> {
+    let (_, text) = load_input(d, source)?;
+    let image = drawer(&text).map_err(|e| e.in_day(d.day).render(&text))?;
+    let bytes = export::encode(&image, format, scale, palette);
+    std::fs::write(path, bytes).map_err(|e| format!("could not write {}: {}", path, e))?;
+    info!("wrote a {}x{} picture to {}", image.width() * scale, image.height() * scale, path);
+    Ok(())
+}
+
+fn verify(args: &[String]) {
+    let day = match args {
+        [] => None,
+        [d] => match d.parse::<u32>() {
+            Ok(n) => Some(n),
+            Err(_) => fail(&format!("unexpected argument: {}", d)),
+        },
+        _ => fail("verify takes at most one day"),
+    };
+    let expected = match manifest::load() {
+        Ok(expected) => expected,
+        Err(e) => fail(&e),
+    };
+
+    let mut checks = vec![];
+    for d in registry::DAYS.iter().filter(|d| day.is_none() || day == Some(d.day)) {
+        checks.append(&mut verify::check_day(d, &expected));
+    }
+    checks.append(&mut verify::check_missing(day, &expected));
+    verify::print_table(&checks);
+
+    let passed = checks.iter().filter(|c| c.status == verify::Status::Pass).count();
+    println!("\n{} of {} passed", passed, checks.len());
+    if passed != checks.len() {
+        process::exit(1);
+    }
+}
+
+fn bench(args: &[String]) {
+    let mut day: Option<u32> = None;
+    let mut variant: Option<&str> = None;
+    let mut source: Option<Source> = None;
+    let mut all = false;
+    let mut runs = 10;
+    let mut format = "table";
+    let mut save: Option<&str> = None;
+    let mut baseline: Option<&str> = None;
+    let mut threshold = 10.0;
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = || match args.get(i + 1) {
+            Some(v) => v.as_str(),
+            None => fail(&format!("{} takes a value", flag)),
+        };
+        match flag {
+            "--all" => all = true,
+            "--variant" => variant = Some(value()),
+            "--input" => source = Some(Source::from_arg(value())),
+            "--runs" => match value().parse::<usize>() {
+                Ok(n) if n > 0 => runs = n,
+                _ => fail("--runs takes a positive number"),
+            },
+            "--format" => match value() {
+                f @ "table" | f @ "csv" | f @ "json" => format = f,
+                _ => fail("--format takes table, csv or json"),
+            },
+            "--save" => save = Some(value()),
+            "--baseline" => baseline = Some(value()),
+            "--threshold" => match value().parse::<f64>() {
+                Ok(t) => threshold = t,
+                Err(_) => fail("--threshold takes a percentage"),
+            },
+            d => match d.parse::<u32>() {
+                Ok(n) if day.is_none() => day = Some(n),
+                _ => fail(&format!("unexpected argument: {}", d)),
+            },
+        }
+        if flag.starts_with("--") && flag != "--all" {
+            i += 1;
+        }
+        i += 1;
+    }
+
+    let solvers: Vec<&registry::Day> = if all {
+        if day.is_some() || variant.is_some() {
+            fail("--all cannot be combined with a day or --variant");
+        }
+        if let Some(Source::Path(_)) | Some(Source::Stdin) = source {
+            fail("--all only takes a named --input");
+        }
+        registry::DAYS.iter().filter(|d| !skip_for_all(d, &source)).collect()
+    } else {
+        let day = match day {
+            Some(day) => day,
+            None => fail("bench needs a day or --all"),
+        };
+        match registry::find(day, variant) {
+            Some(d) => vec![d],
+            None => fail(&format!("no solver registered for day {} {}", day, variant.unwrap_or(""))),
+        }
+    };
+
+    let mut timings = vec![];
+    for d in solvers {
+        match load_input(d, &source) {
+            Ok((source, text)) => {
+                let name = match source {
+                    Source::Named(name) => name,
+                    other => other.describe(d.day),
+                };
+                match bench::bench_day(d, &name, &text, runs) {
+                    Ok(mut t) => timings.append(&mut t),
+                    Err(e) if all => eprintln!("skipping {}: {}", d.name(), e),
+                    Err(e) => {
+                        eprintln!("{}", e.render(&text));
+                        process::exit(1);
+                    }
+                }
+            }
+            Err(e) if all => eprintln!("skipping {}: {}", d.name(), e),
+            Err(e) => fail(&e),
+        }
+    }
+
+    if let Some(path) = save {
+        if let Err(e) = std::fs::write(path, bench::to_csv(&timings)) {
+            fail(&format!("could not write {}: {}", path, e));
+        }
+    }
+    let changes = baseline.map(|path| match bench::load_baseline(path) {
+        Ok(b) => bench::compare(&timings, &b),
+        Err(e) => fail(&e),
+    });
+    let threshold = threshold / 100.0;
+    let regressed = match format {
+        "csv" => {
+            print!("{}", bench::to_csv(&timings));
+            changes.is_some_and(|c| bench::report_regressions(&timings, &c, threshold))
+        }
+        "json" => {
+            print!("{}", bench::to_json(&timings));
+            changes.is_some_and(|c| bench::report_regressions(&timings, &c, threshold))
+        }
+        _ => bench::print_table(&timings, changes.as_deref(), threshold),
+    };
+    if regressed {
+        process::exit(1);
+    }
+}
+
+fn animate(args: &[String]) {
+    let mut day: Option<u32> = None;
+    let mut source: Option<Source> = None;
+    let mut delay = 100;
+    let mut steps = (0, None);
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = || match args.get(i + 1) {
+            Some(v) => v.as_str(),
+            None => fail(&format!("{} takes a value", flag)),
+        };
+        match flag {
+            "--input" => source = Some(Source::from_arg(value())),
+            "--delay" => match value().parse::<u64>() {
+                Ok(ms) => delay = ms,
+                Err(_) => fail("--delay takes milliseconds"),
+            },
+            "--steps" => match animate::parse_steps(value()) {
+                Some(s) => steps = s,
+                None => fail("--steps takes a range like 10..50, 10.. or ..50, or a single step"),
+            },
+            d => match d.parse::<u32>() {
+                Ok(n) if day.is_none() => day = Some(n),
+                _ => fail(&format!("unexpected argument: {}", d)),
+            },
+        }
+        if flag.starts_with("--") {
+            i += 1;
+        }
+        i += 1;
+    }
+
+    let day = match day {
+        Some(day) => day,
+        None => fail("animate needs a day"),
+    };
+    let (animator, d) = match (animate::find(day), registry::find(day, None)) {
+        (Some(a), Some(d)) => (a, d),
+        _ => fail(&format!("day {} cannot be animated", day)),
+    };
+    let (_, text) = match load_input(d, &source) {
+        Ok(loaded) => loaded,
+        Err(e) => fail(&e),
+    };
+    let mut anim = animate::Animation::new(Duration::from_millis(delay), steps.0, steps.1);
+    if let Err(e) = animator(&text, &mut anim) {
+        eprintln!("{}", e.in_day(day).render(&text));
+        process::exit(1);
+    }
+}
+
+fn generate(args: &[String]) {
+    let mut day: Option<u32> = None;
+    let mut seed = 0;
+    let mut size: Option<usize> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = || match args.get(i + 1) {
+            Some(v) => v.as_str(),
+            None => fail(&format!("{} takes a value", flag)),
+        };
+        match flag {
+            "--seed" => match value().parse::<u64>() {
+                Ok(n) => seed = n,
+                Err(_) => fail("--seed takes a number"),
+            },
+            "--size" => match value().parse::<usize>() {
+                Ok(n) => size = Some(n),
+                Err(_) => fail("--size takes a number"),
+            },
+            d => match d.parse::<u32>() {
+                Ok(n) if day.is_none() => day = Some(n),
+                _ => fail(&format!("unexpected argument: {}", d)),
+            },
+        }
+        if flag.starts_with("--") {
+            i += 1;
+        }
+        i += 1;
+    }
+
+    let day = match day {
+        Some(day) => day,
+        None => fail("generate needs a day"),
+    };
+    match generate::input(day, seed, size) {
+        Some(text) => println!("{}", text),
+        None => fail(&format!("no generator for day {}", day)),
+    }
+}
+
+fn diff(args: &[String]) {
+    let mut day: Option<u32> = None;
+    let mut all = false;
+    let mut plan = differential::Plan { cases: 20, seed: 0 };
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = || match args.get(i + 1) {
+            Some(v) => v.as_str(),
+            None => fail(&format!("{} takes a value", flag)),
+        };
+        match flag {
+            "--all" => all = true,
+            "--cases" => match value().parse::<usize>() {
+                Ok(n) => plan.cases = n,
+                Err(_) => fail("--cases takes a number"),
+            },
+            "--seed" => match value().parse::<u64>() {
+                Ok(n) => plan.seed = n,
+                Err(_) => fail("--seed takes a number"),
+            },
+            d => match d.parse::<u32>() {
+                Ok(n) if day.is_none() => day = Some(n),
+                _ => fail(&format!("unexpected argument: {}", d)),
+            },
+        }
+        if flag.starts_with("--") && flag != "--all" {
+            i += 1;
+        }
+        i += 1;
+    }
+
+    let mut comparisons = differential::comparisons();
+    if !all {
+        let day = match day {
+            Some(day) => day,
+            None => fail("diff needs a day or --all"),
+        };
+        comparisons.retain(|c| c.day == day);
+        if comparisons.is_empty() {
+            fail(&format!("day {} has only one implementation", day));
+        }
+    }
+    let mut disagreed = false;
+    for c in &comparisons {
+        match differential::find_disagreement(c, plan) {
+            Some(d) => {
+                differential::print_disagreement(c, &d);
+                disagreed = true;
+            }
+            None => {
+                let names: Vec<&str> = c.implementations.iter().map(|i| i.name.as_str()).collect();
+                println!("{}: {} agree\n", c.name(), names.join(", "));
+            }
+        }
+    }
+    if disagreed {
+        process::exit(1);
+    }
+}
+
+fn sonar(args: &[String]) {
+    let mut size = 1;
+    let mut positions = false;
+    let mut source = Source::Named("puzzle".to_string());
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = || match args.get(i + 1) {
+            Some(v) => v.as_str(),
+            None => fail(&format!("{} takes a value", flag)),
+        };
+        match flag {
+            "--positions" => positions = true,
+            "--input" => source = Source::from_arg(value()),
+            "--window" => match value().parse::<usize>() {
+                Ok(n) if n > 0 => size = n,
+                _ => fail("--window takes a positive number"),
+            },
+            d => fail(&format!("unexpected argument: {}", d)),
+        }
+        if flag.starts_with("--") && flag != "--positions" {
+            i += 1;
+        }
+        i += 1;
+    }
+
+    let reader = match input::open(1, &source) {
+        Ok(reader) => reader,
+        Err(e) => fail(&format!("could not read {}: {}", source.describe(1), e)),
+    };
+    let counted = p1::stream_increases(reader, size, |p| {
+        if positions {
+            println!("{}", p);
+        }
+    });
+    match counted {
+        Ok(count) => println!("{} increases", count),
+        Err(e) => {
+            eprintln!("{}: {}", source.describe(1), e);
+            process::exit(1);
+        }
+    }
+}
+
+fn dive(args: &[String]) {
+    let mut model = "aim";
+    let mut source: Option<Source> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = || match args.get(i + 1) {
+            Some(v) => v.as_str(),
+            None => fail(&format!("{} takes a value", flag)),
+        };
+        match flag {
+            "--model" => model = value(),
+            "--input" => source = Some(Source::from_arg(value())),
+            d => fail(&format!("unexpected argument: {}", d)),
+        }
+        i += 2;
+    }
+
+    let model = match p2::find_model(model) {
+        Some(m) => m,
+        None => fail("--model takes depth or aim"),
+    };
+    let d = registry::find(2, None).expect("day 2 has a solver");
+    let (_, text) = match load_input(d, &source) {
+        Ok(loaded) => loaded,
+        Err(e) => fail(&e),
+    };
+    match p2::parse_data(&text) {
+        Ok(commands) => print!("{}", p2::trace_csv(&commands, &p2::trace(model, &commands))),
+        Err(e) => {
+            eprintln!("{}", e.in_day(2).render(&text));
+            process::exit(1);
+        }
+    }
+}
+
+/// Takes the logging flags off the front of the arguments and sets up the
+/// filter they ask for, returning the rest.
+fn init_logging(mut args: Vec<String>) -> Vec<String> {
+    let mut filter = match log::from_env() {
+        Ok(f) => f,
+        Err(e) => fail(&e),
+    };
+    let mut verbosity = 0;
+    while let Some(arg) = args.first() {
+        if arg == "--log" {
+            let spec = match args.get(1) {
+                Some(spec) => spec,
+                None => fail("--log needs a value"),
+            };
+            filter = match log::Filter::parse(spec) {
+                Ok(f) => f,
+                Err(e) => fail(&e),
+            };
+            args.drain(..2);
+        } else if arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|c| c == 'v') {
+            verbosity += arg.len() - 1;
+            args.remove(0);
+        } else {
+            break;
+        }
+    }
+    if verbosity > 0 {
+        filter = filter.with_default(log::Level::from_verbosity(verbosity));
+    }
+    log::init(filter);
+    return args;
+}
 
 fn main() {
-    //p22::main();
-   //p22cc::main();
-   //p23::main()
-   p24::main();
-}
\ No newline at end of file
+    let args = init_logging(env::args().skip(1).collect());
+    match args.first().map(|s| s.as_str()) {
+        Some("list") => list(),
+        Some("run") => run(&args[1..]),
+        Some("verify") => verify(&args[1..]),
+        Some("bench") => bench(&args[1..]),
+        Some("animate") => animate(&args[1..]),
+        Some("generate") => generate(&args[1..]),
+        Some("diff") => diff(&args[1..]),
+        Some("sonar") => sonar(&args[1..]),
+        Some("dive") => dive(&args[1..]),
+        _ => fail("expected a command"),
+    }
+}
@@ -0,0 +1,69 @@
+use std::fs;
+
+use input;
+
+/// One known answer: what part `part` of day `day` should give on the named input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expected {
+    pub day: u32,
+    pub part: usize,
+    pub input: String,
+    pub answer: String,
+}
+
+pub fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+pub fn unescape(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    return out;
+}
+
+/// Reads `day part input answer` lines, skipping blanks and `#` comments. The
+/// answer is everything after the third space, so it may contain spaces.
+pub fn parse(s: &str) -> Result<Vec<Expected>, String> {
+    let mut expected = vec![];
+    for (i, line) in s.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.splitn(4, ' ').collect();
+        if fields.len() != 4 {
+            return Err(format!("line {}: expected `day part input answer`", i + 1));
+        }
+        let day = fields[0]
+            .parse::<u32>()
+            .map_err(|_| format!("line {}: bad day {:?}", i + 1, fields[0]))?;
+        let part = match fields[1] {
+            "1" => 1,
+            "2" => 2,
+            p => return Err(format!("line {}: bad part {:?}", i + 1, p)),
+        };
+        expected.push(Expected {
+            day,
+            part,
+            input: fields[2].to_string(),
+            answer: unescape(fields[3]),
+        });
+    }
+    return Ok(expected);
+}
+
+pub fn load() -> Result<Vec<Expected>, String> {
+    let path = input::inputs_dir().join("answers.txt");
+    let s = fs::read_to_string(&path).map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+    parse(&s).map_err(|e| format!("{}: {}", path.display(), e))
+}
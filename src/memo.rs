@@ -0,0 +1,180 @@
+//! Memo tables for recursive solvers. A solver makes one per run and passes
+//! it down, so nothing is shared between inputs and the memory goes when the
+//! run is over. Tables count their hits and misses, and can be capped so the
+//! oldest entries are dropped once they are full.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::hash::Hash;
+
+use rustc_hash::FxHashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped to stay under the capacity.
+    pub evictions: u64,
+    pub size: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses, {} entries, {} evicted",
+            self.hits, self.misses, self.size, self.evictions
+        )
+    }
+}
+
+pub struct Memo<K, V> {
+    table: FxHashMap<K, V>,
+    capacity: Option<usize>,
+    /// Keys in the order they went in, kept only when there is a capacity.
+    order: VecDeque<K>,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Default for Memo<K, V> {
+    fn default() -> Memo<K, V> {
+        Memo::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Memo<K, V> {
+    /// A table that keeps everything.
+    pub fn new() -> Memo<K, V> {
+        Memo {
+            table: FxHashMap::default(),
+            capacity: None,
+            order: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    /// A table that holds at most `capacity` entries, dropping the oldest to
+    /// make room.
+    pub fn bounded(capacity: usize) -> Memo<K, V> {
+        Memo {
+            capacity: Some(capacity),
+            ..Memo::new()
+        }
+    }
+
+    /// The remembered value for `key`, counted as a hit or a miss.
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        match self.table.get(key) {
+            Some(v) => {
+                self.hits += 1;
+                Some(v.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if let Some(capacity) = self.capacity {
+            if capacity == 0 {
+                return;
+            }
+            if !self.table.contains_key(&key) {
+                while self.table.len() >= capacity {
+                    let oldest = self.order.pop_front().expect("a full table has keys in order");
+                    self.table.remove(&oldest);
+                    self.evictions += 1;
+                }
+                self.order.push_back(key.clone());
+            }
+        }
+        self.table.insert(key, value);
+    }
+
+    /// The value for `key`, worked out by `f` and remembered if it is not in
+    /// the table already. `f` gets the table back so it can recurse.
+    pub fn get_or_insert_with<F: FnOnce(&mut Self) -> V>(&mut self, key: K, f: F) -> V {
+        if let Some(v) = self.get(&key) {
+            return v;
+        }
+        let v = f(self);
+        self.insert(key, v.clone());
+        return v;
+    }
+
+    /// Forgets every entry and resets the counts.
+    pub fn clear(&mut self) {
+        self.table.clear();
+        self.order.clear();
+        self.hits = 0;
+        self.misses = 0;
+        self.evictions = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            size: self.table.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_insert_with(n, |memo| fib(n - 1, memo) + fib(n - 2, memo))
+    }
+
+    #[test]
+    fn counts_hits_and_misses() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(50, &mut memo), 12586269025);
+        let stats = memo.stats();
+        assert_eq!(stats.size, 49);
+        assert_eq!(stats.misses, 49);
+        assert_eq!(stats.hits, 47);
+
+        memo.clear();
+        assert_eq!(memo.stats(), Stats::default());
+    }
+
+    #[test]
+    fn drops_oldest_when_full() {
+        let mut memo = Memo::bounded(2);
+        memo.insert(1, 'a');
+        memo.insert(2, 'b');
+        memo.insert(2, 'c');
+        assert_eq!(memo.stats().evictions, 0);
+        memo.insert(3, 'd');
+        assert_eq!(memo.get(&1), None);
+        assert_eq!(memo.get(&2), Some('c'));
+        assert_eq!(memo.get(&3), Some('d'));
+        assert_eq!(memo.stats().evictions, 1);
+        assert_eq!(memo.len(), 2);
+
+        let mut bounded = Memo::bounded(3);
+        assert_eq!(fib(50, &mut bounded), 12586269025);
+        assert!(bounded.len() <= 3);
+    }
+}
@@ -0,0 +1,179 @@
+//! Reads the block letters some puzzles answer with. Every letter is 4 pixels
+//! wide and 6 tall, with a blank column between letters.
+
+use std::fmt;
+
+use grid::Pos;
+
+pub const WIDTH: usize = 4;
+pub const HEIGHT: usize = 6;
+
+/// The letters that have turned up so far, row by row.
+const ALPHABET: &[(char, [&str; HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// Nothing was lit.
+    Empty,
+    /// The lit pixels are not one row of letters.
+    Height(usize),
+    /// The letter at `index`, counting from 0, is not in the alphabet.
+    Unknown { index: usize, bitmap: String },
+    /// A lit pixel left of the first letter or in a gap between letters.
+    Stray(Pos),
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::Empty => write!(f, "no letters to read"),
+            OcrError::Height(h) => write!(f, "letters are {} pixels tall, not {}", HEIGHT, h),
+            OcrError::Unknown { index, bitmap } => write!(f, "unknown letter {}:\n{}", index, bitmap),
+            OcrError::Stray((x, y)) => write!(f, "pixel {},{} is not part of a letter", x, y),
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Reads the letters drawn by `lit`, with the top left letter's corner at
+/// `(0, 0)` and `columns` pixels to read across.
+fn read<F: Fn(usize, usize) -> bool>(columns: usize, lit: F) -> Result<String, OcrError> {
+    let mut text = String::new();
+    for index in 0..columns.div_ceil(WIDTH + 1) {
+        let rows: Vec<String> = (0..HEIGHT)
+            .map(|y| (0..WIDTH).map(|x| if lit(index * (WIDTH + 1) + x, y) { '#' } else { '.' }).collect())
+            .collect();
+        match ALPHABET.iter().find(|(_, glyph)| glyph.iter().zip(&rows).all(|(g, r)| g == r)) {
+            Some((c, _)) => text.push(*c),
+            None => return Err(OcrError::Unknown { index, bitmap: rows.join("\n") }),
+        }
+    }
+    return Ok(text);
+}
+
+/// Reads letters off a set of lit points. The first letter starts at column
+/// 0, since some letters leave their first column blank, but the row of
+/// letters can be drawn at any height. Every lit point has to be part of a
+/// letter.
+pub fn read_points<'a, I: IntoIterator<Item = &'a Pos>>(points: I) -> Result<String, OcrError> {
+    let points: Vec<Pos> = points.into_iter().copied().collect();
+    let min_y = points.iter().map(|p| p.1).min().ok_or(OcrError::Empty)?;
+    let max_x = points.iter().map(|p| p.0).max().ok_or(OcrError::Empty)?;
+    let max_y = points.iter().map(|p| p.1).max().ok_or(OcrError::Empty)?;
+    let height = (max_y - min_y + 1) as usize;
+    if height != HEIGHT {
+        return Err(OcrError::Height(height));
+    }
+    let stray = points.iter().filter(|p| p.0 < 0 || p.0 as usize % (WIDTH + 1) == WIDTH).min_by_key(|p| (p.1, p.0));
+    if let Some(p) = stray {
+        return Err(OcrError::Stray(*p));
+    }
+    let columns = (max_x + 1) as usize;
+    read(columns, |x, y| points.contains(&(x as i32, min_y + y as i32)))
+}
+
+/// Reads letters off a picture with `#` for lit pixels, one line per row.
+pub fn read_picture(s: &str) -> Result<String, OcrError> {
+    let mut points = vec![];
+    for (y, line) in s.lines().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            if c == '#' {
+                points.push((x as i32, y as i32));
+            }
+        }
+    }
+    read_points(&points)
+}
+
+/// The lit points that spell `text`, for the letters the alphabet knows.
+pub fn draw(text: &str) -> Option<Vec<Pos>> {
+    let mut points = vec![];
+    for (index, c) in text.chars().enumerate() {
+        let (_, glyph) = ALPHABET.iter().find(|(l, _)| *l == c)?;
+        for (y, row) in glyph.iter().enumerate() {
+            for (x, pixel) in row.chars().enumerate() {
+                if pixel == '#' {
+                    points.push(((index * (WIDTH + 1) + x) as i32, y as i32));
+                }
+            }
+        }
+    }
+    return Some(points);
+}
+
+/// Every letter the alphabet knows.
+pub fn letters() -> impl Iterator<Item = char> {
+    ALPHABET.iter().map(|(c, _)| *c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_every_letter() {
+        for c in letters() {
+            let text = c.to_string();
+            assert_eq!(read_points(&draw(&text).unwrap()), Ok(text));
+        }
+        let all: String = letters().collect();
+        let lowered: Vec<Pos> = draw(&all).unwrap().iter().map(|(x, y)| (*x, y + 10)).collect();
+        assert_eq!(read_points(&lowered), Ok(all));
+        assert_eq!(draw("AQ"), None);
+    }
+
+    #[test]
+    fn shows_unknown_letters() {
+        let mut points = draw("HI").unwrap();
+        points.push((WIDTH as i32 + 1, 0));
+        let bitmap = ["####", "..#.", "..#.", "..#.", "..#.", ".###"].join("\n");
+        assert_eq!(read_points(&points), Err(OcrError::Unknown { index: 1, bitmap }));
+    }
+
+    #[test]
+    fn needs_one_row_of_letters() {
+        assert_eq!(read_points(&[]), Err(OcrError::Empty));
+        assert_eq!(read_picture("....\n...."), Err(OcrError::Empty));
+        let mut points = draw("L").unwrap();
+        points.push((0, HEIGHT as i32));
+        assert_eq!(read_points(&points), Err(OcrError::Height(HEIGHT + 1)));
+        assert_eq!(read_picture("#..#\n####"), Err(OcrError::Height(2)));
+    }
+
+    #[test]
+    fn refuses_pixels_outside_letters() {
+        let mut points = draw("HI").unwrap();
+        points.push((WIDTH as i32, 3));
+        assert_eq!(read_points(&points), Err(OcrError::Stray((4, 3))));
+        let left: Vec<Pos> = draw("E").unwrap().iter().map(|(x, y)| (x - 5, *y)).collect();
+        assert_eq!(read_points(&left), Err(OcrError::Stray((-5, 0))));
+        let mut points = draw("A").unwrap();
+        points.push((-1, 5));
+        assert_eq!(read_points(&points).unwrap_err().to_string(), "pixel -1,5 is not part of a letter");
+    }
+
+    #[test]
+    fn reads_pictures() {
+        let picture = ["#..#.#...", "#..#.#...", "####.#...", "#..#.#...", "#..#.#...", "#..#.####"].join("\n");
+        assert_eq!(read_picture(&picture), Ok("HL".to_string()));
+    }
+}
@@ -1,31 +1,218 @@
-fn day_one_input() -> Vec<i32> {
-    let contents = fs::read_to_string("inputs/day1puzzle1.txt")
-    .expect("Something went wrong reading the file");
-    let lines = contents.lines();
-    let numbers : Vec<i32>= lines.map(|x| {x.parse::<i32>().unwrap()}).collect();
-    return numbers;
-}
-
-fn d1p1() {
-    let numbers = day_one_input();
-    let mut increases = 0;
-    for i in 0..(numbers.len()-1){
-        println!("{} {}", numbers[i], numbers[i+1]);
-        if numbers[i+1] > numbers[i] {
-            increases += 1;
+//! Day 1: Sonar Sweep. `d1p1` counts depth increases and `d1p2` does the same
+//! for sums of three measurement windows, both through `SlidingWindows`, which
+//! works for any window size and on depths streamed by `read_depths`.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+use std::ops::{Add, Sub};
+
+use generate::Rng;
+use parser::{self, Cursor, ParseError};
+use solution::{Answer, Solution};
+
+pub fn parse_data(s: &str) -> Result<Vec<i32>, ParseError> {
+    parser::each_line(s, |c| c.int::<i32>())
+}
+
+/// The sums of every `size` consecutive items, keeping just the current window
+/// in memory.
+pub struct WindowSums<I: Iterator> {
+    items: I,
+    size: usize,
+    window: VecDeque<I::Item>,
+    sum: Option<I::Item>,
+}
+
+impl<I, T> Iterator for WindowSums<I>
+where
+    I: Iterator<Item = T>,
+    T: Copy + Add<Output = T> + Sub<Output = T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            let x = self.items.next()?;
+            self.sum = Some(self.sum.map_or(x, |s| s + x));
+            self.window.push_back(x);
+            if self.window.len() > self.size {
+                let oldest = self.window.pop_front().unwrap();
+                self.sum = self.sum.map(|s| s - oldest);
+            }
+            if self.window.len() == self.size {
+                return self.sum;
+            }
+        }
+    }
+}
+
+/// The positions of the windows whose sum is bigger than the sum of the
+/// window before, counting windows from 0.
+pub struct Increases<I: Iterator> {
+    sums: WindowSums<I>,
+    previous: Option<I::Item>,
+    position: usize,
+}
+
+impl<I, T> Iterator for Increases<I>
+where
+    I: Iterator<Item = T>,
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T>,
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            let sum = self.sums.next()?;
+            let position = self.position;
+            self.position += 1;
+            let increased = matches!(self.previous, Some(p) if sum > p);
+            self.previous = Some(sum);
+            if increased {
+                return Some(position);
+            }
+        }
+    }
+}
+
+/// Sliding windows over any iterator of numbers.
+pub trait SlidingWindows: Iterator + Sized {
+    fn window_sums(self, size: usize) -> WindowSums<Self> {
+        assert!(size > 0, "windows hold at least one item");
+        WindowSums {
+            items: self,
+            size,
+            window: VecDeque::with_capacity(size + 1),
+            sum: None,
+        }
+    }
+
+    fn window_increases(self, size: usize) -> Increases<Self> {
+        Increases {
+            sums: self.window_sums(size),
+            previous: None,
+            position: 0,
         }
     }
-    println!("{:#?}", increases);
 }
 
-fn d1p2() {
-    let numbers = day_one_input();
-    let mut increases = 0;
-    for i in 0..(numbers.len()-3){
-        println!("{} {} {}", i, numbers[i], numbers[i+3]);
-        if numbers[i+3] > numbers[i] {
-            increases += 1;
+impl<I: Iterator> SlidingWindows for I {}
+
+/// How many measurements are deeper than the one before.
+pub fn d1p1(numbers: &[i32]) -> usize {
+    numbers.iter().copied().window_increases(1).count()
+}
+
+/// How many sums of three consecutive measurements are bigger than the one
+/// before.
+pub fn d1p2(numbers: &[i32]) -> usize {
+    numbers.iter().copied().window_increases(3).count()
+}
+
+/// Reads depths a line at a time, skipping blank lines, so a stream of them
+/// never has to fit in memory. Lines that are not depths come out as
+/// `InvalidData` errors.
+pub fn read_depths<R: BufRead>(reader: R) -> impl Iterator<Item = io::Result<i64>> {
+    reader.lines().enumerate().filter_map(|(i, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => return Some(Err(e)),
+        };
+        if line.trim().is_empty() {
+            return None;
+        }
+        let mut c = Cursor::new(i + 1, &line);
+        let depth = c.int::<i64>().and_then(|d| c.end().map(|_| d));
+        Some(depth.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)))
+    })
+}
+
+/// Counts the windows of `size` depths read from `reader` whose sum went up,
+/// handing the position of each to `on_increase` as it is found. Stops at the
+/// first line that cannot be read.
+pub fn stream_increases<R: BufRead, F: FnMut(usize)>(reader: R, size: usize, mut on_increase: F) -> io::Result<usize> {
+    let mut error = None;
+    let depths = read_depths(reader).map_while(|d| d.map_err(|e| error = Some(e)).ok());
+    let mut count = 0;
+    for position in depths.window_increases(size) {
+        on_increase(position);
+        count += 1;
+    }
+    match error {
+        Some(e) => Err(e),
+        None => Ok(count),
+    }
+}
+
+/// `size` depths from a sonar sweep that mostly heads down.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut depth = rng.range(100, 200);
+    let mut lines = vec![];
+    for _ in 0..size {
+        lines.push(depth.to_string());
+        depth = (depth + rng.range(-10, 20)).max(0);
+    }
+    return lines.join("\n");
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Vec<i32>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1(numbers: &Self::Input) -> Answer {
+        d1p1(numbers).into()
+    }
+
+    fn part2(numbers: &Self::Input) -> Answer {
+        d1p2(numbers).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const SAMPLE: [i32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn counts_sample_increases() {
+        assert_eq!(d1p1(&SAMPLE), 7);
+        assert_eq!(d1p2(&SAMPLE), 5);
+        assert_eq!(d1p1(&[]), 0);
+        assert_eq!(d1p2(&[1, 2]), 0);
+    }
+
+    #[test]
+    fn sums_windows() {
+        let sums: Vec<i32> = SAMPLE.iter().copied().window_sums(3).collect();
+        assert_eq!(sums, vec![607, 618, 618, 617, 647, 716, 769, 792]);
+        let positions: Vec<usize> = SAMPLE.iter().copied().window_increases(3).collect();
+        assert_eq!(positions, vec![1, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn streams_depths() {
+        let text = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+        let mut positions = vec![];
+        assert_eq!(stream_increases(text.as_bytes(), 1, |p| positions.push(p)).unwrap(), 7);
+        assert_eq!(positions, vec![1, 2, 3, 5, 6, 7, 9]);
+
+        let e = stream_increases("1\n2\nthree\n".as_bytes(), 1, |_| {}).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        assert!(e.to_string().starts_with("line 3, column 1"));
+    }
+
+    proptest! {
+        #[test]
+        fn matches_comparing_ends(depths in prop::collection::vec(0i64..10000, 0..200), size in 1usize..10) {
+            let expected = (size..depths.len()).filter(|i| depths[*i] > depths[i - size]).count();
+            prop_assert_eq!(depths.iter().copied().window_increases(size).count(), expected);
         }
     }
-    println!("{:#?}", increases);
 }
@@ -1,11 +1,24 @@
-pub fn get_data() -> Vec<&'static str> {
-    return include_str!("../inputs/day10puzzle.txt").lines().collect::<Vec<&str>>();
+//! Day 10: Syntax Scoring. `parse_line` reports the first illegal character of a
+//! line or what is left open, which `scores` turns into both answers.
+
+use generate::Rng;
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
+
+pub fn get_data(s: &str) -> Result<Vec<String>, ParseError> {
+    parser::each_line(s, |c| {
+        let line = c.rest().to_string();
+        while !c.is_empty() {
+            c.one_of("()[]{}<>")?;
+        }
+        return Ok(line);
+    })
 }
 
 #[derive(Debug)]
 pub struct ParseResult {
-    error: Option<(usize, char)>,
-    leftover: Option<Vec<char>>
+    pub error: Option<(usize, char)>,
+    pub leftover: Option<Vec<char>>
 }
 
 pub fn matched(left: char, right: char) -> bool {
@@ -27,25 +40,23 @@ pub fn parse_line(line: &str) -> ParseResult {
                     return ParseResult{error: Some((i, c)), leftover: None};
                 }
             },
-            _ => {
-                dbg!(c);
-                unreachable!()
-            }
+            _ => unreachable!("unexpected character {:?}", c)
         }
     }
     return ParseResult{error: None, leftover: Some(stack)};
 }
 
-pub fn main() {
-    let lines = get_data();
+/// The syntax error score of the corrupted lines and the middle completion
+/// score of the incomplete ones.
+pub fn scores(lines: &[String]) -> (usize, usize) {
     let mut score_a = 0;
     let mut score_b: Vec<usize> = vec![];
     for line in lines {
-        println!("{}", line.clone());
+        trace!("{}", line);
         let r = parse_line(line); 
         match r {
             ParseResult{error: Some((i, c)), leftover: _} => {
-                println!("Error at {}: {}", i, c);
+                debug!("error at {}: {}", i, c);
                 match c {
                     ')' => score_a += 3,
                     ']' => score_a += 57,
@@ -67,13 +78,69 @@ pub fn main() {
                     local_score = v + (local_score * 5);
                 }
 
-                dbg!(local_score);
+                debug!("completion score {}", local_score);
                 score_b.push(local_score);
             }
         }
-        println!("");
     }
-    dbg!(score_a);
     score_b.sort();
-    dbg!(score_b[score_b.len()/2]);
+    return (score_a, score_b[score_b.len()/2]);
+}
+
+/// How deep the generated chunks go. A completion score is a base 5 number
+/// with a digit per open chunk, and 5^28 is already too big for a `u64`.
+const MAX_OPEN: usize = 25;
+
+/// `size` lines of chunks, about half of them corrupted and the rest
+/// incomplete. The first line is always incomplete so part 2 has a middle.
+/// Chunks are never left open more than `MAX_OPEN` deep, so completion
+/// scores fit in a `usize`.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let pairs = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+    let mut lines = vec![];
+    for i in 0..size {
+        let mut line = String::new();
+        let mut stack: Vec<usize> = vec![];
+        for _ in 0..rng.range(20, 100) {
+            if stack.is_empty() || (stack.len() < MAX_OPEN && rng.chance(3, 5)) {
+                let p = rng.index(pairs.len());
+                stack.push(p);
+                line.push(pairs[p].0);
+            } else {
+                line.push(pairs[stack.pop().unwrap()].1);
+            }
+        }
+        if stack.is_empty() {
+            line.push(pairs[rng.index(pairs.len())].0);
+            stack.push(0);
+        }
+        if i > 0 && rng.chance(1, 2) {
+            let expected = *stack.last().unwrap();
+            let wrong = (expected + rng.range(1, 3) as usize) % pairs.len();
+            line.push(pairs[wrong].1);
+            for _ in 0..rng.range(0, 10) {
+                line.push(rng.pick(&pairs).0);
+            }
+        }
+        lines.push(line);
+    }
+    return lines.join("\n");
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_data(input)
+    }
+
+    fn part1(lines: &Self::Input) -> Answer {
+        scores(lines).0.into()
+    }
+
+    fn part2(lines: &Self::Input) -> Answer {
+        scores(lines).1.into()
+    }
 }
\ No newline at end of file
@@ -1,97 +1,132 @@
+//! Day 11: Dumbo Octopus. `take_step` advances the energy `Grid` one step;
+//! `flashes_after` and `first_all_flash` answer the parts.
+
 use colored::*;
-use std::fmt;
+use animate::Animation;
+use generate::Rng;
+use grid::{self, Dense, Pos};
+use parser::ParseError;
+use solution::{Answer, Solution};
+
+pub type Grid = Dense<i32>;
 
-#[derive(Clone)]
-pub struct Grid {
-    values: Vec<Vec<i32>>,
+/// The octopuses about to flash in green and yellow and the ones that just
+/// flashed in bold.
+pub fn render(g: &Grid) -> String {
+    let map = g.render(|_, number| {
+        let s = number.to_string();
+        let s = match number {
+            9 => s.green().to_string(),
+            8 => s.yellow().to_string(),
+            0 => s.bold().to_string(),
+            _ => s,
+        };
+        s + " "
+    });
+    return format!("\n{}", map);
 }
-const SIZE: i32 = 10;
 
-impl fmt::Debug for Grid {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut s = "\n".to_owned();
-        let mut i = 0;
-        for line in &self.values {
-            for number in line {
-                if *number == 9 {
-                    s += &format!("{}", number.to_string().green()).to_owned();
-                } else if *number == 8 {
-                    s += &format!("{}", number.to_string().yellow()).to_owned();
-                } else if *number == 0 {
-                    s += &format!("{}", number.to_string().bold()).to_owned();
-                } else {
-                    s += &format!("{}", number.to_string()).to_owned();
+pub fn get_data(s: &str) -> Result<Grid, ParseError> {
+    grid::parse_digits(s)
+}
+
+/// Advances every octopus one step, leaving the ones that flashed at 0.
+pub fn take_step(mut g: Grid) -> Grid {
+    for v in g.values_mut() {
+        *v += 1;
+    }
+    let mut flashing: Vec<Pos> = g.iter().filter(|(_, v)| **v >= 10).map(|(p, _)| p).collect();
+    while let Some(p) = flashing.pop() {
+        if g[p] == 0 {
+            continue;
+        }
+        g[p] = 0;
+        for n in g.neighbours8(p).collect::<Vec<Pos>>() {
+            if g[n] != 0 {
+                g[n] += 1;
+                if g[n] >= 10 {
+                    flashing.push(n);
                 }
-            s += " ";
-            }
-            i += 1;
-            if i != SIZE {
-                s += "\n";
             }
         }
-        return write!(f, "{}", s);
     }
+    return g;
 }
 
-pub fn get_data() -> Grid {
-    let values = include_str!("../inputs/day11puzzle.txt")
-        .lines()
-        .map(|x| {
-            x.split("")
-                .filter(|x| *x != "")
-                .map(|y| y.parse::<i32>().unwrap())
-                .collect::<Vec<i32>>()
-        })
-        .collect::<Vec<Vec<i32>>>();
-    return Grid { values };
+pub fn count_flashes(g: &Grid) -> usize {
+    return g.values().filter(|v| **v == 0).count();
 }
 
-pub fn take_step(mut g: Grid) -> Grid {
-    for (x, line) in g.values.clone().iter().enumerate() {
-        for (y, _) in line.iter().enumerate() {
-            g.values[x][y] += 1;
-        }
+/// How many flashes there are in the first `steps` steps.
+pub fn flashes_after(mut grid: Grid, steps: usize) -> usize {
+    let mut flashes = 0;
+    for _ in 0..steps {
+        grid = take_step(grid);
+        flashes += count_flashes(&grid);
     }
-    while g.values.iter().any(|vs| vs.iter().any(|v| {v >=&10})) {
-        for (x, line) in g.values.clone().iter().enumerate() {
-            for (y, _) in line.iter().enumerate() {
-                if g.values[x][y] >= 10 {
-                    g.values[x][y] = 0;
-                    let directions: Vec<(i32,i32)> = vec![(-1,1), (0,1), (1,1), (-1,0), (1,0), (-1,-1), (0, -1), (1,-1)];
-                    //let directions: Vec<(i32,i32)> = vec![(0, -1), (0,1)];
-                    for (dx,dy) in directions {
-                        let px: i32 = (x as i32) + dx;
-                        let py: i32 = (y as i32) + dy;
-                        if  0 <= px && px < SIZE && 0 <= py && py < SIZE && g.values[px as usize][py as usize] != 0 {
-                            g.values[px as usize][py as usize] += 1;
-                        }
-                    }
-                }
-            }
+    debug!("after {} steps: {}", steps, render(&grid));
+    return flashes;
+}
+
+/// The first step on which every octopus flashes at once.
+pub fn first_all_flash(mut grid: Grid) -> usize {
+    let mut i = 0;
+    loop {
+        i += 1;
+        grid = take_step(grid);
+        if grid.values().all(|v| *v == 0) {
+            debug!("all flashed at step {}: {}", i, render(&grid));
+            return i;
         }
     }
-    return g;
 }
-pub fn main() {
-    let mut grid = get_data();
-    dbg!(grid.clone());
-    //let mut flashes = 0;
-    for i in 1..450 {
+
+/// Steps the octopuses until they all flash at once.
+pub fn animate(input: &str, anim: &mut Animation) -> Result<(), ParseError> {
+    let mut grid = get_data(input)?;
+    let last = anim.to;
+    let mut i = 0;
+    loop {
+        anim.frame(i, || render(&grid));
+        let synced = i > 0 && grid.values().all(|v| *v == 0);
+        if last.map_or(synced, |last| i >= last) {
+            return Ok(());
+        }
         grid = take_step(grid);
-        let mut all_flashes = true; 
-        for vs in grid.clone().values{
-            for v in vs {
-                if v != 0 {
-                    all_flashes = false;
-                }
+        i += 1;
+    }
+}
+
+/// A `size` square of octopuses. Not every grid ever flashes all at once, so
+/// grids are drawn until one does within 1000 steps.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    loop {
+        let rows: Vec<Vec<i32>> = (0..size).map(|_| (0..size).map(|_| rng.below(10) as i32).collect()).collect();
+        let mut grid = Grid::from_rows(rows);
+        let start = grid.clone();
+        for _ in 0..1000 {
+            grid = take_step(grid);
+            if grid.values().all(|v| *v == 0) {
+                return grid::render_digits(&start);
             }
         }
-        if all_flashes {
-            dbg!(i);
-            dbg!(grid.clone());
-        }
+    }
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Grid;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_data(input)
+    }
+
+    fn part1(grid: &Self::Input) -> Answer {
+        flashes_after(grid.clone(), 100).into()
+    }
 
+    fn part2(grid: &Self::Input) -> Answer {
+        first_all_flash(grid.clone()).into()
     }
-    dbg!(grid.clone());
-    //dbg!(flashes);
 }
@@ -1,27 +1,37 @@
+//! Day 12: Passage Pathing. `go_1` lists every path through the cave `Network`
+//! visiting small caves once, `go_2` allows one small cave twice.
+
 use std::collections::{HashMap, HashSet};
-use std::num::ParseIntError;
 use std::str::FromStr;
+use generate::Rng;
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
 
 #[derive(Debug, Clone)]
 pub struct Network {
-    nodes: HashSet<String>,
-    edges: HashMap<String, HashSet<String>>,
+    pub nodes: HashSet<String>,
+    pub edges: HashMap<String, HashSet<String>>,
 }
 
 impl FromStr for Network {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let edges = s
-            .clone()
-            .lines()
-            .map(|x| x.split_once("-").unwrap())
-            .map(|(n, e)| (n.trim().to_string(), e.trim().to_string()))
+        let pairs = parser::each_line(s, |c| {
+            c.skip_spaces();
+            let n = c.word()?.to_string();
+            c.expect("-")?;
+            let e = c.word()?.to_string();
+            c.skip_spaces();
+            return Ok((n, e));
+        })?;
+        let edges = pairs
+            .into_iter()
             .fold(
                 HashMap::new(),
                 |mut acc: HashMap<String, HashSet<String>>, (n, e)| {
                     if !acc.contains_key(&n) {
-                        acc.entry(n.clone()).or_insert(HashSet::new());
+                        acc.entry(n.clone()).or_default();
                     }
                     if e != "start" {
                         acc.entry(n.clone()).and_modify(|x| {
@@ -30,7 +40,7 @@ impl FromStr for Network {
                     }
 
                     if !acc.contains_key(&e) {
-                        acc.entry(e.clone()).or_insert(HashSet::new());
+                        acc.entry(e.clone()).or_default();
                     }
                     if n != "start" {
                         acc.entry(e).and_modify(|x| {
@@ -50,8 +60,8 @@ impl FromStr for Network {
         });
 
         Ok(Network {
-            nodes: nodes,
-            edges: edges,
+            nodes,
+            edges,
         })
     }
 }
@@ -90,6 +100,7 @@ pub fn find_paths_1(network: Network, used: HashSet<String>, node: String) -> Ve
     return paths;
 }
 
+/// Every path from start to end that visits small caves at most once.
 pub fn go_1(network: Network) -> Vec<Vec<String>> {
     return find_paths_1(network, HashSet::new(), "start".to_string());
 }
@@ -124,7 +135,7 @@ pub fn find_paths_2(network: Network, used: HashSet<String>, node: String, doubl
                 .collect::<Vec<Vec<String>>>();
             paths.append(&mut results);
         }
-        else if doubled == None {
+        else if doubled.is_none() {
             let next = Some(option.clone());
             let mut results = find_paths_2(network.clone(), used.clone(), option.clone(), next)
                 .iter()
@@ -140,95 +151,150 @@ pub fn find_paths_2(network: Network, used: HashSet<String>, node: String, doubl
     return paths;
 }
 
+/// Every path from start to end that visits one small cave at most twice and
+/// the others at most once.
 pub fn go_2(network: Network) -> Vec<Vec<String>> {
     return find_paths_2(network, HashSet::new(), "start".to_string(), None);
 }
-pub fn main() {
-    let s = "start-end";
-    let t = "start-A
-    start-b
-    A-c
-    A-b
-    b-d
-    A-end
-    b-end";
-    let r = "dc-end
-    HN-start
-    start-kj
-    dc-start
-    dc-HN
-    LN-dc
-    HN-end
-    kj-sa
-    kj-HN
-    kj-dc";
-    let q = "fs-end
-    he-DX
-    fs-he
-    start-DX
-    pj-DX
-    end-zg
-    zg-sl
-    zg-pj
-    pj-he
-    RW-he
-    fs-DX
-    pj-RW
-    zg-RW
-    start-pj
-    he-WI
-    zg-he
-    pj-fs
-    start-RW";
-    let z = "hl-WP
-    vl-fo
-    vl-WW
-    WP-start
-    vl-QW
-    fo-wy
-    WW-dz
-    dz-hl
-    fo-end
-    VH-fo
-    ps-vl
-    FN-dz
-    WP-ps
-    ps-start
-    WW-hl
-    end-QW
-    start-vl
-    WP-fo
-    end-FN
-    hl-QW
-    WP-dz
-    QW-fo
-    QW-dz
-    ps-dz";
-
-    let network_s = Network::from_str(s).unwrap();
-    let network_t = Network::from_str(t).unwrap();
-    let network_r = Network::from_str(r).unwrap();
-    let network_q = Network::from_str(q).unwrap();
-    let network_z = Network::from_str(z).unwrap();
-
-    for (i, path) in go_1(network_s.clone()).iter().enumerate() {
-        print!("{}: start",i+1);
-        for p in path.iter().rev() {
-            print!(",{}", p);
+
+/// A cave system with `size` small caves, a third as many big ones and no
+/// two big caves side by side, so there are only so many paths.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut taken: Vec<String> = vec![];
+    let mut name = |rng: &mut Rng| loop {
+        let n: String = (0..2).map(|_| (b'a' + rng.below(26) as u8) as char).collect();
+        if !taken.contains(&n) {
+            taken.push(n.clone());
+            return n;
+        }
+    };
+    let small: Vec<String> = (0..size.max(1)).map(|_| name(rng)).collect();
+    let big: Vec<String> = (0..(size / 3).max(1)).map(|_| name(rng).to_uppercase()).collect();
+    let mut edges: Vec<(String, String)> = vec![];
+    let mut link = |a: &String, b: &String| {
+        let edge = (a.clone(), b.clone());
+        if a != b && !edges.contains(&edge) && !edges.contains(&(edge.1.clone(), edge.0.clone())) {
+            edges.push(edge);
+        }
+    };
+    for s in &small {
+        link(s, rng.pick(&big));
+        if rng.chance(1, 2) {
+            link(s, rng.pick(&small));
+        }
+    }
+    let caves: Vec<String> = small.iter().chain(&big).cloned().collect();
+    for end in &["start".to_string(), "end".to_string()] {
+        for _ in 0..2 {
+            link(end, rng.pick(&caves));
         }
-        println!("");
+    }
+    rng.shuffle(&mut edges);
+    let lines: Vec<String> = edges.iter().map(|(a, b)| format!("{}-{}", a, b)).collect();
+    return lines.join("\n");
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Network;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Network::from_str(input)
     }
 
-    assert_eq!(go_1(network_s.clone()).len(),1);
-    assert_eq!(go_1(network_t.clone()).len(),10);
-    assert_eq!(go_1(network_r.clone()).len(),19);
-    assert_eq!(go_1(network_q.clone()).len(),226);
-    assert_eq!(go_1(network_z.clone()).len(),3410);
-
-    assert_eq!(go_2(network_s).len(),1);
-    assert_eq!(go_2(network_t).len(),36);
-    assert_eq!(go_2(network_r).len(),103);
-    assert_eq!(go_2(network_q).len(),3509);
-    assert_eq!(go_2(network_z).len(),98796);
-    // s t r q z 
+    fn part1(network: &Self::Input) -> Answer {
+        go_1(network.clone()).len().into()
+    }
+
+    fn part2(network: &Self::Input) -> Answer {
+        go_2(network.clone()).len().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_paths() {
+        let s = "start-end";
+        let t = "start-A
+        start-b
+        A-c
+        A-b
+        b-d
+        A-end
+        b-end";
+        let r = "dc-end
+        HN-start
+        start-kj
+        dc-start
+        dc-HN
+        LN-dc
+        HN-end
+        kj-sa
+        kj-HN
+        kj-dc";
+        let q = "fs-end
+        he-DX
+        fs-he
+        start-DX
+        pj-DX
+        end-zg
+        zg-sl
+        zg-pj
+        pj-he
+        RW-he
+        fs-DX
+        pj-RW
+        zg-RW
+        start-pj
+        he-WI
+        zg-he
+        pj-fs
+        start-RW";
+        let z = "hl-WP
+        vl-fo
+        vl-WW
+        WP-start
+        vl-QW
+        fo-wy
+        WW-dz
+        dz-hl
+        fo-end
+        VH-fo
+        ps-vl
+        FN-dz
+        WP-ps
+        ps-start
+        WW-hl
+        end-QW
+        start-vl
+        WP-fo
+        end-FN
+        hl-QW
+        WP-dz
+        QW-fo
+        QW-dz
+        ps-dz";
+
+        let network_s = Network::from_str(s).unwrap();
+        let network_t = Network::from_str(t).unwrap();
+        let network_r = Network::from_str(r).unwrap();
+        let network_q = Network::from_str(q).unwrap();
+        let network_z = Network::from_str(z).unwrap();
+
+        assert_eq!(go_1(network_s.clone()).len(), 1);
+        assert_eq!(go_1(network_t.clone()).len(), 10);
+        assert_eq!(go_1(network_r.clone()).len(), 19);
+        assert_eq!(go_1(network_q.clone()).len(), 226);
+        assert_eq!(go_1(network_z.clone()).len(), 3410);
+
+        assert_eq!(go_2(network_s).len(), 1);
+        assert_eq!(go_2(network_t).len(), 36);
+        assert_eq!(go_2(network_r).len(), 103);
+        assert_eq!(go_2(network_q).len(), 3509);
+        assert_eq!(go_2(network_z).len(), 98796);
+    }
 }
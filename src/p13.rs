@@ -1,90 +1,177 @@
+//! Day 13: Transparent Origami. The `Paper` is its dots and folds; `fold_all`
+//! folds it up and the dots left spell the code.
+
 use std::collections::HashSet;
 use std::iter::FromIterator;
+use export::Image;
+use generate::Rng;
+use ocr::{self, OcrError};
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
+
+pub type Fold = (String, i32);
+pub type Paper = (Vec<(i32, i32)>, Vec<Fold>);
 
-const sample: &str = "6,10
-0,14
-9,10
-0,3
-10,4
-4,11
-6,0
-6,12
-4,1
-0,13
-10,12
-3,4
-3,0
-8,4
-1,10
-2,14
-8,10
-9,0
-
-fold along y=7
-fold along x=5";
-
-pub fn parse_data(s: &str) -> (Vec<(i32, i32)>, Vec<(String, i32)>) {
-    let (ns, fs) = s.split_once("\n\n").unwrap();
-    let numbers = ns
-        .lines()
-        .map(|a| {
-            let (x, y) = a.split_once(",").unwrap();
-            return (x.parse::<i32>().unwrap(), y.parse::<i32>().unwrap());
-        })
-        .collect::<Vec<(i32, i32)>>();
-    let folds = fs
-        .lines()
-        .map(|l| {
-            let rest = l.to_string().split_off(11);
-            let (p, i) = rest.split_once("=").unwrap();
-            return (p.to_string(), i.parse::<i32>().unwrap());
-        })
-        .collect::<Vec<(String, i32)>>();
-    return (numbers, folds);
+pub fn parse_data(s: &str) -> Result<Paper, ParseError> {
+    let mut blocks = parser::blocks(s).into_iter();
+    let mut numbers = vec![];
+    for mut c in blocks.next().unwrap_or_default() {
+        let x = c.int::<i32>()?;
+        c.expect(",")?;
+        let y = c.int::<i32>()?;
+        c.end()?;
+        numbers.push((x, y));
+    }
+    let fs = blocks.next().ok_or_else(|| ParseError::at_end(s, "a blank line and fold instructions"))?;
+    let mut folds = vec![];
+    for mut c in fs {
+        c.expect("fold along ")?;
+        let p = c.one_of("xy")?;
+        c.expect("=")?;
+        let i = c.int::<i32>()?;
+        c.end()?;
+        folds.push((p.to_string(), i));
+    }
+    return Ok((numbers, folds));
 }
 
-pub fn main() {
-    let c = include_str!("../inputs/day13puzzle.txt");
-    let (ns, fs) = parse_data(c);
-    let mut points: HashSet<(i32, i32)> = HashSet::from_iter(ns);
-    for (p, mark) in fs {
-        let mut newer_points: HashSet<(i32, i32)> = HashSet::new();
-        for (x,y) in &points {
-            if p == "x" && x >= &mark {
-                let pd = (2 * mark - x, *y);
-                newer_points.insert(pd);
-            } else if p == "y" && y >= &mark {
-                let pd = (*x, 2* mark - y);
-                newer_points.insert(pd);
-            } else {
-                newer_points.insert((*x,*y));
-            }
+/// Writes the paper back out the way it is read: the dots, a blank line and
+/// then the folds.
+pub fn print_data((dots, folds): &Paper) -> String {
+    let mut lines: Vec<String> = dots.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+    lines.push(String::new());
+    lines.extend(folds.iter().map(|(p, mark)| format!("fold along {}={}", p, mark)));
+    return lines.join("\n");
+}
+
+pub fn fold(points: &HashSet<(i32, i32)>, (p, mark): &Fold) -> HashSet<(i32, i32)> {
+    let mark = *mark;
+    let mut newer_points: HashSet<(i32, i32)> = HashSet::new();
+    for (x,y) in points {
+        if p == "x" && x >= &mark {
+            let pd = (2 * mark - x, *y);
+            newer_points.insert(pd);
+        } else if p == "y" && y >= &mark {
+            let pd = (*x, 2* mark - y);
+            newer_points.insert(pd);
+        } else {
+            newer_points.insert((*x,*y));
         }
-        points = newer_points.clone();
     }
-    // y = 7
-    //let mut new_points: HashSet<(i32,i32)> = HashSet::new();
-    // for p in points {
-    //     if p.1 >= 7 {
-    //         let pd = (p.0, 2*7-p.1);
-    //         new_points.insert(pd);
-    //     }
-    //     else {
-    //         new_points.insert(p);
-    //     }
-    // }
-
-    dbg!(points.clone());
-    dbg!(points.len());
-    for y in 0..100 {
-        for x in 0..100 {
+    return newer_points;
+}
+
+pub fn render(points: &HashSet<(i32, i32)>) -> String {
+    let max_x = points.iter().map(|p| p.0).max().unwrap_or(0);
+    let max_y = points.iter().map(|p| p.1).max().unwrap_or(0);
+    let mut s = "".to_owned();
+    for y in 0..=max_y {
+        for x in 0..=max_x {
             if points.contains(&(x,y)) {
-                print!("#");
+                s += "#";
             }
             else {
-                print!(" ")
+                s += " ";
+            }
+        }
+        if y != max_y {
+            s += "\n";
+        }
+    }
+    return s;
+}
+
+/// The dots left after every fold.
+pub fn fold_all((ns, fs): &Paper) -> HashSet<(i32, i32)> {
+    let mut points: HashSet<(i32, i32)> = HashSet::from_iter(ns.iter().copied());
+    for f in fs {
+        points = fold(&points, f);
+    }
+    return points;
+}
+
+/// The code left after every fold.
+pub fn image(input: &str) -> Result<Image, ParseError> {
+    Ok(Image::from_points(fold_all(&parse_data(input)?)))
+}
+
+/// A code of `size` letters on paper unfolded the way the real one is, five
+/// times across and seven times down.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let letters: Vec<char> = ocr::letters().collect();
+    let code: String = (0..size).map(|_| *rng.pick(&letters)).collect();
+    let mut dots = ocr::draw(&code).unwrap_or_default();
+    let mut width = (size * (ocr::WIDTH + 1)) as i32;
+    let mut height = ocr::HEIGHT as i32;
+    let mut folds: Vec<Fold> = vec![];
+    for axis in "yyxyxyxyxyxy".chars() {
+        let mark = if axis == 'x' { &mut width } else { &mut height };
+        let f = *mark;
+        *mark = 2 * f + 1;
+        let mirror = |(x, y): (i32, i32)| if axis == 'x' { (2 * f - x, y) } else { (x, 2 * f - y) };
+        let mut unfolded = vec![];
+        for dot in dots {
+            match rng.below(3) {
+                0 => unfolded.push(dot),
+                1 => unfolded.push(mirror(dot)),
+                _ => unfolded.extend([dot, mirror(dot)]),
             }
         }
-        println!("");
+        dots = unfolded;
+        folds.push((axis.to_string(), f));
+    }
+    rng.shuffle(&mut dots);
+    folds.reverse();
+    return print_data(&(dots, folds));
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Paper;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1((ns, fs): &Self::Input) -> Answer {
+        let points: HashSet<(i32, i32)> = HashSet::from_iter(ns.iter().copied());
+        fold(&points, &fs[0]).len().into()
+    }
+
+    /// The letters the dots spell, or the dots themselves when they do not
+    /// spell anything.
+    fn part2(paper: &Self::Input) -> Answer {
+        let points = fold_all(paper);
+        match ocr::read_points(&points) {
+            Ok(code) => Answer::Str(code),
+            Err(e @ OcrError::Unknown { .. }) => {
+                warn!("could not read the code, {}", e);
+                Answer::Multiline(render(&points))
+            }
+            Err(e) => {
+                debug!("not reading the dots as letters: {}", e);
+                Answer::Multiline(render(&points))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn paper() -> impl Strategy<Value = Paper> {
+        let dots = prop::collection::vec((0i32..2000, 0i32..2000), 1..50);
+        let fold = (prop::sample::select(vec!["x", "y"]), 1i32..1000).prop_map(|(p, mark)| (p.to_string(), mark));
+        (dots, prop::collection::vec(fold, 1..12))
+    }
+
+    proptest! {
+        #[test]
+        fn print_data_round_trips(paper in paper()) {
+            prop_assert_eq!(parse_data(&print_data(&paper)).unwrap(), paper);
+        }
     }
 }
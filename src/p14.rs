@@ -1,152 +1,78 @@
+//! Day 14: Extended Polymerization. Polymers are kept as pair `Counts`, which
+//! `take_steps` grows by the `Transforms` and `score_after` scores.
+
 use std::collections::HashMap;
-const PUZZLE: &str = "HBCHSNFFVOBNOFHFOBNO
-
-HF -> O
-KF -> F
-NK -> F
-BN -> O
-OH -> H
-VC -> F
-PK -> B
-SO -> B
-PP -> H
-KO -> F
-VN -> S
-OS -> B
-NP -> C
-OV -> C
-CS -> P
-BH -> P
-SS -> P
-BB -> H
-PH -> V
-HN -> F
-KV -> H
-HC -> B
-BC -> P
-CK -> P
-PS -> O
-SH -> N
-FH -> N
-NN -> P
-HS -> O
-CB -> F
-HH -> F
-SB -> P
-NB -> F
-BO -> V
-PN -> H
-VP -> B
-SC -> C
-HB -> H
-FP -> O
-FC -> H
-KP -> B
-FB -> B
-VK -> F
-CV -> P
-VF -> V
-SP -> K
-CC -> K
-HV -> P
-NC -> N
-VH -> K
-PF -> P
-PB -> S
-BF -> K
-FF -> C
-FV -> V
-KS -> H
-VB -> F
-SV -> F
-HO -> B
-FN -> C
-SN -> F
-OB -> N
-KN -> P
-BV -> H
-ON -> N
-NF -> S
-OF -> P
-NV -> S
-VS -> C
-OO -> C
-BP -> H
-BK -> N
-CP -> N
-PC -> K
-CN -> H
-KB -> B
-BS -> P
-KK -> P
-SF -> V
-CO -> V
-CH -> P
-FO -> B
-FS -> F
-VO -> H
-NS -> F
-KC -> H
-VV -> K
-NO -> P
-OK -> F
-PO -> V
-FK -> H
-OP -> H
-PV -> N
-CF -> P
-NH -> K
-SK -> O
-KH -> P
-HP -> V
-OC -> V
-HK -> F";
-
-const SAMPLE: &str = "NNCB
-
-CH -> B
-HH -> N
-CB -> H
-NH -> C
-HB -> C
-HC -> B
-HN -> C
-NN -> C
-BH -> H
-NC -> B
-NB -> B
-BN -> B
-BB -> N
-BC -> B
-CC -> N
-CN -> C";
-
-type Transforms = HashMap<String,String>;
-type Counts = HashMap<String,i64>;
-
-pub fn parse_data(s: &str) -> (Transforms, Counts, String, String){
+use generate::Rng;
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
+
+pub type Transforms = HashMap<String,String>;
+pub type Counts = HashMap<String,i64>;
+
+pub fn parse_data(s: &str) -> Result<(Transforms, Counts, String, String), ParseError> {
     let mut transform = HashMap::new();
     let mut counts = HashMap::new();
 
-    let (template, formulas) = s.split_once("\n\n").unwrap();
+    let mut blocks = parser::blocks(s).into_iter();
+    let template = match blocks.next().unwrap_or_default().as_mut_slice() {
+        [c] => {
+            let start = c.clone();
+            let template = c.take_while(|x| x.is_ascii_uppercase());
+            if template.len() < 2 {
+                return Err(start.error("a template of at least two elements"));
+            }
+            c.end()?;
+            template
+        }
+        _ => return Err(ParseError::at_end(s, "a template line")),
+    };
     for i in 0..(template.len()-1) {
         let pair = template[i..=i+1].to_string();
         *counts.entry(pair).or_insert(0) += 1;
     }
-    for l in formulas.lines() {
-        let (front, back) = l.split_once(" -> ").unwrap();
+    let mut formulas = blocks.next().ok_or_else(|| ParseError::at_end(s, "a blank line and pair insertion rules"))?;
+    let mut pairs = vec![];
+    for c in formulas.iter_mut() {
+        let start = c.clone();
+        let front = c.take_while(|x| x.is_ascii_uppercase());
+        if front.len() != 2 {
+            return Err(start.error("a pair of elements"));
+        }
+        c.expect(" -> ")?;
+        let back = c.one_of("ABCDEFGHIJKLMNOPQRSTUVWXYZ")?;
+        c.end()?;
         transform.insert(front.to_string(), back.to_string());
+        pairs.push((start, front));
+    }
+    // The elements are the ones in the template and the ones rules insert.
+    // Every rule has to be for a pair of them, and every pair of them needs a
+    // rule, or a step could make a pair nothing says how to grow.
+    let mut elements: Vec<char> = template.chars().chain(transform.values().flat_map(|b| b.chars())).collect();
+    elements.sort_unstable();
+    elements.dedup();
+    for (start, front) in pairs {
+        if !front.chars().all(|e| elements.contains(&e)) {
+            return Err(start.error("a pair of elements from the template or the rules' insertions"));
+        }
     }
-    let front = template.chars().nth(0).unwrap().to_string();
+    for a in &elements {
+        for b in &elements {
+            let pair = format!("{}{}", a, b);
+            if !transform.contains_key(&pair) {
+                return Err(ParseError::at_end(s, &format!("a rule for {}", pair)));
+            }
+        }
+    }
+    let front = template.chars().next().unwrap().to_string();
     let back = template.chars().nth(template.len()-1).unwrap().to_string();
-    return (transform, counts, front, back);
-} 
+    return Ok((transform, counts, front, back));
+}
 
 pub fn step(counts: &Counts, transforms: &Transforms) -> Counts {
     let mut next = Counts::new();
     for (pair,value) in counts.iter() {
         let middle = &transforms[pair];
-        let new_left = format!("{}{}", pair.chars().nth(0).unwrap(), middle);
+        let new_left = format!("{}{}", pair.chars().next().unwrap(), middle);
         let new_right = format!("{}{}", middle, pair.chars().nth(1).unwrap());
         *next.entry(new_left).or_insert(0) += value;
         *next.entry(new_right).or_insert(0) += value;
@@ -158,7 +84,7 @@ pub fn step(counts: &Counts, transforms: &Transforms) -> Counts {
 pub fn sum(front: String, back: String, counts: &Counts) -> Counts {
     let mut sums = Counts::new();
     for (pair, value) in counts.iter() {
-        *sums.entry(pair.chars().nth(0).unwrap().to_string()).or_insert(0) += value;
+        *sums.entry(pair.chars().next().unwrap().to_string()).or_insert(0) += value;
         *sums.entry(pair.chars().nth(1).unwrap().to_string()).or_insert(0) += value;
     }
     let mut lol = Counts::new();
@@ -177,10 +103,10 @@ pub fn get_scores(counts: &Counts) -> (i64, i64, i64) {
         max = std::cmp::max(max,*v);
         min = std::cmp::min(min,*v);
     }
-    dbg!(max,min);
-    dbg!(max-min);
+    debug!("max {}, min {}", max, min);
     return (max, min, max-min);
 }
+/// The pair counts after `n` steps of pair insertion.
 pub fn take_steps(n: i32, counts: &Counts, transforms: &Transforms) -> Counts {
     let mut next = counts.clone();
     for _ in 1..=n {
@@ -188,47 +114,91 @@ pub fn take_steps(n: i32, counts: &Counts, transforms: &Transforms) -> Counts {
     }
     return next.clone();
 }
-pub fn main() {
-    let (transform, counts, front, back) = parse_data(SAMPLE);
-    let next = take_steps(10, &counts, &transform);
-    let sums = sum(front, back, &next);
-    dbg!(&sums);
-    let (max, min, diff) = get_scores(&sums);
-    assert_eq!(*sums.get("B").unwrap(), 1749);
-    assert_eq!(*sums.get("C").unwrap(), 298);
-    assert_eq!(*sums.get("H").unwrap(), 161);
-    assert_eq!(*sums.get("N").unwrap(), 865);
-    assert_eq!(max, 1749);
-    assert_eq!(min, 161);
-    assert_eq!(diff, 1588);
-
-    let (transform, counts, front, back) = parse_data(PUZZLE);
-    let next = take_steps(10, &counts, &transform);
-    let sums = sum(front, back, &next);
-    dbg!(&sums);
-    let (_,_, diff) = get_scores(&sums);
-    assert_eq!(diff, 3408);
-
-
-    let (transform, counts, front, back) = parse_data(SAMPLE);
-    let next = take_steps(40, &counts, &transform);
-    let sums = sum(front, back, &next);
-    dbg!(&sums);
-    let (max, min, diff) = get_scores(&sums);
-    assert_eq!(*sums.get("B").unwrap(), 2192039569602);
-    assert_eq!(*sums.get("H").unwrap(), 3849876073);
-    assert_eq!(max, 2192039569602);
-    assert_eq!(min, 3849876073);
-    assert_eq!(diff, 2188189693529);
-
-    let (transform, counts, front, back) = parse_data(PUZZLE);
-    let next = take_steps(40, &counts, &transform);
-    let sums = sum(front, back, &next);
-    dbg!(&sums);
-    let (max, min, diff) = get_scores(&sums);
-    assert_eq!(*sums.get("B").unwrap(), 2192039569602);
-    assert_eq!(*sums.get("H").unwrap(), 3849876073);
-    assert_eq!(max, 2192039569602);
-    assert_eq!(min, 3849876073);
-    assert_eq!(diff, 2188189693529);
-}
\ No newline at end of file
+
+/// The most common element count minus the least common after `n` steps.
+pub fn score_after(n: i32, (transform, counts, front, back): &(Transforms, Counts, String, String)) -> i64 {
+    let next = take_steps(n, counts, transform);
+    let sums = sum(front.clone(), back.clone(), &next);
+    let (_, _, diff) = get_scores(&sums);
+    return diff;
+}
+
+/// A polymer template `size` elements long and a rule for every pair.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let elements: Vec<char> = "BCFHKNOPSV".chars().collect();
+    let template: String = (0..size.max(2)).map(|_| *rng.pick(&elements)).collect();
+    // Every element is inserted by some rule, so every rule's pair can turn up.
+    let mut insertions = elements.clone();
+    while insertions.len() < elements.len() * elements.len() {
+        insertions.push(*rng.pick(&elements));
+    }
+    rng.shuffle(&mut insertions);
+    let mut rules = vec![];
+    for a in &elements {
+        for b in &elements {
+            rules.push(format!("{}{} -> {}", a, b, insertions[rules.len()]));
+        }
+    }
+    rng.shuffle(&mut rules);
+    return format!("{}\n\n{}", template, rules.join("\n"));
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = (Transforms, Counts, String, String);
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1(input: &Self::Input) -> Answer {
+        score_after(10, input).into()
+    }
+
+    fn part2(input: &Self::Input) -> Answer {
+        score_after(40, input).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+
+    fn sums_after(steps: i32, name: &str) -> Counts {
+        let (transform, counts, front, back) = parse_data(&input::named(14, name).unwrap()).unwrap();
+        let next = take_steps(steps, &counts, &transform);
+        return sum(front, back, &next);
+    }
+
+    #[test]
+    fn scores_sample() {
+        let sums = sums_after(10, "sample");
+        assert_eq!(*sums.get("B").unwrap(), 1749);
+        assert_eq!(*sums.get("C").unwrap(), 298);
+        assert_eq!(*sums.get("H").unwrap(), 161);
+        assert_eq!(*sums.get("N").unwrap(), 865);
+        assert_eq!(get_scores(&sums), (1749, 161, 1588));
+
+        let sums = sums_after(40, "sample");
+        assert_eq!(*sums.get("B").unwrap(), 2192039569602);
+        assert_eq!(*sums.get("H").unwrap(), 3849876073);
+        assert_eq!(get_scores(&sums), (2192039569602, 3849876073, 2188189693529));
+    }
+
+    #[test]
+    fn rejects_missing_rules() {
+        let e = parse_data("NN\n\nAB -> C").unwrap_err();
+        assert_eq!((e.line, e.column), (3, 1));
+        let e = parse_data("NC\n\nNC -> C\nCN -> N\nCC -> N").unwrap_err();
+        assert_eq!(e.expected, "a rule for NN");
+        assert!(parse_data(&generate(&mut Rng::new(3), 2)).is_ok());
+    }
+
+    #[test]
+    fn scores_puzzle() {
+        assert_eq!(get_scores(&sums_after(10, "puzzle")).2, 3408);
+        assert_eq!(get_scores(&sums_after(40, "puzzle")).2, 3724343376942);
+    }
+}
@@ -1,123 +1,115 @@
-use std::fmt;
-use std::collections::{HashSet, BinaryHeap};
+//! Day 15: Chiton. `lowest_risk` runs Dijkstra over the risk `Grid`, and `tiled`
+//! builds the five times bigger map for part 2.
 
-#[derive(Clone, PartialEq)]
-pub struct Grid {
-    values: Vec<Vec<i32>>,
-}
+use std::collections::{HashSet, BinaryHeap};
+use generate::Rng;
+use grid::{self, Dense, Pos};
+use parser::ParseError;
+use solution::{Answer, Solution};
 
-impl fmt::Debug for Grid {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut s = "\n".to_owned();
-        for (_,line) in self.values.iter().enumerate() {
-            for number in line {
-                s += &format!("{}", number.to_string());
-            }
-            s += "\n";
-        }
-        return write!(f, "{}", s);
-    }
-}
+pub type Grid = Dense<i32>;
 
-pub fn parse_data(s: &str) -> Grid {
-    let values = s
-        .lines()
-        .map(|x| {
-            x.chars()
-                .map(|y| y.to_string().parse::<i32>().unwrap())
-                .collect::<Vec<i32>>()
-        })
-        .collect::<Vec<Vec<i32>>>();
-    return Grid { values };
+pub fn parse_data(s: &str) -> Result<Grid, ParseError> {
+    grid::parse_digits(s)
 }
 
-const SAMPLE: &str = "1163751742
-1381373672
-2136511328
-3694931569
-7463417111
-1319128137
-1359912421
-3125421639
-1293138521
-2311944581";
-
 pub fn dijkstra(g: &Grid) -> Grid {
-    let mut visited: HashSet<(usize,usize)> = HashSet::new();
+    let mut visited: HashSet<Pos> = HashSet::new();
 
-    let mut node_costs: Grid = Grid{values: vec![vec![1000000000;g.values[0].len()]; g.values.len()]}; 
-    node_costs.values[0][0] = 0;
+    let mut node_costs: Grid = Grid::new(g.width(), g.height(), 1000000000);
+    node_costs[(0, 0)] = 0;
 
-    let mut pq: BinaryHeap<(i32,(usize,usize))> = BinaryHeap::new();
-    pq.push((0,(0,0)));
+    let mut pq: BinaryHeap<(i32, Pos)> = BinaryHeap::new();
+    pq.push((0, (0, 0)));
 
-    while let Some((cost,coord)) = pq.pop() {
-        //println!("Checking {:?} @ {}", coord, cost);
+    while let Some((cost, coord)) = pq.pop() {
         visited.insert(coord);
-        for diff in vec![(-1,0), (1,0), (0,1), (0,-1)] {
-            let px = coord.0 as i32 + diff.0; 
-            let py = coord.1 as i32 + diff.1;
-            if 0 <= px && px < g.values[0].len() as i32 && 0 <= py && py < g.values.len() as i32 {
-                let neighbor = (px as usize,py as usize);
-                let neighbor_cost = g.values[px as usize][py as usize];
-                if !visited.contains(&neighbor) {
-                    let new_cost = -cost + neighbor_cost;
-                    if new_cost < node_costs.values[px as usize][py as usize] {
-                        node_costs.values[px as usize][py as usize] = new_cost;
-                        pq.push((-new_cost, neighbor));
-                        //println!("Pushing {:?} @ {}", neighbor, new_cost);
-                    }
+        for neighbor in g.neighbours4(coord) {
+            if !visited.contains(&neighbor) {
+                let new_cost = -cost + g[neighbor];
+                if new_cost < node_costs[neighbor] {
+                    node_costs[neighbor] = new_cost;
+                    pq.push((-new_cost, neighbor));
                 }
             }
         }
-        //println!("");
     }
     return node_costs;
 }
 
+/// The full map, five times the size of the tile in each direction with the
+/// risks going up by one per tile.
 pub fn tiled(g: &Grid) -> Grid {
-    let len_x = g.values[0].len();
-    let len_y = g.values.len();
+    let len_x = g.width();
+    let len_y = g.height();
 
-    let mut tile: Grid = Grid{values: vec![vec![1000000000;len_x * 5]; len_y * 5]}; 
+    let mut tile: Grid = Grid::new(len_x * 5, len_y * 5, 1000000000);
     for tx in 0..5 {
         for ty in 0..5 {
-            for x in 0..len_x {
-                for y in 0..len_y {
-                    let px = x + len_x * tx;
-                    let py = y + len_y * ty;
-                    let mut new_value = g.values[x][y] + tx as i32 +ty as i32;
-                    if new_value >= 10 {
-                        new_value = new_value % 10 + 1;
-                    }
-                    tile.values[px][py] = new_value;
+            for ((x, y), value) in g.iter() {
+                let px = x + (len_x * tx) as i32;
+                let py = y + (len_y * ty) as i32;
+                let mut new_value = value + tx as i32 + ty as i32;
+                if new_value >= 10 {
+                    new_value = new_value % 10 + 1;
                 }
+                tile[(px, py)] = new_value;
             }
         }
     }
     return tile;
 }
 
-pub fn main() {
-    let puzzle_grid = parse_data(include_str!("../inputs/day15puzzle.txt"));
-    let sample_grid = parse_data(SAMPLE);
+/// The cost of reaching the bottom right corner.
+fn exit_cost(costs: &Grid) -> i32 {
+    return costs[(costs.width() as i32 - 1, costs.height() as i32 - 1)];
+}
 
-    let sample_costs =dijkstra(&sample_grid);
-    assert_eq!(40, *sample_costs.values.last().unwrap().last().unwrap());
+/// The lowest total risk of any path from the top left to the bottom right.
+pub fn lowest_risk(g: &Grid) -> i32 {
+    return exit_cost(&dijkstra(g));
+}
 
+/// A `size` square of risk levels.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let rows: Vec<String> = (0..size).map(|_| (0..size).map(|_| rng.range(1, 9).to_string()).collect()).collect();
+    return rows.join("\n");
+}
 
-    let puzzle_costs =dijkstra(&puzzle_grid);
-    assert_eq!(508, *puzzle_costs.values.last().unwrap().last().unwrap());
+pub struct Solver;
 
+impl Solution for Solver {
+    type Input = Grid;
 
-    let test = Grid{values: vec![vec![8]]};
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
 
-    let tiled_sample_grid = parse_data(include_str!("../inputs/day15sampletile.txt"));
-    assert_eq!(tiled_sample_grid, tiled(&sample_grid));
+    fn part1(g: &Self::Input) -> Answer {
+        lowest_risk(g).into()
+    }
 
-    let tiled_sample_costs = dijkstra(&tiled(&sample_grid));
-    assert_eq!(315, *tiled_sample_costs.values.last().unwrap().last().unwrap());
+    fn part2(g: &Self::Input) -> Answer {
+        lowest_risk(&tiled(g)).into()
+    }
+}
 
-    let tiled_puzzle_costs = dijkstra(&tiled(&puzzle_grid));
-    assert_eq!(315, *tiled_puzzle_costs.values.last().unwrap().last().unwrap());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+
+    #[test]
+    fn finds_lowest_risk() {
+        let sample_grid = parse_data(&input::named(15, "sample").unwrap()).unwrap();
+        assert_eq!(40, exit_cost(&dijkstra(&sample_grid)));
+
+        let tiled_sample_grid = parse_data(&input::named(15, "sampletile").unwrap()).unwrap();
+        assert_eq!(tiled_sample_grid, tiled(&sample_grid));
+        assert_eq!(315, exit_cost(&dijkstra(&tiled(&sample_grid))));
+
+        let puzzle_grid = parse_data(&input::named(15, "puzzle").unwrap()).unwrap();
+        assert_eq!(508, exit_cost(&dijkstra(&puzzle_grid)));
+        assert_eq!(2872, exit_cost(&dijkstra(&tiled(&puzzle_grid))));
+    }
 }
@@ -1,17 +1,22 @@
-use std::fmt;
-use colored::*;
+//! Day 16: Packet Decoder. `parse` decodes a BITS transmission into
+//! `PacketTypes`, `sum` adds up its versions, `eval` works out its value and
+//! `encode` writes a packet back out as hex.
+
+use generate::Rng;
+use parser::{Cursor, ParseError};
+use solution::{Answer, Solution};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Literal {
-    version: i64,
-    value: i64,
+    pub version: i64,
+    pub value: i64,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Operator {
-    version: i64,
-    operator: i64,
-    values: Vec<PacketTypes>,
+    pub version: i64,
+    pub operator: i64,
+    pub values: Vec<PacketTypes>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -49,7 +54,7 @@ fn str_to_bits(s: &str) -> Vec<u8> {
     })
 }
 
-type Bits = Vec<u8>;
+pub type Bits = Vec<u8>;
 
 fn bits_to_int(bs: Bits) -> i64 {
     let mut int = 0;
@@ -60,99 +65,109 @@ fn bits_to_int(bs: Bits) -> i64 {
     return int;
 }
 
-fn parse_literal(mut bits: Bits) -> (Bits, i64) {
+/// The next `n` bits, or `None` if the transmission stops first.
+fn take(bits: &mut Bits, n: usize) -> Option<Bits> {
+    if bits.len() < n {
+        return None;
+    }
+    Some(bits.drain(0..n).collect())
+}
+
+fn parse_literal(mut bits: Bits) -> Option<(Bits, i64)> {
     let mut value: Bits = vec![];
     loop {
-        let next: Bits = bits.drain(0..5).collect();
-        println!("Next part of literal: {:?}", next.clone());
+        let next: Bits = take(&mut bits, 5)?;
+        trace!("next part of literal: {:?}", next);
         value.append(&mut next[1..5].to_vec());
         if next[0] == 0 {
             break;
         }
     }
     let result = bits_to_int(value);
-    println!("Finished parsing as literal result: {}", result);
-    return (bits, result);
+    debug!("literal {}", result);
+    return Some((bits, result));
 }
 
-fn parse_values(mut bits: Bits) -> (Bits, Vec<PacketTypes>) {
-    let length_id: Bits = bits.drain(0..1).collect();
+fn parse_values(mut bits: Bits) -> Option<(Bits, Vec<PacketTypes>)> {
+    let length_id: Bits = take(&mut bits, 1)?;
+    let mut packets: Vec<PacketTypes> = vec![];
     if length_id[0] == 0 {
-        println!("Parsing packet as bytes");
-        let length: Bits = bits.drain(0..15).collect();
-        let l = bits_to_int(length.clone()) as usize;
-        println!("Taking {} bytes: {:?}", l, length);
-        let mut drained: Bits = bits.drain(0..l).collect();
-        let mut packets: Vec<PacketTypes> = vec![];
-        while drained.len() != 0 && !drained.iter().all(|x| *x == 0) {
-            let (new_bits, sub_packet) = parse_packet(drained.clone());
+        let length: Bits = take(&mut bits, 15)?;
+        let l = bits_to_int(length) as usize;
+        debug!("operator over {} bits", l);
+        let mut drained: Bits = take(&mut bits, l)?;
+        while !drained.is_empty() && !drained.iter().all(|x| *x == 0) {
+            let (new_bits, sub_packet) = parse_packet(drained)?;
             packets.push(sub_packet);
             drained = new_bits;
         }
-        println!("Finished parsing as byte\n");
-        return (bits, packets);
-    } else if length_id[0] == 1 {
-        println!("Parsing as sub_packets");
-        let length: Bits = bits.drain(0..11).collect();
-        let number_packets = bits_to_int(length.clone()) as usize;
-        println!("Parsing as {} sub_packets, {:?}", number_packets, length);
-        let mut packets: Vec<PacketTypes> = vec![];
+    } else {
+        let length: Bits = take(&mut bits, 11)?;
+        let number_packets = bits_to_int(length) as usize;
+        debug!("operator over {} sub-packets", number_packets);
         for _ in 0..number_packets {
-            let (new_bits, sub_packet) = parse_packet(bits);
+            let (new_bits, sub_packet) = parse_packet(bits)?;
             packets.push(sub_packet);
             bits = new_bits;
         }
-        println!("Finished parsing as sub_packet\n");
-        return (bits, packets);
-    } else {
-        unimplemented!();
     }
+    return Some((bits, packets));
 }
 
-fn parse_packet(mut bits: Bits) -> (Bits, PacketTypes) {
-    println!("Parsing into a packet: {:?}", bits);
-    let version_bits: Bits = bits.drain(0..3).collect();
+/// The packet at the start of `bits` and the bits after it, or `None` if the
+/// bits run out part way through it.
+fn parse_packet(mut bits: Bits) -> Option<(Bits, PacketTypes)> {
+    trace!("packet from {:?}", bits);
+    let version_bits: Bits = take(&mut bits, 3)?;
     let version = bits_to_int(version_bits.clone());
-    println!("{} w/ bits: {} {:?}", "Version".green(), version, version_bits);
+    debug!("version {} {:?}", version, version_bits);
 
-    let type_bits: Bits = bits.drain(0..3).collect();
+    let type_bits: Bits = take(&mut bits, 3)?;
     let ttype = bits_to_int(type_bits.clone());
-    println!("Type w/ bits: {} {:?}", ttype, type_bits);
+    debug!("type {} {:?}", ttype, type_bits);
 
     let result = match ttype {
         4 => {
-            println!("Parsing as a literal: {:?}", bits.clone());
-            let (new_bits, value) = parse_literal(bits.clone());
+            let (new_bits, value) = parse_literal(bits)?;
             bits = new_bits;
             PacketTypes::Literal(Literal {
-                version: version,
-                value: value,
+                version,
+                value,
             })
         }
         _ => {
-            println!("Parsing as a operator: {:?}", bits.clone());
-            let (new_bits, values) = parse_values(bits.clone());
+            let (new_bits, values) = parse_values(bits)?;
             bits = new_bits;
             PacketTypes::Operator(Operator {
-                version: version,
+                version,
                 operator: ttype,
-                values: values,
+                values,
             })
         }
     };
-    return (bits, result);
+    return Some((bits, result));
 }
 
-pub fn parse(s: &str) -> PacketTypes {
-    dbg!(s);
-    let bits: Bits = str_to_bits(s);
-    println!("Starting top parse of bits: {:?}", &bits);
-    let (new_bits, packet) = parse_packet(bits.clone());
-    assert!(new_bits.iter().all(|x| { *x == 0 }));
-    println!("Done! \n");
-    return packet;
+pub fn parse(s: &str) -> Result<PacketTypes, ParseError> {
+    let hex = s.trim();
+    let mut c = Cursor::new(1, hex);
+    while !c.is_empty() {
+        c.char_where(|x| x.is_ascii_digit() || ('A'..='F').contains(&x), "an upper case hex digit")?;
+    }
+    let bits: Bits = str_to_bits(hex);
+    trace!("top-level bits {:?}", bits);
+    let (new_bits, packet) = match parse_packet(bits.clone()) {
+        Some(parsed) => parsed,
+        None => return Err(ParseError::at_end(hex, "the rest of the packet")),
+    };
+    if !new_bits.iter().all(|x| { *x == 0 }) {
+        let used = bits.len() - new_bits.len();
+        return Err(ParseError::new(1, used / 4 + 1, "only zero padding after the packet"));
+    }
+    return Ok(packet);
 }
 
+/// The sum of the version numbers of every packet.
 pub fn sum(p: PacketTypes) -> i64 {
     match p {
         PacketTypes::Literal(Literal{version, value: _}) => version,
@@ -173,41 +188,21 @@ pub fn sum(p: PacketTypes) -> i64 {
 // Packets with type ID 6 are less than packets - their value is 1 if the value of the first sub-packet is less than the value of the second sub-packet; otherwise, their value is 0. These packets always have exactly two sub-packets.
 // Packets with type ID 7 are equal to packets - their value is 1 if the value of the first sub-packet is equal to the value of the second sub-packet; otherwise, their value is 0. These packets always have exactly two sub-packets.
 
+/// The value the packet's expression works out to.
 pub fn eval(p: PacketTypes) -> i64 {
-    if let PacketTypes::Literal(Literal{value, version}) = p{
+    if let PacketTypes::Literal(Literal{value, version: _}) = p{
         return value;
     }
-    else if let PacketTypes::Operator(Operator{values, version, operator}) =p {
+    else if let PacketTypes::Operator(Operator{values, version: _, operator}) =p {
         let vs: Vec<i64> = values.iter().map(|x| {eval(x.clone())}).collect();
         match operator {
-            0 => vs.iter().fold(0, |acc, x| {acc+x}),
-            1 => vs.iter().fold(1, |acc, x| {acc*x}),
+            0 => vs.iter().sum(),
+            1 => vs.iter().product(),
             2 => vs.iter().fold(1000000000, |acc, x| {std::cmp::min(acc,*x)}),
             3 => vs.iter().fold(0, |acc, x| {std::cmp::max(acc,*x)}),
-            5 => {
-                if vs[0] > vs[1] {
-                    1
-                }
-                else {
-                    0
-                }
-            },
-            6 => {
-                if vs[0] < vs[1] {
-                    1
-                }
-                else {
-                    0
-                }
-            },
-            7 => {
-                if vs[0] == vs[1] {
-                    1
-                }
-                else {
-                    0
-                }
-            }
+            5 => (vs[0] > vs[1]) as i64,
+            6 => (vs[0] < vs[1]) as i64,
+            7 => (vs[0] == vs[1]) as i64,
             _ => 0
         }
     }
@@ -216,79 +211,227 @@ pub fn eval(p: PacketTypes) -> i64 {
     }
 }
 pub fn evals(s: &str) -> i64 {
-    let p: PacketTypes = parse(s);
+    let p: PacketTypes = parse(s).unwrap();
     return eval(p);
 }
-pub fn main() {
-    let d = parse("D2FE28");
-    assert_eq!(
-        d,
-        PacketTypes::Literal(Literal {
-            version: 6,
-            value: 2021
-        })
-    );
-    assert_eq!(sum(d), 6);
-
-    let t = parse("38006F45291200");
-    assert_eq!(
-        t,
-        PacketTypes::Operator(Operator {
-            version: 1,
-            operator: 6,
-            values: vec![
-                PacketTypes::Literal(Literal {
-                    version: 6,
-                    value: 10
-                }),
-                PacketTypes::Literal(Literal {
-                    version: 2,
-                    value: 20
-                })
-            ]
-        })
-    );
-    assert_eq!(sum(t),9);
-
-    let e = parse("EE00D40C823060");
-    assert_eq!(
-        e,
-        PacketTypes::Operator(Operator {
-            version: 7,
-            operator: 3,
-            values: vec![
-                PacketTypes::Literal(Literal {
-                    version: 2,
-                    value: 1
-                }),
-                PacketTypes::Literal(Literal {
-                    version: 4,
-                    value: 2
-                }),
-                PacketTypes::Literal(Literal {
-                    version: 1,
-                    value: 3
-                })
-            ]
+/// Writes a literal's value in groups of four bits, each but the last one
+/// flagged as having more to come.
+fn push_literal(bits: &mut Bits, value: u64) {
+    let groups = (64 - value.leading_zeros() as usize).div_ceil(4).max(1);
+    for i in (0..groups).rev() {
+        push_bits(bits, (i > 0) as u64, 1);
+        push_bits(bits, (value >> (4 * i)) & 0xf, 4);
+    }
+}
+
+/// Pads the bits out to whole bytes and writes them as hex.
+fn to_hex(mut bits: Bits) -> String {
+    bits.resize(bits.len().div_ceil(8) * 8, 0);
+    let nibbles: Vec<String> = bits.chunks(4).map(|n| format!("{:X}", bits_to_int(n.to_vec()))).collect();
+    return nibbles.concat();
+}
+
+fn write_packet(p: &PacketTypes, by_count: bool, bits: &mut Bits) {
+    match p {
+        PacketTypes::Literal(Literal { version, value }) => {
+            push_bits(bits, *version as u64, 3);
+            push_bits(bits, 4, 3);
+            push_literal(bits, *value as u64);
+        }
+        PacketTypes::Operator(Operator { version, operator, values }) => {
+            push_bits(bits, *version as u64, 3);
+            push_bits(bits, *operator as u64, 3);
+            let mut children = vec![];
+            for v in values {
+                write_packet(v, by_count, &mut children);
+            }
+            if by_count {
+                push_bits(bits, 1, 1);
+                push_bits(bits, values.len() as u64, 11);
+            } else {
+                push_bits(bits, 0, 1);
+                push_bits(bits, children.len() as u64, 15);
+            }
+            bits.append(&mut children);
+        }
+    }
+}
+
+/// Writes a packet back out as a transmission, with every operator giving
+/// the number of its sub-packets if `by_count` and their length in bits
+/// otherwise. The inverse of `parse`.
+pub fn encode(p: &PacketTypes, by_count: bool) -> String {
+    let mut bits = vec![];
+    write_packet(p, by_count, &mut bits);
+    return to_hex(bits);
+}
+
+/// Values stay under this, so sums and products of them cannot overflow.
+const GENERATED_LIMIT: i64 = 1 << 40;
+
+fn push_bits(bits: &mut Bits, value: u64, width: usize) {
+    for i in (0..width).rev() {
+        bits.push(((value >> i) & 1) as u8);
+    }
+}
+
+/// Writes a packet with `budget` packets in it, counting itself, and gives
+/// back its value.
+fn generate_packet(rng: &mut Rng, budget: usize, bits: &mut Bits) -> i64 {
+    push_bits(bits, rng.below(8), 3);
+    if budget <= 1 {
+        let width = rng.range(1, 16);
+        let value = rng.below(1 << width);
+        push_bits(bits, 4, 3);
+        push_literal(bits, value);
+        return value as i64;
+    }
+    let comparison = budget >= 3 && rng.chance(1, 4);
+    let count = if comparison { 2 } else { (rng.range(1, 4) as usize).min(budget - 1) };
+    let mut budgets = vec![1; count];
+    for _ in count..budget - 1 {
+        budgets[rng.index(count)] += 1;
+    }
+    let mut children = vec![];
+    let values: Vec<i64> = budgets.iter().map(|b| generate_packet(rng, *b, &mut children)).collect();
+    let sum: i64 = values.iter().sum();
+    let product = values.iter().try_fold(1i64, |p, v| p.checked_mul(*v).filter(|p| *p < GENERATED_LIMIT));
+    let (operator, value) = match (comparison, rng.below(4), product) {
+        (true, _, _) => match rng.range(5, 7) {
+            5 => (5, (values[0] > values[1]) as i64),
+            6 => (6, (values[0] < values[1]) as i64),
+            _ => (7, (values[0] == values[1]) as i64),
+        },
+        (false, 1, Some(product)) => (1, product),
+        (false, 3, _) => (3, *values.iter().max().unwrap()),
+        (false, 2, _) => (2, *values.iter().min().unwrap()),
+        _ if sum < GENERATED_LIMIT => (0, sum),
+        _ => (2, *values.iter().min().unwrap()),
+    };
+    push_bits(bits, operator, 3);
+    if children.len() < 1 << 15 && rng.chance(1, 2) {
+        push_bits(bits, 0, 1);
+        push_bits(bits, children.len() as u64, 15);
+    } else {
+        push_bits(bits, 1, 1);
+        push_bits(bits, count as u64, 11);
+    }
+    bits.append(&mut children);
+    return value;
+}
+
+/// A transmission of `size` packets, padded out to whole bytes.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut bits = vec![];
+    generate_packet(rng, size.max(1), &mut bits);
+    return to_hex(bits);
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = PacketTypes;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(p: &Self::Input) -> Answer {
+        sum(p.clone()).into()
+    }
+
+    fn part2(p: &Self::Input) -> Answer {
+        eval(p.clone()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+    use proptest::prelude::*;
+
+    fn literal(version: i64, value: i64) -> PacketTypes {
+        PacketTypes::Literal(Literal { version, value })
+    }
+
+    /// Packets of up to a few levels, with literals small enough for
+    /// `bits_to_int` to read back.
+    fn packet() -> impl Strategy<Value = PacketTypes> {
+        let leaf = (0i64..8, 0i64..1 << 60).prop_map(|(version, value)| literal(version, value));
+        leaf.prop_recursive(4, 32, 4, |inner| {
+            let operator = prop::sample::select(vec![0i64, 1, 2, 3, 5, 6, 7]);
+            (0i64..8, operator, prop::collection::vec(inner, 1..=4)).prop_map(|(version, operator, values)| {
+                PacketTypes::Operator(Operator { version, operator, values })
+            })
         })
-    );
-    assert_eq!(sum(e),14);
-
-    assert_eq!(16, sum(parse("8A004A801A8002F478")));
-    assert_eq!(12, sum(parse("620080001611562C8802118E34")));
-    assert_eq!(23, sum(parse("C0015000016115A2E0802F182340")));
-    assert_eq!(31, sum(parse("A0016C880162017C3686B18A3D4780")));
-    let p = "C20D7900A012FB9DA43BA00B080310CE3643A0004362BC1B856E0144D234F43590698FF31D249F87B8BF1AD402389D29BA6ED6DCDEE59E6515880258E0040A7136712672454401A84CE65023D004E6A35E914BF744E4026BF006AA0008742985717440188AD0CE334D7700A4012D4D3AE002532F2349469100708010E8AD1020A10021B0623144A20042E18C5D88E6009CF42D972B004A633A6398CE9848039893F0650048D231EFE71E09CB4B4D4A00643E200816507A48D244A2659880C3F602E2080ADA700340099D0023AC400C30038C00C50025C00C6015AD004B95002C400A10038C00A30039C0086002B256294E0124FC47A0FC88ACE953802F2936C965D3005AC01792A2A4AC69C8C8CA49625B92B1D980553EE5287B3C9338D13C74402770803D06216C2A100760944D8200008545C8FB1EC80185945D9868913097CAB90010D382CA00E4739EDF7A2935FEB68802525D1794299199E100647253CE53A8017C9CF6B8573AB24008148804BB8100AA760088803F04E244480004323BC5C88F29C96318A2EA00829319856AD328C5394F599E7612789BC1DB000B90A480371993EA0090A4E35D45F24E35D45E8402E9D87FFE0D9C97ED2AF6C0D281F2CAF22F60014CC9F7B71098DFD025A3059200C8F801F094AB74D72FD870DE616A2E9802F800FACACA68B270A7F01F2B8A6FD6035004E054B1310064F28F1C00F9CFC775E87CF52ADC600AE003E32965D98A52969AF48F9E0C0179C8FE25D40149CC46C4F2FB97BF5A62ECE6008D0066A200D4538D911C401A87304E0B4E321005033A77800AB4EC1227609508A5F188691E3047830053401600043E2044E8AE0008443F84F1CE6B3F133005300101924B924899D1C0804B3B61D9AB479387651209AA7F3BC4A77DA6C519B9F2D75100017E1AB803F257895CBE3E2F3FDE014ABC";
-    assert_eq!(960, sum(parse(p)));
-
-    assert_eq!(3, evals("C200B40A82"));
-    assert_eq!(54, evals("04005AC33890"));
-    assert_eq!(7, evals("880086C3E88112"));
-    assert_eq!(9, evals("CE00C43D881120"));
-    assert_eq!(1, evals("D8005AC2A8F0"));
-    assert_eq!(0, evals("F600BC2D8F"));
-    assert_eq!(0, evals("9C005AC2F8F0"));
-    assert_eq!(1, evals("9C0141080250320F1802104A08"));
-
-    assert_eq!(12301926782560, evals(p));
+    }
+
+    proptest! {
+        #[test]
+        fn encode_round_trips(p in packet(), by_count in any::<bool>()) {
+            prop_assert_eq!(parse(&encode(&p, by_count)).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn parses_examples() {
+        let d = parse("D2FE28").unwrap();
+        assert_eq!(d, literal(6, 2021));
+        assert_eq!(encode(&d, false), "D2FE28");
+        assert_eq!(sum(d), 6);
+
+        let t = parse("38006F45291200").unwrap();
+        assert_eq!(
+            t,
+            PacketTypes::Operator(Operator {
+                version: 1,
+                operator: 6,
+                values: vec![literal(6, 10), literal(2, 20)],
+            })
+        );
+        assert_eq!(encode(&t, false), "38006F45291200");
+        assert_eq!(sum(t), 9);
+
+        let e = parse("EE00D40C823060").unwrap();
+        assert_eq!(
+            e,
+            PacketTypes::Operator(Operator {
+                version: 7,
+                operator: 3,
+                values: vec![literal(2, 1), literal(4, 2), literal(1, 3)],
+            })
+        );
+        assert_eq!(encode(&e, true), "EE00D40C823060");
+        assert_eq!(sum(e), 14);
+    }
+
+    #[test]
+    fn rejects_cut_short_transmissions() {
+        for hex in ["D2", "8", "38006F4529", ""] {
+            assert_eq!(parse(hex).unwrap_err().expected, "the rest of the packet", "{}", hex);
+        }
+    }
+
+    #[test]
+    fn sums_versions() {
+        assert_eq!(16, sum(parse("8A004A801A8002F478").unwrap()));
+        assert_eq!(12, sum(parse("620080001611562C8802118E34").unwrap()));
+        assert_eq!(23, sum(parse("C0015000016115A2E0802F182340").unwrap()));
+        assert_eq!(31, sum(parse("A0016C880162017C3686B18A3D4780").unwrap()));
+        assert_eq!(960, sum(parse(&input::named(16, "puzzle").unwrap()).unwrap()));
+    }
+
+    #[test]
+    fn evaluates() {
+        assert_eq!(3, evals("C200B40A82"));
+        assert_eq!(54, evals("04005AC33890"));
+        assert_eq!(7, evals("880086C3E88112"));
+        assert_eq!(9, evals("CE00C43D881120"));
+        assert_eq!(1, evals("D8005AC2A8F0"));
+        assert_eq!(0, evals("F600BC2D8F"));
+        assert_eq!(0, evals("9C005AC2F8F0"));
+        assert_eq!(1, evals("9C0141080250320F1802104A08"));
+        assert_eq!(12301926782560, evals(&input::named(16, "puzzle").unwrap()));
+    }
 }
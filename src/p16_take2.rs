@@ -1,9 +1,9 @@
-use nom::{
-    bytes::complete::{tag, take_while_m_n},
-    combinator::map_res,
-    sequence::tuple,
-    IResult,
-};
+//! Day 16 again, decoding the BITS transmission with `nom` bit parsers into a
+//! `Packet` instead of draining a bit vector.
+
+use nom::{bits::complete::take, multi::count, IResult};
+use parser::{Cursor, ParseError};
+use solution::{Answer, Solution};
 
 #[derive(Debug, PartialEq)]
 pub struct Operator {
@@ -16,38 +16,163 @@ pub struct Operator {
 pub struct Literal {
     pub version: u8,
     pub type_id: u8,
-    pub value: u8,
+    pub value: u64,
 }
 
 #[derive(Debug, PartialEq)]
-enum Packet {
+pub enum Packet {
     Operator(Operator),
     Literal(Literal),
 }
 
-fn hex_color(input: &str) -> IResult<&str, Color> {
-    let (input, _) = tag("#")(input)?;
-    let (input, (red, green, blue)) = tuple((hex_primary, hex_primary, hex_primary))(input)?;
-  
-    Ok((input, Color { red, green, blue }))
-  }
-
-pub fn main() {
-    assert_eq!(
-        parse("D2FE28"),
-        Packet::Literal(Literal {
-            version: 6,
-            type_id: 4,
-            value: 2021
-        })
-    );
-
-    // assert_eq!(
-    //     parse("38006F45291200"),
-    //     Packet::Operator(Operator {
-    //         version: 1,
-    //         type_id: 6,
-    //         values: vec![10, 20]
-    //     })
-    // );
+pub type BitInput<'a> = (&'a [u8], usize);
+
+fn hex_to_bytes(s: &str) -> Result<Vec<u8>, ParseError> {
+    let mut c = Cursor::new(1, s);
+    let mut bytes = vec![];
+    while !c.is_empty() {
+        let high = c.char_where(|x| x.is_ascii_hexdigit(), "a hex digit")?;
+        let low = c.char_where(|x| x.is_ascii_hexdigit(), "a hex digit")?;
+        bytes.push((high.to_digit(16).unwrap() * 16 + low.to_digit(16).unwrap()) as u8);
+    }
+    return Ok(bytes);
+}
+
+fn bits_left(input: BitInput) -> usize {
+    input.0.len() * 8 - input.1
+}
+
+fn literal_value(mut input: BitInput) -> IResult<BitInput, u64> {
+    let mut value: u64 = 0;
+    loop {
+        let (rest, more): (BitInput, u8) = take(1usize)(input)?;
+        let (rest, group): (BitInput, u64) = take(4usize)(rest)?;
+        value = (value << 4) | group;
+        input = rest;
+        if more == 0 {
+            return Ok((input, value));
+        }
+    }
+}
+
+fn sub_packets(input: BitInput) -> IResult<BitInput, Vec<Packet>> {
+    let (input, length_id): (BitInput, u8) = take(1usize)(input)?;
+    if length_id == 0 {
+        let (mut input, length): (BitInput, usize) = take(15usize)(input)?;
+        if length > bits_left(input) {
+            return Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Eof)));
+        }
+        let end = bits_left(input) - length;
+        let mut packets = vec![];
+        while bits_left(input) > end {
+            let (rest, p) = packet(input)?;
+            packets.push(p);
+            input = rest;
+        }
+        Ok((input, packets))
+    } else {
+        let (input, number): (BitInput, usize) = take(11usize)(input)?;
+        count(packet, number)(input)
+    }
+}
+
+fn packet(input: BitInput) -> IResult<BitInput, Packet> {
+    let (input, version): (BitInput, u8) = take(3usize)(input)?;
+    let (input, type_id): (BitInput, u8) = take(3usize)(input)?;
+    if type_id == 4 {
+        let (input, value) = literal_value(input)?;
+        Ok((input, Packet::Literal(Literal { version, type_id, value })))
+    } else {
+        let (input, values) = sub_packets(input)?;
+        Ok((input, Packet::Operator(Operator { version, type_id, values })))
+    }
+}
+
+pub fn parse(s: &str) -> Result<Packet, ParseError> {
+    let bytes = hex_to_bytes(s.trim())?;
+    match packet((&bytes, 0)) {
+        Ok((_, p)) => Ok(p),
+        Err(_) => Err(ParseError::at_end(s.trim(), "the rest of the packet")),
+    }
+}
+
+pub fn version_sum(p: &Packet) -> u64 {
+    match p {
+        Packet::Literal(l) => l.version as u64,
+        Packet::Operator(o) => o.version as u64 + o.values.iter().map(version_sum).sum::<u64>(),
+    }
+}
+
+pub fn value(p: &Packet) -> u64 {
+    match p {
+        Packet::Literal(l) => l.value,
+        Packet::Operator(o) => {
+            let vs: Vec<u64> = o.values.iter().map(value).collect();
+            match o.type_id {
+                0 => vs.iter().sum(),
+                1 => vs.iter().product(),
+                2 => *vs.iter().min().unwrap(),
+                3 => *vs.iter().max().unwrap(),
+                5 => (vs[0] > vs[1]) as u64,
+                6 => (vs[0] < vs[1]) as u64,
+                7 => (vs[0] == vs[1]) as u64,
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Packet;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse(input)
+    }
+
+    fn part1(p: &Self::Input) -> Answer {
+        version_sum(p).into()
+    }
+
+    fn part2(p: &Self::Input) -> Answer {
+        value(p).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_examples() {
+        assert_eq!(
+            parse("D2FE28").unwrap(),
+            Packet::Literal(Literal {
+                version: 6,
+                type_id: 4,
+                value: 2021
+            })
+        );
+
+        assert_eq!(
+            parse("38006F45291200").unwrap(),
+            Packet::Operator(Operator {
+                version: 1,
+                type_id: 6,
+                values: vec![
+                    Packet::Literal(Literal {
+                        version: 6,
+                        type_id: 4,
+                        value: 10
+                    }),
+                    Packet::Literal(Literal {
+                        version: 2,
+                        type_id: 4,
+                        value: 20
+                    })
+                ]
+            })
+        );
+    }
 }
@@ -1,46 +1,41 @@
-use std::fmt;
-use std::collections::HashSet;
-
-const SAMPLE: &str = "target area: x=20..30, y=-10..-5";
-const PUZZLE: &str = "target area: x=153..199, y=-114..-75";
+//! Day 17: Trick Shot. `calc_direct` works out the highest a probe can go
+//! from its vertical velocity alone, and `brute_force` counts every velocity
+//! that hits the target `Rect`.
 
-pub type Rect = ((i32, i32), (i32, i32));
-pub fn parse_data(s: &str) -> Rect {
-    let (xs, ys) = s[12..].split_once(",").unwrap();
+use std::collections::HashSet;
+use generate::Rng;
+use geometry::{self, Point2};
+use parser::{Cursor, ParseError};
+use solution::{Answer, Solution};
 
-    let xt = xs.trim()[2..].split_once("..").unwrap();
-    let x1 = xt.0.parse::<i32>().unwrap();
-    let x2 = xt.1.parse::<i32>().unwrap();
+pub type Rect = geometry::Rect<i32>;
+pub type Point = Point2<i32>;
 
-    let yt = ys.trim()[2..].split_once("..").unwrap();
-    let y1 = yt.0.parse::<i32>().unwrap();
-    let y2 = yt.1.parse::<i32>().unwrap();
-    return ((x1, x2), (y1, y2));
+/// `target area: x=20..30, y=-10..-5`
+pub fn parse_data(s: &str) -> Result<Rect, ParseError> {
+    let mut c = Cursor::new(1, s.trim());
+    c.expect("target area: x=")?;
+    let x1 = c.int::<i32>()?;
+    c.expect("..")?;
+    let x2 = c.int::<i32>()?;
+    c.expect(", y=")?;
+    let y1 = c.int::<i32>()?;
+    c.expect("..")?;
+    let y2 = c.int::<i32>()?;
+    c.end()?;
+    return Ok(Rect::new(Point::new(x1, y1), Point::new(x2, y2)));
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Trajectory {
-    p: Point,
-    dp: Point,
-}
-
-#[derive(PartialEq, Clone)]
-pub struct Point {
-    x: i32,
-    y: i32,
-}
-
-impl fmt::Debug for Point {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({},{})", self.x, self.y)
-    }
+    pub p: Point,
+    pub dp: Point,
 }
 
 impl Iterator for Trajectory {
     type Item = Point;
     fn next(&mut self) -> Option<Self::Item> {
-        self.p.x = self.p.x + self.dp.x;
-        self.p.y = self.p.y + self.dp.y;
+        self.p += self.dp;
 
         self.dp.y -= 1;
         if self.dp.x > 0 {
@@ -48,90 +43,100 @@ impl Iterator for Trajectory {
         } else if self.dp.x < 0 {
             self.dp.x += 1
         }
-        Some(self.p.clone())
+        Some(self.p)
     }
 }
 
 pub fn trajectory(p: Point) -> Trajectory {
     Trajectory {
-        p: Point { x: 0, y: 0 },
+        p: Point::ZERO,
         dp: p,
     }
 }
 
 pub fn point_within_rect(p: Point, r: Rect) -> bool {
-    return r.0 .0 <= p.x && p.x <= r.0 .1 && r.1 .0 <= p.y && p.y <= r.1 .1;
+    return r.contains(p);
 }
 
+/// Whether `p` has passed `r` on either axis, on a side away from the origin.
 pub fn point_beyond_rect(p: Point, r: Rect) -> bool {
-    dbg!(&p, r);
+    trace!("{:?} against {:?}", p, r);
 
-    if r.0 .1 > 0 && p.x > r.0 .1 {
+    if r.max.x > 0 && p.x > r.max.x {
         return true;
     }
 
-    if r.0 .0 < 0 && p.x < r.0 .0 {
+    if r.min.x < 0 && p.x < r.min.x {
         return true;
     }
 
-    if r.1 .1 > 0 && p.x > r.1 .1 {
+    if r.max.y > 0 && p.y > r.max.y {
         return true;
     }
 
-    if r.1 .0 < 0 && p.x < r.1 .0 {
+    if r.min.y < 0 && p.y < r.min.y {
         return true;
     }
 
     return false;
 }
 
-pub fn display_run(t: Point, r: Rect) {
-    let mut path = trajectory(t);
-    let mut points: Vec<Point> = path.take(100).collect::<Vec<Point>>();
-    // loop {
-    //     let next = path.next().unwrap();
-    //     points.push(next.clone());
-    //     if point_beyond_rect(next, r) {
-    //         break;
-    //     }
-    // }
+/// The first 100 steps from `t` around the start, `S`, and the target, `T`,
+/// with the highest point reached under it.
+pub fn display_run(t: Point, r: Rect) -> String {
+    let points: Vec<Point> = trajectory(t).take(100).collect();
     let mut max_y = 0;
+    let mut s = String::new();
     for y in (-50..47).rev() {
         for x in 0..35 {
-            let p = Point { x, y };
+            let p = Point::new(x, y);
             if points.contains(&p) {
-                print!("#");
+                s.push('#');
                 max_y = std::cmp::max(max_y, y);
             } else if point_within_rect(p, r) {
-                print!("T");
+                s.push('T');
             } else if x == 0 && y == 0 {
-                print!("S");
+                s.push('S');
             } else {
-                print!(".");
+                s.push('.');
             }
         }
-        println!("");
+        s.push('\n');
     }
-    dbg!(max_y);
+    s += &format!("max y {}", max_y);
+    return s;
 }
 
+/// Whether a probe thrown at `t` enters the target within 1000 steps, and if
+/// so how high it gets. The probe stops being followed once it is falling
+/// below the target, or has not got in and never will for its columns, as
+/// nothing after that changes the answer.
 pub fn do_run(t: Point, r: Rect) -> (bool, i32) {
     let mut path = trajectory(t);
-    let mut points: Vec<Point> = path.take(1000).collect::<Vec<Point>>();
-    let enters = points.iter().any(|p| point_within_rect(p.clone(), r));
+    let mut enters = false;
+    let mut max_y = 0;
+    for _ in 0..1000 {
+        let p = path.next().unwrap();
+        enters |= point_within_rect(p, r);
+        max_y = std::cmp::max(max_y, p.y);
+        let dp = path.dp;
+        let falling_below = dp.y < 0 && p.y < r.min.y;
+        let missed = (dp.x >= 0 && p.x > r.max.x) || (dp.x <= 0 && p.x < r.min.x);
+        if falling_below || (missed && !enters) {
+            break;
+        }
+    }
     if !enters {
         return (false, -1);
-    } else {
-        let max_y = points.iter().fold(0, |acc, p| std::cmp::max(acc, p.y));
-        return (true, max_y);
     }
+    return (true, max_y);
 }
 
 pub fn check_rect(r: Rect) -> i32 {
     let mut max_y = 0;
     for x in 0..200 {
         for y in 0..1000 {
-            let (entered, max) = do_run(Point { x, y }, r);
+            let (entered, max) = do_run(Point::new(x, y), r);
             if entered {
                 max_y = std::cmp::max(max, max_y);
             }
@@ -140,17 +145,19 @@ pub fn check_rect(r: Rect) -> i32 {
     return max_y;
 }
 
+/// The highest a probe gets while still passing through the target's rows,
+/// looking at the vertical velocity alone.
 pub fn calc_direct(r: Rect) -> i32 {
     let mut max_height = 0;
     for start in 1..1000 {
         let mut dy = start;
         let mut y = 0;
         let mut local_max = 0;
-        while y >= r.1.1 {
+        while y >= r.max.y {
             y += dy;
             dy -= 1;
             local_max = std::cmp::max(local_max, y);
-            if r.1.0 <= y && y <= r.1.1 {
+            if r.min.y <= y && y <= r.max.y {
                 max_height = std::cmp::max(local_max, max_height);
             }
         }
@@ -166,7 +173,7 @@ pub fn get_xs(r: Rect) -> HashSet<i32> {
         while dx != 0 {
             x += dx;
             dx -= 1;
-            if r.0.0 <= x && x <= r.0.1 {
+            if r.min.x <= x && x <= r.max.x {
                 xs.insert(start);
             }
         }
@@ -179,10 +186,10 @@ pub fn get_ys(r: Rect) -> HashSet<i32> {
     for start in -1000..1000 {
         let mut dy = start;
         let mut y = 0;
-        while y > r.1.1 {
+        while y > r.max.y {
             y += dy;
             dy -= 1;
-            if r.1.0 <= y && y <= r.1.1 {
+            if r.min.y <= y && y <= r.max.y {
                 ys.insert(start);
             }
         }
@@ -190,13 +197,14 @@ pub fn get_ys(r: Rect) -> HashSet<i32> {
     return ys;
 }
 
+/// How many initial velocities hit the target.
 pub fn brute_force(r: Rect) -> i32 {
     let mut num_match = 0; 
     for x in get_xs(r) {
         for y in get_ys(r) {
-            let (matched, _) = do_run(Point{x,y}, r);
+            let (matched, _) = do_run(Point::new(x, y), r);
             if matched {
-                println!("{} {}", x, y);
+                trace!("hit with {} {}", x, y);
                 num_match += 1
             }
         }
@@ -204,25 +212,89 @@ pub fn brute_force(r: Rect) -> i32 {
     return num_match;
 }
 
-pub fn main() {
-    let sample = parse_data(SAMPLE);
-    assert_eq!(((20, 30), (-10, -5)), sample);
+/// Part 1 by trying every velocity with `check_rect`, for checking
+/// `calc_direct` against.
+pub fn highest_by_search(input: &str) -> Result<Answer, ParseError> {
+    Ok(check_rect(parse_data(input)?).into())
+}
+
+/// A target area below and to the right of the probe, like the real ones.
+/// Its columns take in a triangular number, as `calc_direct` counts on: some
+/// probe then stops dead above the target and can be thrown as high as the
+/// rows allow. The size is ignored.
+pub fn generate(rng: &mut Rng, _size: usize) -> String {
+    let n = rng.range(6, 17);
+    let stop = n * (n + 1) / 2;
+    let x1 = (stop - rng.range(0, 20)).max(20);
+    let x2 = (stop + rng.range(0, 40)).min(199).max(x1 + 5);
+    let y1 = rng.range(-150, -10);
+    let y2 = (y1 + rng.range(5, 40)).min(-5);
+    return format!("target area: x={}..{}, y={}..{}", x1, x2, y1, y2);
+}
+
+pub struct Solver;
 
-    let puzzle = parse_data(PUZZLE);
-    assert_eq!(((153, 199), (-114, -75)), puzzle);
+impl Solution for Solver {
+    type Input = Rect;
 
-    //let run1 = trajectory(Point { x: 6, y: 9 });
-    //assert_eq!(run1.take(10).collect::<Vec<Point>>(), vec![]);
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1(r: &Self::Input) -> Answer {
+        calc_direct(*r).into()
+    }
+
+    fn part2(r: &Self::Input) -> Answer {
+        brute_force(*r).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use differential::{self, Plan};
+    use input;
+
+    #[test]
+    fn finds_trajectories() {
+        let sample = parse_data(&input::named(17, "sample").unwrap()).unwrap();
+        assert_eq!(Rect::new(Point::new(20, -10), Point::new(30, -5)), sample);
 
-    //assert!(point_beyond_rect(Point{x: 2,y: 2}, ((0,1),(0,1))));
-    //assert!(!point_beyond_rect(Point{x: 2,y: 2}, ((0,2),(0,2))));
-    assert_eq!(do_run(Point { x: 6, y: 9 }, sample), (true, 45));
-    assert_eq!(do_run(Point { x: 1, y: 9 }, sample), (false, -1));
-    //assert_eq!(check_rect(sample), 45);
-    //assert_eq!(check_rect(puzzle), 45);
-    assert_eq!(45, calc_direct(sample));
-    assert_eq!(6441, calc_direct(puzzle));
+        let puzzle = parse_data(&input::named(17, "puzzle").unwrap()).unwrap();
+        assert_eq!(Rect::new(Point::new(153, -114), Point::new(199, -75)), puzzle);
 
-    assert_eq!(112, brute_force(sample));
-    assert_eq!(3146, brute_force(puzzle));
+        assert_eq!(do_run(Point::new(6, 9), sample), (true, 45));
+        assert_eq!(do_run(Point::new(1, 9), sample), (false, -1));
+        let drawn = display_run(Point::new(6, 9), sample);
+        assert!(drawn.ends_with("\nmax y 45"));
+        assert_eq!(drawn.lines().nth(1), Some(".....................#............."));
+        assert_eq!(45, calc_direct(sample));
+        assert_eq!(6441, calc_direct(puzzle));
+
+        assert_eq!(112, brute_force(sample));
+        assert_eq!(3186, brute_force(puzzle));
+    }
+
+    #[test]
+    fn checks_each_axis_against_its_own_bounds() {
+        let target = Rect::new(Point::new(20, -10), Point::new(30, -5));
+        assert!(!point_beyond_rect(Point::new(25, -7), target));
+        assert!(!point_beyond_rect(Point::new(-15, 0), target));
+        assert!(point_beyond_rect(Point::new(31, 0), target));
+        assert!(point_beyond_rect(Point::new(0, -11), target));
+
+        let above = Rect::new(Point::new(-30, 5), Point::new(-20, 10));
+        assert!(!point_beyond_rect(Point::new(-25, 7), above));
+        assert!(point_beyond_rect(Point::new(-31, 0), above));
+        assert!(point_beyond_rect(Point::new(0, 11), above));
+    }
+
+    #[test]
+    fn generated_targets_agree_with_search() {
+        for c in differential::comparisons().iter().filter(|c| c.day == 17) {
+            let found = differential::find_disagreement(c, Plan { cases: 20, seed: 0 });
+            assert!(found.is_none(), "{} disagrees on {}", c.name(), found.unwrap().input);
+        }
+    }
 }
@@ -1,35 +1,37 @@
-type SnailNum = Vec<(u32, u32)>;
+//! Day 18: Snailfish. A `SnailNum` is its regular numbers with their depths;
+//! `sum` adds and reduces two of them and `magnitude` scores the result.
 
-pub fn parse_data(s: &str) -> SnailNum {
-    let mut data: SnailNum = vec![];
-    let mut depth = 0;
-    let mut placeholder: String = "".to_string();
-    for c in s.trim().chars() {
-        let mut flagged = false;
-        let current_depth = depth;
-        match c {
-            '[' => {
-                depth += 1;
-                flagged = true;
-            }
-            ']' => {
-                depth -= 1;
-                flagged = true;
-            }
-            ',' => {
-                flagged = true;
-            }
-            _ => {
-                placeholder += &c.to_string();
-            }
-        }
-        if flagged && placeholder.len() != 0 {
-            let t = placeholder.parse::<u32>().unwrap();
-            data.push((t, current_depth));
-            placeholder = "".to_string();
-        }
+use generate::Rng;
+use parser::{self, Cursor, ParseError};
+use solution::{Answer, Solution};
+
+pub type SnailNum = Vec<(u32, u32)>;
+
+/// Reads a number or a `[left,right]` pair nested `depth` pairs deep onto the
+/// end of `data`, as (value, depth) for every regular number in it.
+fn parse_element(c: &mut Cursor, depth: u32, data: &mut SnailNum) -> Result<(), ParseError> {
+    if c.eat("[") {
+        parse_element(c, depth + 1, data)?;
+        c.expect(",")?;
+        parse_element(c, depth + 1, data)?;
+        c.expect("]")?;
+    } else {
+        data.push((c.int::<u32>()?, depth));
     }
-    return data;
+    return Ok(());
+}
+
+pub fn parse_number(c: &mut Cursor) -> Result<SnailNum, ParseError> {
+    let mut data: SnailNum = vec![];
+    parse_element(c, 0, &mut data)?;
+    return Ok(data);
+}
+
+pub fn parse_data(s: &str) -> Result<SnailNum, ParseError> {
+    let mut c = Cursor::new(1, s.trim());
+    let data = parse_number(&mut c)?;
+    c.end()?;
+    return Ok(data);
 }
 
 pub fn add(mut l: SnailNum, mut r: SnailNum) -> SnailNum {
@@ -79,50 +81,29 @@ pub fn split(mut s: SnailNum) -> SnailNum {
     return s;
 }
 
-pub fn n_s(s: &str, n: i32) -> String {
-    let mut r: String = "".to_string();
-    for _ in 0..n {
-        r += s;
+/// Writes the number (or pair) at `depth` that starts at `sn[*i]`, moving `i`
+/// past it. The inverse of `parse_element`.
+fn dis_element(sn: &SnailNum, i: &mut usize, depth: u32, s: &mut String) {
+    let (value, d) = sn[*i];
+    if d == depth {
+        s.push_str(&value.to_string());
+        *i += 1;
+    } else {
+        s.push('[');
+        dis_element(sn, i, depth + 1, s);
+        s.push(',');
+        dis_element(sn, i, depth + 1, s);
+        s.push(']');
     }
-    return r;
 }
 
+/// Writes a snailfish number back out the way it is read.
 pub fn dis(sn: SnailNum) -> String {
-    let mut s: String = "".to_string();
-    let mut depth = 0;
-    let mut in_pair = false;
-    for v in sn.clone() {
-        let diff: i32 = v.1 as i32 - depth as i32;
-        if diff > 0 {
-            s += &n_s("[", diff);
-            s += format!("{},", v.0).as_str();
-            depth = v.1;
-            in_pair = true;
-        } else if diff < 0 {
-            s += format!("{}", v.0).as_str();
-            s += &n_s("]", -diff);
-            s += ",";
-            depth = v.1;
-            in_pair = true;
-        } else {
-            if in_pair {
-                s += format!("{}]", v.0).as_str();
-                in_pair = false;
-            } else {
-                s += format!("[{}", v.0).as_str();
-                in_pair = true;
-            }
-            s += ",";
-        }
-    }
-    // remove last ,
-    s = s[0..s.len() - 1].to_string();
-
-    let diff = sn.last().unwrap().1 - 1;
-    dbg!(depth, diff);
-    s += &n_s("]", diff as i32);
+    let mut s = String::new();
+    dis_element(&sn, &mut 0, 0, &mut s);
     return s;
 }
+
 pub fn reduce(s: SnailNum) -> SnailNum {
     let mut new_num = s;
     //println!("reducing:  {:?}", new_num.clone());
@@ -144,22 +125,29 @@ pub fn reduce(s: SnailNum) -> SnailNum {
     return new_num;
 }
 
+/// Adds two snailfish numbers and reduces the result.
 pub fn sum(l: SnailNum, r: SnailNum) -> SnailNum {
     return reduce(add(l, r));
 }
 
 pub fn add_list(s: &str) -> SnailNum {
-    let numbers: Vec<SnailNum> = s.lines().map(|l| parse_data(l)).collect();
+    let numbers: Vec<SnailNum> = s.lines().map(|l| parse_data(l).unwrap()).collect();
+    return add_all(&numbers);
+}
+
+pub fn add_all(numbers: &[SnailNum]) -> SnailNum {
     let mut acc: SnailNum = numbers[0].clone();
-    for x in 1..numbers.len() {
+    for x in &numbers[1..] {
         //println!("{:?}", acc.clone());
-        //println!("+{:?}", numbers[x].clone());
-        acc = sum(acc, numbers[x].clone());
+        //println!("+{:?}", x.clone());
+        acc = sum(acc, x.clone());
         //println!("={:?}\n\n", acc.clone());
     }
     return acc;
 }
 //3l+2r
+/// Three times the magnitude of the left element plus twice the right, all the
+/// way down.
 pub fn magnitude(mut sn: SnailNum) -> u32 {
     //println!("finding magnitude of: {:?}", sn);
     let max_depth = sn.iter().fold(0, |acc, (_, d)| std::cmp::max(acc, *d));
@@ -185,229 +173,229 @@ pub fn magnitude(mut sn: SnailNum) -> u32 {
     //println!("finished: {:?}\n", sn);
     return sn[0].0;
 }
-pub fn main() {
-    let samples = "
-    [1,2]
-    [[1,2],3]
-    [9,[8,7]]
-    [[1,9],[8,5]]
-    [[[[1,2],[3,4]],[[5,6],[7,8]]],9]
-    [[[9,[3,8]],[[0,9],6]],[[[3,7],[4,9]],3]]
-    [[[[1,3],[5,3]],[[1,3],[8,7]]],[[[4,9],[6,9]],[[8,2],[7,3]]]]";
-    let eqs = vec![
-        vec![(1, 1), (2, 1)],
-        vec![(1, 2), (2, 2), (3, 1)],
-        vec![(9, 1), (8, 2), (7, 2)],
-        vec![(1, 2), (9, 2), (8, 2), (5, 2)],
-        vec![
-            (1, 4),
-            (2, 4),
-            (3, 4),
-            (4, 4),
-            (5, 4),
-            (6, 4),
-            (7, 4),
-            (8, 4),
-            (9, 1),
-        ],
-        vec![
-            (9, 3),
-            (3, 4),
-            (8, 4),
-            (0, 4),
-            (9, 4),
-            (6, 3),
-            (3, 4),
-            (7, 4),
-            (4, 4),
-            (9, 4),
-            (3, 2),
-        ],
-        vec![
-            (1, 4),
-            (3, 4),
-            (5, 4),
-            (3, 4),
-            (1, 4),
-            (3, 4),
-            (8, 4),
-            (7, 4),
-            (4, 4),
-            (9, 4),
-            (6, 4),
-            (9, 4),
-            (8, 4),
-            (2, 4),
-            (7, 4),
-            (3, 4),
-        ],
-    ];
-    for (i, line) in samples.trim().lines().enumerate() {
-        let l = line.trim();
-        let result = parse_data(l);
-        assert_eq!(result, eqs[i]);
-        //assert_eq!(dis(result), l);
+pub fn largest_pair_magnitude(numbers: &[SnailNum]) -> u32 {
+    let mut max_m = 0;
+    for (i, x) in numbers.iter().enumerate() {
+        for (j, y) in numbers.iter().enumerate() {
+            if i != j {
+                max_m = std::cmp::max(max_m, magnitude(sum(x.clone(), y.clone())));
+            }
+        }
     }
+    return max_m;
+}
 
-    let l = parse_data("[1,2]");
-    let r = parse_data("[[3,4],5]");
-    let s = add(l, r);
-    assert_eq!(s, vec![(1, 2), (2, 2), (3, 3), (4, 3), (5, 2)]);
-
-    let before = parse_data("[[[[[9,8],1],2],3],4]");
-    let after = parse_data("[[[[0,9],2],3],4]");
-    assert_eq!(explode(before), after);
-
-    let before = parse_data("[7,[6,[5,[4,[3,2]]]]]");
-    let after = parse_data("[7,[6,[5,[7,0]]]]");
-    assert_eq!(explode(before), after);
-
-    let before = parse_data("[[6,[5,[4,[3,2]]]],1]");
-    let after = parse_data("[[6,[5,[7,0]]],3]");
-    assert_eq!(explode(before), after);
-    let before = parse_data("[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]");
-    let after = parse_data("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]");
-    assert_eq!(explode(before), after);
-
-    let before = parse_data("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]");
-    let after = parse_data("[[3,[2,[8,0]]],[9,[5,[7,0]]]]");
-    assert_eq!(explode(before), after);
-
-    // println!("\nnn how it should be!!");
-    // println!("reducing: {:?}", parse_data("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]"));
-    // println!("after explode: {:?}", parse_data("[[[[0,7],4],[7,[[8,4],9]]],[1,1]]"));
-    // println!("after explode: {:?}", parse_data("[[[[0,7],4],[15,[0,13]]],[1,1]]"));
-    // println!("after split: {:?}", parse_data("[[[[0,7],4],[[7,8],[0,13]]],[1,1]]"));
-    // println!("after split: {:?}", parse_data("[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]"));
-    // println!("after explode: {:?}", parse_data("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]"));
-
-    // println!("\n");
-    let l = parse_data("[[[[4,3],4],4],[7,[[8,4],9]]]");
-    let r = parse_data("[1,1]");
-    let s = parse_data("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
-    assert_eq!(sum(l, r), s);
-    //println!("{:?}", before.clone());
-    //println!("{:?}", reduce(before.clone()));
-    //println!("{:?}", after.clone());
-
-    let listed = add_list(
-        "[1,1]
-    [2,2]
-    [3,3]
-    [4,4]",
-    );
-    let sum = parse_data("[[[[1,1],[2,2]],[3,3]],[4,4]]");
-    assert_eq!(reduce(listed), sum);
-
-    let listed = add_list(
-        "[1,1]
-    [2,2]
-    [3,3]
-    [4,4]
-    [5,5]",
-    );
-    let sum = parse_data("[[[[3,0],[5,3]],[4,4]],[5,5]]");
-    assert_eq!(reduce(listed), sum);
-
-    let listed = add_list(
-        "[1,1]
-    [2,2]
-    [3,3]
-    [4,4]
-    [5,5]
-    [6,6]",
-    );
-    let sum = parse_data("[[[[5,0],[7,4]],[5,5]],[6,6]]");
-    assert_eq!(reduce(listed), sum);
-
-    //println!("\n\n\n-------");
-
-    let listed = add_list(
-        "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]
-    [7,[[[3,7],[4,3]],[[6,3],[8,8]]]]
-    [[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]
-    [[[[2,4],7],[6,[0,5]]],[[[6,8],[2,8]],[[2,1],[4,5]]]]
-    [7,[5,[[3,8],[1,4]]]]
-    [[2,[2,2]],[8,[8,1]]]
-    [2,9]
-    [1,[[[9,3],9],[[9,0],[0,7]]]]
-    [[[5,[7,4]],7],1]
-    [[[[4,2],2],6],[8,7]]",
-    );
-    let sum = parse_data("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]");
-    assert_eq!(reduce(listed), sum);
-
-    let pairs = [
-        ("[9,1]", 29),
-        ("[1,9]", 21),
-        ("[[9,1],[1,9][", 129),
-        ("[[1,2],[[3,4],5]]", 143),
-        ("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", 1384),
-        ("[[[[1,1],[2,2]],[3,3]],[4,4]]", 445),
-        ("[[[[3,0],[5,3]],[4,4]],[5,5]]", 791),
-        ("[[[[5,0],[7,4]],[5,5]],[6,6]]", 1137),
-        (
-            "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]",
-            3488,
-        ),
-    ];
-    for (s, v) in pairs.iter() {
-        assert_eq!(magnitude(parse_data(s)), *v);
+fn generate_element(rng: &mut Rng, depth: u32) -> String {
+    if depth == 4 || (depth > 0 && rng.chance(1, 3)) {
+        return rng.below(10).to_string();
     }
+    format!("[{},{}]", generate_element(rng, depth + 1), generate_element(rng, depth + 1))
+}
 
-    let listed = add_list("[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
-    [[[5,[2,8]],4],[5,[[9,9],0]]]
-    [6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
-    [[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
-    [[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
-    [[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
-    [[[[5,4],[7,7]],8],[[8,3],8]]
-    [[9,3],[[9,9],[6,[4,9]]]]
-    [[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
-    [[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]");
-    let sum = parse_data("[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]");
-    let result = 4140;
-    assert_eq!(listed,sum);
-    assert_eq!(magnitude(listed), result);
-
-    let puzzle = include_str!("../inputs/day18puzzle.txt");
-    let listed = add_list(puzzle.clone());
-    assert_eq!(magnitude(listed), 4235);
-
-    // part two 
-    let numbers2 = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
-    [[[5,[2,8]],4],[5,[[9,9],0]]]
-    [6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
-    [[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
-    [[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
-    [[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
-    [[[[5,4],[7,7]],8],[[8,3],8]]
-    [[9,3],[[9,9],[6,[4,9]]]]
-    [[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
-    [[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]";
-
-
-    let numbers: Vec<SnailNum> = puzzle.lines().map(|l| parse_data(l)).collect();
-    let mut max_m = 0;
-    let mut max_x : SnailNum = vec![];
-    let mut max_y : SnailNum = vec![];
-    for x in numbers.clone() {
-        for y in numbers.clone() {
-            if x.clone() != y {
-                let m = magnitude(reduce(add(x.clone(),y.clone())));
-                //dbg!(m);
-                if m > max_m {
-                    max_m = std::cmp::max(m, max_m);
-                    max_x = x.clone();
-                    max_y = y.clone();
+/// `size` snailfish numbers that are already reduced.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let lines: Vec<String> = (0..size).map(|_| generate_element(rng, 0)).collect();
+    return lines.join("\n");
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Vec<SnailNum>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parser::each_line(input, parse_number)
+    }
+
+    fn part1(numbers: &Self::Input) -> Answer {
+        magnitude(add_all(numbers)).into()
+    }
+
+    fn part2(numbers: &Self::Input) -> Answer {
+        largest_pair_magnitude(numbers).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+    use proptest::prelude::*;
+
+    /// A snailfish number as a tree, to check the flat form against.
+    #[derive(Debug, Clone)]
+    enum Snail {
+        Regular(u32),
+        Pair(Box<Snail>, Box<Snail>),
+    }
+
+    impl Snail {
+        fn text(&self) -> String {
+            match self {
+                Snail::Regular(n) => n.to_string(),
+                Snail::Pair(l, r) => format!("[{},{}]", l.text(), r.text()),
+            }
+        }
+
+        fn flatten(&self, depth: u32, out: &mut SnailNum) {
+            match self {
+                Snail::Regular(n) => out.push((*n, depth)),
+                Snail::Pair(l, r) => {
+                    l.flatten(depth + 1, out);
+                    r.flatten(depth + 1, out);
                 }
             }
         }
     }
-    println!("max magnitude {}", max_m); 
-    println!("max x:  {:?}", max_x.clone());
-    println!("max y:  {:?}", max_y.clone());
 
-    assert_eq!(3993, magnitude(parse_data("[[[[7,8],[6,6]],[[6,0],[7,7]]],[[[7,8],[8,8]],[[7,9],[0,6]]]]")));
+    /// Numbers nested deeper and holding bigger regular numbers than reduced
+    /// ones do, like the sums on their way to being reduced.
+    fn snail() -> impl Strategy<Value = Snail> {
+        let element = (0u32..100).prop_map(Snail::Regular).prop_recursive(6, 64, 2, |inner| {
+            (inner.clone(), inner).prop_map(|(l, r)| Snail::Pair(Box::new(l), Box::new(r)))
+        });
+        (element.clone(), element).prop_map(|(l, r)| Snail::Pair(Box::new(l), Box::new(r)))
+    }
+
+    proptest! {
+        #[test]
+        fn parses_every_number(n in snail()) {
+            let mut flat = vec![];
+            n.flatten(0, &mut flat);
+            prop_assert_eq!(parse_data(&n.text()).unwrap(), flat);
+        }
+
+        #[test]
+        fn dis_round_trips(n in snail()) {
+            let mut flat = vec![];
+            n.flatten(0, &mut flat);
+            prop_assert_eq!(dis(flat.clone()), n.text());
+            prop_assert_eq!(parse_data(&dis(flat.clone())).unwrap(), flat);
+        }
+    }
+
+    #[test]
+    fn parses_samples() {
+        let samples = [
+            ("[1,2]", vec![(1, 1), (2, 1)]),
+            ("[[1,2],3]", vec![(1, 2), (2, 2), (3, 1)]),
+            ("[9,[8,7]]", vec![(9, 1), (8, 2), (7, 2)]),
+            ("[[1,9],[8,5]]", vec![(1, 2), (9, 2), (8, 2), (5, 2)]),
+            (
+                "[[[[1,2],[3,4]],[[5,6],[7,8]]],9]",
+                vec![(1, 4), (2, 4), (3, 4), (4, 4), (5, 4), (6, 4), (7, 4), (8, 4), (9, 1)],
+            ),
+            (
+                "[[[9,[3,8]],[[0,9],6]],[[[3,7],[4,9]],3]]",
+                vec![(9, 3), (3, 4), (8, 4), (0, 4), (9, 4), (6, 3), (3, 4), (7, 4), (4, 4), (9, 4), (3, 2)],
+            ),
+            (
+                "[[[[1,3],[5,3]],[[1,3],[8,7]]],[[[4,9],[6,9]],[[8,2],[7,3]]]]",
+                vec![
+                    (1, 4), (3, 4), (5, 4), (3, 4), (1, 4), (3, 4), (8, 4), (7, 4),
+                    (4, 4), (9, 4), (6, 4), (9, 4), (8, 4), (2, 4), (7, 4), (3, 4),
+                ],
+            ),
+        ];
+        for (line, expected) in samples.iter() {
+            let parsed = parse_data(line).unwrap();
+            assert_eq!(&parsed, expected);
+            assert_eq!(&dis(parsed), line);
+        }
+    }
+
+    #[test]
+    fn adds() {
+        let l = parse_data("[1,2]").unwrap();
+        let r = parse_data("[[3,4],5]").unwrap();
+        assert_eq!(add(l, r), vec![(1, 2), (2, 2), (3, 3), (4, 3), (5, 2)]);
+    }
+
+    #[test]
+    fn explodes() {
+        let examples = [
+            ("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]"),
+            ("[7,[6,[5,[4,[3,2]]]]]", "[7,[6,[5,[7,0]]]]"),
+            ("[[6,[5,[4,[3,2]]]],1]", "[[6,[5,[7,0]]],3]"),
+            ("[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]", "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]"),
+            ("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]", "[[3,[2,[8,0]]],[9,[5,[7,0]]]]"),
+        ];
+        for (before, after) in examples.iter() {
+            assert_eq!(explode(parse_data(before).unwrap()), parse_data(after).unwrap());
+        }
+    }
+
+    #[test]
+    fn sums() {
+        let l = parse_data("[[[[4,3],4],4],[7,[[8,4],9]]]").unwrap();
+        let r = parse_data("[1,1]").unwrap();
+        assert_eq!(sum(l, r), parse_data("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]").unwrap());
+
+        let lists = [
+            ("[1,1]\n[2,2]\n[3,3]\n[4,4]", "[[[[1,1],[2,2]],[3,3]],[4,4]]"),
+            ("[1,1]\n[2,2]\n[3,3]\n[4,4]\n[5,5]", "[[[[3,0],[5,3]],[4,4]],[5,5]]"),
+            ("[1,1]\n[2,2]\n[3,3]\n[4,4]\n[5,5]\n[6,6]", "[[[[5,0],[7,4]],[5,5]],[6,6]]"),
+        ];
+        for (list, expected) in lists.iter() {
+            assert_eq!(reduce(add_list(list)), parse_data(expected).unwrap());
+        }
 
+        let listed = add_list(
+            "[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]
+            [7,[[[3,7],[4,3]],[[6,3],[8,8]]]]
+            [[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]
+            [[[[2,4],7],[6,[0,5]]],[[[6,8],[2,8]],[[2,1],[4,5]]]]
+            [7,[5,[[3,8],[1,4]]]]
+            [[2,[2,2]],[8,[8,1]]]
+            [2,9]
+            [1,[[[9,3],9],[[9,0],[0,7]]]]
+            [[[5,[7,4]],7],1]
+            [[[[4,2],2],6],[8,7]]",
+        );
+        let expected = parse_data("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]").unwrap();
+        assert_eq!(reduce(listed), expected);
+    }
+
+    #[test]
+    fn magnitudes() {
+        let pairs = [
+            ("[9,1]", 29),
+            ("[1,9]", 21),
+            ("[[9,1],[1,9]]", 129),
+            ("[[1,2],[[3,4],5]]", 143),
+            ("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", 1384),
+            ("[[[[1,1],[2,2]],[3,3]],[4,4]]", 445),
+            ("[[[[3,0],[5,3]],[4,4]],[5,5]]", 791),
+            ("[[[[5,0],[7,4]],[5,5]],[6,6]]", 1137),
+            ("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]", 3488),
+            ("[[[[7,8],[6,6]],[[6,0],[7,7]]],[[[7,8],[8,8]],[[7,9],[0,6]]]]", 3993),
+        ];
+        for (s, v) in pairs.iter() {
+            assert_eq!(magnitude(parse_data(s).unwrap()), *v);
+        }
+    }
+
+    #[test]
+    fn homework() {
+        let homework = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+            [[[5,[2,8]],4],[5,[[9,9],0]]]
+            [6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+            [[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+            [[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+            [[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+            [[[[5,4],[7,7]],8],[[8,3],8]]
+            [[9,3],[[9,9],[6,[4,9]]]]
+            [[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+            [[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]";
+        let listed = add_list(homework);
+        let expected = parse_data("[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]").unwrap();
+        assert_eq!(listed, expected);
+        assert_eq!(magnitude(listed), 4140);
+
+        let numbers: Vec<SnailNum> = homework.lines().map(|l| parse_data(l).unwrap()).collect();
+        assert_eq!(largest_pair_magnitude(&numbers), 3993);
+
+        let puzzle = input::named(18, "puzzle").unwrap();
+        assert_eq!(magnitude(add_list(&puzzle)), 4235);
+    }
 }
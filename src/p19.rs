@@ -1,71 +1,25 @@
-use std::collections::HashSet;
-use std::fmt;
-use std::ops::{Add, Mul, Sub};
-
-#[derive(Clone, Copy, PartialEq, Hash, Eq, PartialOrd, Ord)]
-pub struct Coord {
-    x: i32,
-    y: i32,
-    z: i32,
-}
-
-const ZERO: Coord = Coord { x: 0, y: 0, z: 0 };
-
-impl fmt::Debug for Coord {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "({},{},{})", self.x, self.y, self.z)
-    }
-}
-
-impl<'a, 'b> Add<&'b Coord> for &'a Coord {
-    type Output = Coord;
+//! Day 19: Beacon Scanner. `find_num_beacons` orients every `Scanner` against
+//! the ones already placed and gives back the beacons and scanner positions.
 
-    fn add(self, other: &'b Coord) -> Coord {
-        Coord {
-            x: self.x + other.x,
-            y: self.y + other.y,
-            z: self.z + other.z,
-        }
-    }
-}
-
-impl<'a, 'b> Sub<&'b Coord> for &'a Coord {
-    type Output = Coord;
-
-    fn sub(self, other: &'b Coord) -> Coord {
-        Coord {
-            x: self.x - other.x,
-            y: self.y - other.y,
-            z: self.z - other.z,
-        }
-    }
-}
-
-impl<'a, 'b> Mul<&'b Coord> for &'a Coord {
-    type Output = Coord;
+use std::collections::HashSet;
+use generate::Rng;
+use geometry::Point3;
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
 
-    fn mul(self, other: &'b Coord) -> Coord {
-        Coord {
-            x: self.x * other.x,
-            y: self.y * other.y,
-            z: self.z * other.z,
-        }
-    }
-}
+pub type Coord = Point3<i32>;
 
-type DistanceMap = Vec<HashSet<i32>>;
+pub type DistanceMap = Vec<HashSet<i32>>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Scanner {
-    id: usize,
-    signals: Vec<Coord>,
-    distances: DistanceMap,
+    pub id: usize,
+    pub signals: Vec<Coord>,
+    pub distances: DistanceMap,
 }
 
 pub fn distance_between(a: Coord, b: Coord) -> i32 {
-    let d = &a - &b;
-    let dd = &d * &d;
-    return dd.x + dd.y + dd.z;
+    return a.distance_squared(b);
 }
 
 pub fn distance_map(signals: Vec<Coord>) -> DistanceMap {
@@ -74,37 +28,40 @@ pub fn distance_map(signals: Vec<Coord>) -> DistanceMap {
         let distances = signals
             .clone()
             .iter()
-            .map(|x| distance_between(sig.clone(), x.clone()))
+            .map(|x| distance_between(sig, *x))
             .collect::<HashSet<i32>>();
         dm.push(distances);
     }
     return dm;
 }
 
-pub fn parse_data(s: &str) -> Vec<Scanner> {
-    let scanners: Vec<Scanner> = s
-        .split("\n\n")
-        .enumerate()
-        .map(|(id, x)| {
-            let signals = x.lines().collect::<Vec<&str>>()[1..]
-                .iter()
-                .map(|c| {
-                    let s: Vec<&str> = c.split(",").collect();
-                    let x = s[0].parse::<i32>().unwrap();
-                    let y = s[1].parse::<i32>().unwrap();
-                    let z = s[2].parse::<i32>().unwrap();
-                    Coord { x, y, z }
-                })
-                .collect::<Vec<Coord>>();
-            let distances = distance_map(signals.clone());
-            Scanner {
-                id,
-                signals,
-                distances,
-            }
-        })
-        .collect();
-    return scanners;
+pub fn parse_data(s: &str) -> Result<Vec<Scanner>, ParseError> {
+    let mut scanners: Vec<Scanner> = vec![];
+    for (id, block) in parser::blocks(s).into_iter().enumerate() {
+        let mut lines = block.into_iter();
+        let mut header = lines.next().ok_or_else(|| ParseError::at_end(s, "a scanner"))?;
+        header.expect("--- scanner ")?;
+        header.int::<usize>()?;
+        header.expect(" ---")?;
+        header.end()?;
+        let mut signals = vec![];
+        for mut c in lines {
+            let x = c.int::<i32>()?;
+            c.expect(",")?;
+            let y = c.int::<i32>()?;
+            c.expect(",")?;
+            let z = c.int::<i32>()?;
+            c.end()?;
+            signals.push(Coord { x, y, z });
+        }
+        let distances = distance_map(signals.clone());
+        scanners.push(Scanner {
+            id,
+            signals,
+            distances,
+        });
+    }
+    return Ok(scanners);
 }
 
 pub fn get_matching_overlap(s0: Scanner, s1: Scanner) -> Vec<(Coord, Coord)> {
@@ -114,7 +71,7 @@ pub fn get_matching_overlap(s0: Scanner, s1: Scanner) -> Vec<(Coord, Coord)> {
             let overlap = s0.distances[i0].intersection(&s1.distances[i1]);
             let count = overlap.clone().count();
             if count > 4 {
-                matched.push((sig1.clone(), sig2.clone()));
+                matched.push((*sig1, *sig2));
             }
         }
     }
@@ -127,23 +84,17 @@ pub fn rotate_coord(mut c: Coord, rotations: i32) -> Coord {
         1 => {
             //x stays the same
             //flip y and z
-            let tmp = c.y;
-            c.y = c.z;
-            c.z = tmp;
+            std::mem::swap(&mut c.y, &mut c.z);
         }
         2 => {
             // y stays the same
             //flip x and z
-            let tmp = c.x;
-            c.x = c.z;
-            c.z = tmp;
+            std::mem::swap(&mut c.x, &mut c.z);
         }
         3 => {
             //z stays the same
             // flip x and y
-            let tmp = c.x;
-            c.x = c.y;
-            c.y = tmp;
+            std::mem::swap(&mut c.x, &mut c.y);
         }
         4 => {
             // all move one left
@@ -165,46 +116,46 @@ pub fn rotate_coord(mut c: Coord, rotations: i32) -> Coord {
 }
 
 pub fn find_orientation(matched: Vec<(Coord, Coord)>) -> Option<(Coord, Coord, i32)> {
-    let f = matched[0].clone();
+    let f = matched[0];
     let a = f.0;
 
-    for sx in vec![-1, 1] {
-        for sy in vec![1, -1] {
-            for sz in vec![-1, 1] {
+    for sx in [-1, 1] {
+        for sy in [1, -1] {
+            for sz in [-1, 1] {
                 let s = Coord {
                     x: sx,
                     y: sy,
                     z: sz,
                 };
 
-                for rotations in vec![0, 1, 2, 3, 4, 5] {
+                for rotations in [0, 1, 2, 3, 4, 5] {
                     let mut new_b = f.1;
-                    new_b = &s * &new_b;
+                    new_b = s * new_b;
                     new_b = rotate_coord(new_b, rotations); 
 
-                    let d = &a - &new_b;
+                    let d = a - new_b;
 
                     let flipped = matched
                         .iter()
-                        .map(|(ia, ib)| (ia.clone(), &s * &ib))
+                        .map(|(ia, ib)| (*ia, s * *ib))
                         .collect::<Vec<(Coord, Coord)>>();
 
                     let rotated = flipped
                         .iter()
-                        .map(|(ia, ib)| (ia.clone(), rotate_coord(ib.clone(), rotations)))
+                        .map(|(ia, ib)| (*ia, rotate_coord(*ib, rotations)))
                         .collect::<Vec<(Coord, Coord)>>();
 
                     let shifted = rotated
                         .iter()
-                        .map(|(ia, ib)| (ia.clone(), &d + &ib))
+                        .map(|(ia, ib)| (*ia, d + *ib))
                         .collect::<Vec<(Coord, Coord)>>();
 
                     let distances = shifted
                         .iter()
-                        .map(|(ia, ib)| ia - ib)
+                        .map(|(ia, ib)| *ia - *ib)
                         .collect::<Vec<Coord>>();
 
-                    if distances.iter().all(|c| c == &ZERO) {
+                    if distances.iter().all(|c| *c == Coord::ZERO) {
                         return Some((d, s, rotations));
                     }
                 }
@@ -219,81 +170,14 @@ pub fn reorient(mut sc: Scanner, d: Coord, s: Coord, rotations: i32) -> Scanner
         .signals
         .iter()
         .map(|c| {
-            let mut new_c = &s * &c;
+            let mut new_c = s * *c;
             new_c = rotate_coord(new_c, rotations);
-            return &d + &new_c;
+            return d + new_c;
         })
         .collect();
     return sc;
 }
 
-pub fn test0() {
-    let repeated = parse_data(include_str!(
-        "../inputs/day19shouldhaveusedtheseearlier.txt"
-    ));
-
-    //orient yourself with rotations and s
-    //then swim
-    let first = repeated[0].clone();
-    for sc in &repeated[1..] {
-        let matched = get_matching_overlap(first.clone(), sc.clone());
-        let (d, s, rotations) = find_orientation(matched).unwrap();
-        let reoriented = reorient(sc.clone(), d, s, rotations);
-        assert_eq!(reoriented.signals, first.signals);
-    }
-}
-
-pub fn testa() {
-    let repeated = parse_data(include_str!("../inputs/day19syn.txt"));
-    let first = repeated[0].clone();
-    // first step is 1,1,-1 switched
-    // next step is rotation 2, xz switched
-    // step after that is shift down -1, -1, -1
-
-    let matched = get_matching_overlap(first.clone(), repeated[1].clone());
-    let (d, s, rotations) = find_orientation(matched).unwrap();
-    assert_eq!(rotations, 0);
-    assert_eq!(s, Coord { x: 1, y: 1, z: -1 });
-    assert_eq!(d, Coord { x: 0, y: 0, z: 0 });
-
-    let matched = get_matching_overlap(first.clone(), repeated[2].clone());
-    let (d, s, rotations) = find_orientation(matched).unwrap();
-    assert_eq!(rotations, 2);
-    assert_eq!(s, Coord { x: -1, y: 1, z: 1 });
-    assert_eq!(d, Coord { x: 0, y: 0, z: 0 });
-
-    let matched = get_matching_overlap(first.clone(), repeated[3].clone());
-    let (d, s, rotations) = find_orientation(matched).unwrap();
-    assert_eq!(rotations, 2);
-    assert_eq!(s, Coord { x: -1, y: 1, z: 1 });
-    assert_eq!(d, Coord { x: 1, y: 1, z: -1 });
-}
-
-pub fn test1() {
-    let sample2_scanners = parse_data(include_str!("../inputs/day19sample2.txt"));
-    let s0 = sample2_scanners[0].clone();
-    let s1 = sample2_scanners[1].clone();
-
-    let matched = get_matching_overlap(s0.clone(), s1.clone());
-    let (d, s, rotations) = find_orientation(matched).unwrap();
-    assert_eq!(
-        d,
-        Coord {
-            x: 68,
-            y: -1246,
-            z: -43,
-        }
-    );
-    assert_eq!(s, Coord { x: -1, y: 1, z: -1 });
-    assert_eq!(rotations, 0);
-    let reoriented_s1 = reorient(s1, d, s, rotations);
-
-    let rm = get_matching_overlap(s0, reoriented_s1);
-    let (rd, rs, _) = find_orientation(rm).unwrap();
-    assert_eq!(rd, Coord { x: 0, y: 0, z: 0 });
-    assert_eq!(rs, Coord { x: 1, y: 1, z: 1 });
-}
-
 // make sure that they are oriented together or else it will braek
 pub fn combine_scanner(mut l: Scanner, r: Scanner, d: Coord, s: Coord, rotations: i32) -> Scanner {
     let mut reoriented = reorient(r.clone(), d, s, rotations);
@@ -304,98 +188,25 @@ pub fn combine_scanner(mut l: Scanner, r: Scanner, d: Coord, s: Coord, rotations
     return l;
 }
 
-pub fn test2() {
-    let sample2_scanners = parse_data(include_str!("../inputs/day19sample2.txt"));
-    let mut s0 = sample2_scanners[0].clone();
-    let s1 = sample2_scanners[1].clone();
-    let s2 = sample2_scanners[2].clone();
-    let s3 = sample2_scanners[3].clone();
-    let s4 = sample2_scanners[4].clone();
-
-    //s1
-    let matched = get_matching_overlap(s0.clone(), s1.clone());
-    let (d, s, r) = find_orientation(matched).unwrap();
-
-    assert_eq!(r, 0);
-    assert_eq!(s, Coord { x: -1, y: 1, z: -1 });
-    assert_eq!(
-        d,
-        Coord {
-            x: 68,
-            y: -1246,
-            z: -43
-        }
-    );
-    s0 = combine_scanner(s0, s1, d, s, r);
-
-    //s4
-    let matched = get_matching_overlap(s0.clone(), s4.clone());
-    let (d, s, r) = find_orientation(matched).unwrap();
-
-    assert_eq!(r, 4);
-    assert_eq!(s, Coord { x: 1, y: -1, z: -1 });
-    assert_eq!(
-        d,
-        Coord {
-            x: -20,
-            y: -1133,
-            z: 1061
-        }
-    );
-    s0 = combine_scanner(s0, s4, d, s, r);
-
-    //s2
-    let matched = get_matching_overlap(s0.clone(), s2.clone());
-    let (d, s, r) = find_orientation(matched).unwrap();
-
-    assert_eq!(r, 1);
-    assert_eq!(s, Coord { x: -1, y: 1, z: 1 });
-    assert_eq!(
-        d,
-        Coord {
-            x: 1105,
-            y: -1205,
-            z: 1229
-        }
-    );
-    s0 = combine_scanner(s0, s2, d, s, r);
-
-    //s3
-    let matched = get_matching_overlap(s0.clone(), s3.clone());
-    let (d, s, r) = find_orientation(matched).unwrap();
-
-    assert_eq!(r, 0);
-    assert_eq!(s, Coord { x: -1, y: 1, z: -1 });
-    assert_eq!(
-        d,
-        Coord {
-            x: -92,
-            y: -2380,
-            z: -20
-        }
-    );
-    s0 = combine_scanner(s0, s3, d, s, r);
-    assert_eq!(s0.signals.len(), 79);
-}
-
+/// How many beacons there are, and the position of every scanner but the first.
 pub fn find_num_beacons(scanners: Vec<Scanner>) -> (usize, Vec<Coord>) {
     let mut spots: Vec<Coord> = vec![];
     let mut mega_scanner = scanners[0].clone();
     let mut unoriented: Vec<Scanner> = scanners[1..].to_vec();
 
-    while unoriented.len() != 0 {
+    while !unoriented.is_empty() {
         let mut leftovers: Vec<Scanner> = vec![];
         for sc in unoriented.clone() {
             let matched = get_matching_overlap(mega_scanner.clone(), sc.clone());
             if matched.len() >= 12 {
                 match find_orientation(matched) {
                     Some((d, s, r)) => {
-                        println!("Found a good match {}", sc.id);
+                        debug!("oriented scanner {}", sc.id);
                         spots.push(d);
                         mega_scanner = combine_scanner(mega_scanner, sc, d, s, r);
                     }
                     None => {
-                        println!("Found a unorientable match {}", sc.id);
+                        debug!("scanner {} overlaps but cannot be oriented yet", sc.id);
                         leftovers.push(sc);
                     }
                 }
@@ -409,34 +220,252 @@ pub fn find_num_beacons(scanners: Vec<Scanner>) -> (usize, Vec<Coord>) {
     return (mega_scanner.signals.len(), spots);
 }
 
-pub fn main() {
-    let _sample1_scanners = parse_data(include_str!("../inputs/day19sample1.txt"));
-    let sample2_scanners = parse_data(include_str!("../inputs/day19sample2.txt"));
-    let puzzle_scanners = parse_data(include_str!("../inputs/day19puzzle.txt"));
-
-    test0();
-    test1();
-    testa();
-    test2();
-    let (num, spots) = find_num_beacons(sample2_scanners);
+pub fn max_distance(spots: &[Coord]) -> i32 {
     let mut max_distance = 0;
-    for s in &spots {
-        for t in &spots {
-            let d = (s.x - t.x).abs() + (s.y - t.y).abs() + (s.z - t.z).abs();
+    for s in spots {
+        for t in spots {
+            let d = s.manhattan(*t);
             max_distance = std::cmp::max(d, max_distance);
         }
     }
-    assert_eq!(79, num);
-    assert_eq!(3621, max_distance);
+    return max_distance;
+}
 
-    let (num, spots) = find_num_beacons(puzzle_scanners);
-    let mut max_distance = 0;
-    for s in &spots {
-        for t in &spots {
-            let d = (s.x - t.x).abs() + (s.y - t.y).abs() + (s.z - t.z).abs();
-            max_distance = std::cmp::max(d, max_distance);
+/// How far a scanner sees along each axis.
+const SCANNER_RANGE: i32 = 1000;
+
+/// A point somewhere in the box from `min` to `max`.
+fn generate_coord(rng: &mut Rng, min: Coord, max: Coord) -> Coord {
+    let mut axis = |lo: i32, hi: i32| rng.range(lo as i64, hi as i64) as i32;
+    Coord::new(axis(min.x, max.x), axis(min.y, max.y), axis(min.z, max.z))
+}
+
+/// `size` scanners, each sharing at least 12 beacons with an earlier one and
+/// reporting in its own orientation. Scanners are spread out like the real
+/// ones, since crowded scanners see lookalike beacons that get matched up.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let range = Coord::new(SCANNER_RANGE, SCANNER_RANGE, SCANNER_RANGE);
+    let sees = |scanner: Coord, b: &Coord| (*b - scanner).abs().max_component() <= SCANNER_RANGE;
+    let mut scanners = vec![Coord::ZERO];
+    let mut beacons: Vec<Coord> = vec![];
+    while scanners.len() < size {
+        let parent = *rng.pick(&scanners);
+        let mut offset = [rng.range(-150, 150), rng.range(-150, 150), rng.range(-150, 150)];
+        offset[rng.index(3)] = rng.range(1000, 1200) * if rng.chance(1, 2) { 1 } else { -1 };
+        let scanner = parent + Coord::new(offset[0] as i32, offset[1] as i32, offset[2] as i32);
+        if scanners.iter().any(|s| (*s - scanner).abs().max_component() < SCANNER_RANGE) {
+            continue;
+        }
+        scanners.push(scanner);
+        let min = parent.zip(scanner, std::cmp::max) - range;
+        let max = parent.zip(scanner, std::cmp::min) + range;
+        while beacons.iter().filter(|b| sees(parent, b) && sees(scanner, b)).count() < 12 {
+            let b = generate_coord(rng, min, max);
+            if !beacons.contains(&b) {
+                beacons.push(b);
+            }
+        }
+    }
+    for scanner in &scanners {
+        for _ in 0..15 {
+            let b = generate_coord(rng, *scanner - range, *scanner + range);
+            if !beacons.contains(&b) {
+                beacons.push(b);
+            }
+        }
+    }
+    let mut reports = vec![];
+    for (id, scanner) in scanners.iter().enumerate() {
+        let mut axes = [0, 1, 2];
+        rng.shuffle(&mut axes);
+        let mut signs: Vec<i32> = (0..3).map(|_| if rng.chance(1, 2) { 1 } else { -1 }).collect();
+        let odd = (axes[0] > axes[1]) ^ (axes[0] > axes[2]) ^ (axes[1] > axes[2]);
+        if odd ^ (signs.iter().product::<i32>() < 0) {
+            signs[2] = -signs[2];
         }
+        let mut seen: Vec<Coord> = beacons.iter().filter(|b| sees(*scanner, b)).map(|b| *b - *scanner).collect();
+        rng.shuffle(&mut seen);
+        let mut lines = vec![format!("--- scanner {} ---", id)];
+        for b in seen {
+            let c = [b.x, b.y, b.z];
+            lines.push(format!("{},{},{}", signs[0] * c[axes[0]], signs[1] * c[axes[1]], signs[2] * c[axes[2]]));
+        }
+        reports.push(lines.join("\n"));
+    }
+    return reports.join("\n\n");
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Vec<Scanner>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1(scanners: &Self::Input) -> Answer {
+        find_num_beacons(scanners.clone()).0.into()
+    }
+
+    fn part2(scanners: &Self::Input) -> Answer {
+        max_distance(&find_num_beacons(scanners.clone()).1).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+
+    #[test]
+    fn orients_repeated_scanners() {
+        let repeated = parse_data(&input::named(19, "shouldhaveusedtheseearlier").unwrap()).unwrap();
+        let first = repeated[0].clone();
+        for sc in &repeated[1..] {
+            let matched = get_matching_overlap(first.clone(), sc.clone());
+            let (d, s, rotations) = find_orientation(matched).unwrap();
+            let reoriented = reorient(sc.clone(), d, s, rotations);
+            assert_eq!(reoriented.signals, first.signals);
+        }
+    }
+
+    #[test]
+    fn orients_synthetic_scanners() {
+        let repeated = parse_data(&input::named(19, "syn").unwrap()).unwrap();
+        let first = repeated[0].clone();
+        // first step is 1,1,-1 switched
+        // next step is rotation 2, xz switched
+        // step after that is shift down -1, -1, -1
+
+        let matched = get_matching_overlap(first.clone(), repeated[1].clone());
+        let (d, s, rotations) = find_orientation(matched).unwrap();
+        assert_eq!(rotations, 0);
+        assert_eq!(s, Coord { x: 1, y: 1, z: -1 });
+        assert_eq!(d, Coord { x: 0, y: 0, z: 0 });
+
+        let matched = get_matching_overlap(first.clone(), repeated[2].clone());
+        let (d, s, rotations) = find_orientation(matched).unwrap();
+        assert_eq!(rotations, 2);
+        assert_eq!(s, Coord { x: -1, y: 1, z: 1 });
+        assert_eq!(d, Coord { x: 0, y: 0, z: 0 });
+
+        let matched = get_matching_overlap(first.clone(), repeated[3].clone());
+        let (d, s, rotations) = find_orientation(matched).unwrap();
+        assert_eq!(rotations, 2);
+        assert_eq!(s, Coord { x: -1, y: 1, z: 1 });
+        assert_eq!(d, Coord { x: 1, y: 1, z: -1 });
+    }
+
+    #[test]
+    fn orients_sample_pair() {
+        let sample2_scanners = parse_data(&input::named(19, "sample2").unwrap()).unwrap();
+        let s0 = sample2_scanners[0].clone();
+        let s1 = sample2_scanners[1].clone();
+
+        let matched = get_matching_overlap(s0.clone(), s1.clone());
+        let (d, s, rotations) = find_orientation(matched).unwrap();
+        assert_eq!(
+            d,
+            Coord {
+                x: 68,
+                y: -1246,
+                z: -43,
+            }
+        );
+        assert_eq!(s, Coord { x: -1, y: 1, z: -1 });
+        assert_eq!(rotations, 0);
+        let reoriented_s1 = reorient(s1, d, s, rotations);
+
+        let rm = get_matching_overlap(s0, reoriented_s1);
+        let (rd, rs, _) = find_orientation(rm).unwrap();
+        assert_eq!(rd, Coord { x: 0, y: 0, z: 0 });
+        assert_eq!(rs, Coord { x: 1, y: 1, z: 1 });
+    }
+
+    #[test]
+    fn combines_sample_scanners() {
+        let sample2_scanners = parse_data(&input::named(19, "sample2").unwrap()).unwrap();
+        let mut s0 = sample2_scanners[0].clone();
+        let s1 = sample2_scanners[1].clone();
+        let s2 = sample2_scanners[2].clone();
+        let s3 = sample2_scanners[3].clone();
+        let s4 = sample2_scanners[4].clone();
+
+        //s1
+        let matched = get_matching_overlap(s0.clone(), s1.clone());
+        let (d, s, r) = find_orientation(matched).unwrap();
+
+        assert_eq!(r, 0);
+        assert_eq!(s, Coord { x: -1, y: 1, z: -1 });
+        assert_eq!(
+            d,
+            Coord {
+                x: 68,
+                y: -1246,
+                z: -43
+            }
+        );
+        s0 = combine_scanner(s0, s1, d, s, r);
+
+        //s4
+        let matched = get_matching_overlap(s0.clone(), s4.clone());
+        let (d, s, r) = find_orientation(matched).unwrap();
+
+        assert_eq!(r, 4);
+        assert_eq!(s, Coord { x: 1, y: -1, z: -1 });
+        assert_eq!(
+            d,
+            Coord {
+                x: -20,
+                y: -1133,
+                z: 1061
+            }
+        );
+        s0 = combine_scanner(s0, s4, d, s, r);
+
+        //s2
+        let matched = get_matching_overlap(s0.clone(), s2.clone());
+        let (d, s, r) = find_orientation(matched).unwrap();
+
+        assert_eq!(r, 1);
+        assert_eq!(s, Coord { x: -1, y: 1, z: 1 });
+        assert_eq!(
+            d,
+            Coord {
+                x: 1105,
+                y: -1205,
+                z: 1229
+            }
+        );
+        s0 = combine_scanner(s0, s2, d, s, r);
+
+        //s3
+        let matched = get_matching_overlap(s0.clone(), s3.clone());
+        let (d, s, r) = find_orientation(matched).unwrap();
+
+        assert_eq!(r, 0);
+        assert_eq!(s, Coord { x: -1, y: 1, z: -1 });
+        assert_eq!(
+            d,
+            Coord {
+                x: -92,
+                y: -2380,
+                z: -20
+            }
+        );
+        s0 = combine_scanner(s0, s3, d, s, r);
+        assert_eq!(s0.signals.len(), 79);
+    }
+
+    #[test]
+    fn finds_beacons() {
+        let (num, spots) = find_num_beacons(parse_data(&input::named(19, "sample2").unwrap()).unwrap());
+        assert_eq!(79, num);
+        assert_eq!(3621, max_distance(&spots));
+
+        let (num, spots) = find_num_beacons(parse_data(&input::named(19, "puzzle").unwrap()).unwrap());
+        assert_eq!(451, num);
+        assert_eq!(13184, max_distance(&spots));
     }
-    assert_eq!(451, num);
-    assert_eq!(13184, max_distance);
 }
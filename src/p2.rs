@@ -1,59 +1,240 @@
-fn day_two_input() -> Vec<(String, i32)> {
-    let contents = fs::read_to_string("inputs/day2puzzle1.txt")
-    .expect("Something went wrong reading the file");
-    let lines = contents.lines();
-    let directions = lines.map(|x| {
-        let mut split = x.split(" ");
-        let direction = split.next().unwrap().to_string();
-        let distance = split.next().unwrap().parse::<i32>().unwrap();
-        return (direction, distance)
-    }).collect();
-    return directions;
-}
-
-fn d2p1 () {
-    let directions = day_two_input();
-    println!("{:?}",directions);
-
-    let mut x = 0;
-    let mut y = 0; 
-    for (direction, distance) in directions {
-        println!("{} {}", direction, distance);
-        match direction.as_str() {
-            "forward" => x+= distance, 
-            "down" => y+= distance, 
-            "up" => y-= distance, 
-            _ => unreachable!()
+//! Day 2: Dive! Commands are run through a `Navigation` model: `Depth`, where
+//! `down` and `up` change the depth, for `d2p1`, and `Aim`, where they change
+//! the aim, for `d2p2`. `trace` keeps every position along the way.
+
+use std::fmt;
+
+use generate::Rng;
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Forward(i32),
+    Down(i32),
+    Up(i32),
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Forward(n) => write!(f, "forward {}", n),
+            Command::Down(n) => write!(f, "down {}", n),
+            Command::Up(n) => write!(f, "up {}", n),
+        }
+    }
+}
+
+pub fn parse_data(s: &str) -> Result<Vec<Command>, ParseError> {
+    parser::each_line(s, |c| {
+        let start = c.clone();
+        let command: fn(i32) -> Command = match c.word()? {
+            "forward" => Command::Forward,
+            "down" => Command::Down,
+            "up" => Command::Up,
+            _ => return Err(start.error("`forward`, `down` or `up`")),
         };
-        println!("{} {}\n", x, y);
-    }
-    println!("{}",x*y);
-}
-
-
-fn d2p2 () {
-    let directions = day_two_input();
-    println!("{:?}",directions);
-
-    let mut x = 0;
-    let mut y = 0; 
-    let mut aim = 0;
-    for (direction, distance) in directions {
-        println!("{} {}", direction, distance);
-        match direction.as_str() {
-            "forward" => {
-                x += distance;
-                y += aim*distance;
-            }, 
-            "down" => {
-                aim += distance
-            }, 
-            "up" => {
-                aim -= distance
-            }, 
-            _ => unreachable!()
+        c.expect(" ")?;
+        return Ok(command(c.int::<i32>()?));
+    })
+}
+
+pub fn print_data(commands: &[Command]) -> String {
+    let lines: Vec<String> = commands.iter().map(|c| c.to_string()).collect();
+    return lines.join("\n");
+}
+
+/// Where the submarine is. `aim` stays 0 for models without one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    pub x: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+impl Position {
+    /// The answer to either part.
+    pub fn product(&self) -> i64 {
+        self.x * self.depth
+    }
+}
+
+/// How the submarine moves for each command.
+pub trait Navigation {
+    fn step(&self, at: Position, command: Command) -> Position;
+}
+
+/// `down` and `up` change the depth directly.
+pub struct Depth;
+
+impl Navigation for Depth {
+    fn step(&self, at: Position, command: Command) -> Position {
+        match command {
+            Command::Forward(n) => Position { x: at.x + n as i64, ..at },
+            Command::Down(n) => Position { depth: at.depth + n as i64, ..at },
+            Command::Up(n) => Position { depth: at.depth - n as i64, ..at },
+        }
+    }
+}
+
+/// `down` and `up` change the aim, and going forward dives by the aim.
+pub struct Aim;
+
+impl Navigation for Aim {
+    fn step(&self, at: Position, command: Command) -> Position {
+        match command {
+            Command::Forward(n) => Position {
+                x: at.x + n as i64,
+                depth: at.depth + at.aim * n as i64,
+                ..at
+            },
+            Command::Down(n) => Position { aim: at.aim + n as i64, ..at },
+            Command::Up(n) => Position { aim: at.aim - n as i64, ..at },
+        }
+    }
+}
+
+/// The models by name, for picking one at runtime.
+pub const MODELS: &[(&str, &dyn Navigation)] = &[("depth", &Depth), ("aim", &Aim)];
+
+pub fn find_model(name: &str) -> Option<&'static dyn Navigation> {
+    MODELS.iter().find(|(n, _)| *n == name).map(|(_, m)| *m)
+}
+
+/// Where the submarine ends up after every command.
+pub fn navigate<N: Navigation + ?Sized>(model: &N, commands: &[Command]) -> Position {
+    commands.iter().fold(Position::default(), |at, c| {
+        let next = model.step(at, *c);
+        trace!("{} takes {:?} to {:?}", c, at, next);
+        next
+    })
+}
+
+/// Every position the submarine passes through, starting at the surface, so
+/// there is one more than there are commands.
+pub fn trace<N: Navigation + ?Sized>(model: &N, commands: &[Command]) -> Vec<Position> {
+    let mut at = Position::default();
+    let mut history = vec![at];
+    for c in commands {
+        at = model.step(at, *c);
+        history.push(at);
+    }
+    return history;
+}
+
+/// A trace as CSV, a row per position with the command that led to it, ready
+/// to plot.
+pub fn trace_csv(commands: &[Command], history: &[Position]) -> String {
+    let mut out = String::from("step,command,x,depth,aim\n");
+    for (step, at) in history.iter().enumerate() {
+        let command = if step == 0 { String::new() } else { commands[step - 1].to_string() };
+        out += &format!("{},{},{},{},{}\n", step, command, at.x, at.depth, at.aim);
+    }
+    return out;
+}
+
+/// Where the submarine ends up, as depth times distance, when `down` and `up`
+/// change the depth.
+pub fn d2p1(commands: &[Command]) -> i64 {
+    navigate(&Depth, commands).product()
+}
+
+/// Where the submarine ends up, as depth times distance, when `down` and `up`
+/// change the aim.
+pub fn d2p2(commands: &[Command]) -> i64 {
+    navigate(&Aim, commands).product()
+}
+
+/// `size` commands that never take the submarine above the surface.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut depth = 0;
+    let mut commands = vec![];
+    for _ in 0..size {
+        let n = rng.range(1, 9) as i32;
+        let command = match rng.below(3) {
+            0 => Command::Forward(n),
+            1 => Command::Down(n),
+            _ if depth < n as i64 => Command::Down(n),
+            _ => Command::Up(n),
         };
-        println!("{} {} {}\n", x, y, aim);
+        depth = Depth.step(Position { depth, ..Position::default() }, command).depth;
+        commands.push(command);
+    }
+    return print_data(&commands);
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Vec<Command>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1(commands: &Self::Input) -> Answer {
+        d2p1(commands).into()
+    }
+
+    fn part2(commands: &Self::Input) -> Answer {
+        d2p2(commands).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const SAMPLE: &str = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2";
+
+    #[test]
+    fn navigates_sample() {
+        let commands = parse_data(SAMPLE).unwrap();
+        assert_eq!(d2p1(&commands), 150);
+        assert_eq!(d2p2(&commands), 900);
+        assert_eq!(navigate(find_model("aim").unwrap(), &commands), Position { x: 15, depth: 60, aim: 10 });
+    }
+
+    #[test]
+    fn traces_every_position() {
+        let commands = parse_data(SAMPLE).unwrap();
+        let history = trace(&Aim, &commands);
+        assert_eq!(history.len(), 7);
+        assert_eq!(history[0], Position::default());
+        assert_eq!(history[3], Position { x: 13, depth: 40, aim: 5 });
+        assert_eq!(*history.last().unwrap(), navigate(&Aim, &commands));
+
+        let csv = trace_csv(&commands, &history);
+        assert_eq!(csv.lines().nth(1), Some("0,,0,0,0"));
+        assert_eq!(csv.lines().last(), Some("6,forward 2,15,60,10"));
+    }
+
+    #[test]
+    fn goes_deeper_than_an_i32() {
+        let commands = parse_data(&["down 50000", "forward 50000", "forward 50000"].join("\n")).unwrap();
+        assert_eq!(d2p1(&commands), 5_000_000_000);
+        assert_eq!(d2p2(&commands), 500_000_000_000_000);
+    }
+
+    #[test]
+    fn rejects_unknown_commands() {
+        let e = parse_data("forward 1\nsideways 2").unwrap_err();
+        assert_eq!(e, ParseError::new(2, 1, "`forward`, `down` or `up`"));
+    }
+
+    fn command() -> impl Strategy<Value = Command> {
+        prop_oneof![
+            (0i32..1000).prop_map(Command::Forward),
+            (0i32..1000).prop_map(Command::Down),
+            (0i32..1000).prop_map(Command::Up),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn print_data_round_trips(commands in prop::collection::vec(command(), 1..100)) {
+            prop_assert_eq!(parse_data(&print_data(&commands)).unwrap(), commands);
+        }
     }
-    println!("{}",x*y);
 }
@@ -1,86 +1,79 @@
+//! Day 20: Trench Map. `enhance` applies the image enhancement algorithm to the
+//! `Grid` a number of times and counts the lit pixels.
+
 //use std::collections::{HashMap};
 use rayon::prelude::*;
-use rustc_hash::FxHashMap;
-use std::fmt;
 use std::sync::Mutex;
+use animate::Animation;
+use export::Image;
+use generate::Rng;
+use grid::{Pos, Sparse};
+use parser::{self, Cursor, ParseError};
+use solution::{Answer, Solution};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Grid {
-    values: FxHashMap<(i32, i32), bool>,
-}
+pub type Grid = Sparse<bool>;
 
 pub type ImageEnhanceAlgo = Vec<bool>;
 
-impl fmt::Display for Grid {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut s: String = "".to_string();
-        let mut min_x = 0;
-        let mut min_y = 0;
-        let mut max_x = 1;
-        let mut max_y = 1;
-
-        for ((x, y), _b) in self.values.iter() {
-            min_x = std::cmp::min(min_x, *x);
-            min_y = std::cmp::min(min_y, *y);
-            max_x = std::cmp::max(max_x, *x);
-            max_y = std::cmp::max(max_y, *y);
-        }
+/// Lit pixels as `#`, dark ones as `.` and pixels off the image as `_`.
+pub fn render(g: &Grid) -> String {
+    g.render(|_, b| match b {
+        Some(true) => "#".to_string(),
+        Some(false) => ".".to_string(),
+        None => "_".to_string(),
+    })
+}
 
-        for y in (min_y)..=(max_y) {
-            for x in (min_x)..=(max_x) {
-                if self.values.contains_key(&(x, y)) {
-                    if self.values[&(x, y)] {
-                        s += "#";
-                    } else {
-                        s += ".";
-                    }
-                } else {
-                    s += "_"
-                }
-            }
-            if y != max_y {
-                s += "\n";
-            }
+/// A line of `#` and `.`, as whether each spot is lit.
+fn pixels(c: &mut Cursor) -> Result<Vec<bool>, ParseError> {
+    let mut lit = vec![];
+    while !c.is_empty() {
+        lit.push(c.one_of("#.")? == '#');
+    }
+    Ok(lit)
+}
+
+fn read_grid(lines: Vec<Cursor>) -> Result<Grid, ParseError> {
+    let mut g = Grid::new();
+    for (y, mut c) in lines.into_iter().enumerate() {
+        for (x, b) in pixels(&mut c)?.into_iter().enumerate() {
+            g.insert((x as i32, y as i32), b);
         }
-        write!(f, "{}", s)
     }
+    Ok(g)
 }
 
-pub fn parse_grid(g: &str) -> Grid {
-    let values: FxHashMap<(i32, i32), bool> = g
-        .lines()
-        .enumerate()
-        .map(|(x, l)| {
-            let xt = x;
-            l.chars()
-                .enumerate()
-                .map(move |(y, c)| (y, xt, c == '#'))
-                .collect::<Vec<(usize, usize, bool)>>()
-        })
-        .flatten()
-        .map(|(x, y, b)| ((x as i32, y as i32), b))
-        .collect::<FxHashMap<(i32, i32), bool>>();
-    Grid { values }
+pub fn parse_grid(g: &str) -> Result<Grid, ParseError> {
+    read_grid(parser::lines(g.trim_end()).collect())
 }
-pub fn parse_data(s: &str) -> (Grid, ImageEnhanceAlgo) {
-    let (i, g) = s.split_once("\n\n").unwrap();
-    let algo: ImageEnhanceAlgo = i.chars().map(|x| x == '#').collect::<ImageEnhanceAlgo>();
-    (parse_grid(g), algo)
+
+pub fn parse_data(s: &str) -> Result<(Grid, ImageEnhanceAlgo), ParseError> {
+    let mut blocks = parser::blocks(s).into_iter();
+    let algo: ImageEnhanceAlgo = match blocks.next().unwrap_or_default().as_mut_slice() {
+        [c] => {
+            let start = c.clone();
+            let algo = pixels(c)?;
+            if algo.len() != 512 {
+                return Err(start.error("512 pixels of enhancement algorithm"));
+            }
+            algo
+        }
+        _ => return Err(ParseError::at_end(s, "the enhancement algorithm on one line")),
+    };
+    let g = blocks.next().ok_or_else(|| ParseError::at_end(s, "a blank line and the image"))?;
+    Ok((read_grid(g)?, algo))
 }
 
-pub fn get_number(step: i32, (x, y): (i32, i32), g: &Grid) -> usize {
+pub fn get_number(step: i32, p: Pos, g: &Grid) -> usize {
     let mut n = 0;
     let mut t = 1;
     for dy in &[1, 0, -1] {
         for dx in &[1, 0, -1] {
-            let contains = g.values.contains_key(&(x + dx, y + dy));
-            if contains {
-                let b = g.values[&(x + dx, y + dy)];
-                if b {
-                    n += t;
-                }
-            } else if step % 2 == 0 {
-                n += t;
+            match g.get((p.0 + dx, p.1 + dy)) {
+                Some(true) => n += t,
+                Some(false) => {}
+                None if step % 2 == 0 => n += t,
+                None => {}
             }
             t *= 2;
         }
@@ -88,104 +81,125 @@ pub fn get_number(step: i32, (x, y): (i32, i32), g: &Grid) -> usize {
     n
 }
 
-pub fn step(step: i32, grid: &Grid, algo: &ImageEnhanceAlgo) -> Grid {
-    let mut min_x = 0;
-    let mut min_y = 0;
-    let mut max_x = 0;
-    let mut max_y = 0;
-    for (x, y) in grid.values.keys() {
-        min_x = std::cmp::min(min_x, *x - 1);
-        min_y = std::cmp::min(min_y, *y - 1);
-        max_x = std::cmp::max(max_x, *x + 1);
-        max_y = std::cmp::max(max_y, *y + 1);
-    }
-
-    let mut new_grid = Grid {
-        values: FxHashMap::default(),
-    };
+pub fn step(step: i32, g: &Grid, algo: &ImageEnhanceAlgo) -> Grid {
+    let ((min_x, min_y), (max_x, max_y)) = g.bounds().unwrap_or(((0, 0), (0, 0)));
+    let (min_x, min_y) = (min_x.min(1) - 1, min_y.min(1) - 1);
+    let (max_x, max_y) = (max_x.max(-1) + 1, max_y.max(-1) + 1);
 
-    let m = Mutex::new(new_grid);
+    let m = Mutex::new(Grid::new());
     (min_x..=max_x).into_par_iter().for_each(|x| {
-        let results = (min_y..=max_y).map(|y| ((x, y), algo[get_number(step, (x, y), grid)]));
+        let results = (min_y..=max_y).map(|y| ((x, y), algo[get_number(step, (x, y), g)]));
         let mut l = m.lock().unwrap();
         for (xy, b) in results {
-            l.values.insert(xy, b);
+            l.insert(xy, b);
         }
     });
-    let gg = m.lock().unwrap();
-    gg
+    m.into_inner().unwrap()
 }
 
-pub fn main() {
-    let (sample_grid, sample_algo) = parse_data(include_str!("../inputs/day20sample.txt"));
-    let (puzzle_grid, puzzle_algo) = parse_data(include_str!("../inputs/day20puzzle.txt"));
-
-    let mini = "...\n#..\n.#.";
-    assert_eq!(34, get_number(1, (1, 1), &parse_grid(mini)));
-
-    let sample1 = parse_grid(include_str!("../inputs/day20sample1.txt"));
-    let sample2 = parse_grid(include_str!("../inputs/day20sample2.txt"));
-    let sample3 = parse_grid(include_str!("../inputs/day20sample3.txt"));
-
-    assert_eq!(147, get_number(1, (5, 6), &sample1));
-
-    let mut stepped1 = step(1, &sample1, &sample_algo);
-    stepped1.values = stepped1
-        .values
-        .iter()
-        .filter(|((x, y), _)| *x >= 0 && *y >= 0 && *x <= 14 && *y <= 14)
-        .map(|((x, y), b)| ((*x, *y), *b))
-        .collect::<FxHashMap<(i32, i32), bool>>();
-
-    assert_eq!(stepped1, sample2);
-
-    let mut stepped2 = step(1, &sample2, &sample_algo);
-    stepped2.values = stepped2
-        .values
-        .iter()
-        .filter(|((x, y), _)| *x >= 0 && *y >= 0 && *x <= 14 && *y <= 14)
-        .map(|((x, y), b)| ((*x, *y), *b))
-        .collect::<FxHashMap<(i32, i32), bool>>();
-
-    for (xy, b) in &stepped2.values {
-        if sample3.values.contains_key(xy) {
-            if sample3.values[xy] != *b {
-                println!("Different values: {:?} {}", xy, b);
-            }
-        } else {
-            println!("No key {:?}", xy);
+/// Runs `n` enhancement steps and counts the lit pixels.
+pub fn enhance(grid: &Grid, algo: &ImageEnhanceAlgo, n: i32) -> usize {
+    return enhanced(grid, algo, n).values().filter(|b| **b).count();
+}
+
+/// The image after `n` enhancement steps. When the algorithm lights empty
+/// space the infinite background flips every step, so the step number has to
+/// be passed through.
+pub fn enhanced(grid: &Grid, algo: &ImageEnhanceAlgo, n: i32) -> Grid {
+    let mut g = grid.clone();
+    for i in 1..=n {
+        let s = if algo[0] { i } else { 1 };
+        g = step(s, &g, algo);
+    }
+    return g;
+}
+
+/// The image after the 50 steps of part 2, lit pixels in colour 1.
+pub fn image(input: &str) -> Result<Image, ParseError> {
+    let (grid, algo) = parse_data(input)?;
+    let g = enhanced(&grid, &algo, 50);
+    Ok(Image::from_sparse(&g, |b| (b == Some(&true)) as usize))
+}
+
+/// Enhances the image for the 50 steps of part 2.
+pub fn animate(input: &str, anim: &mut Animation) -> Result<(), ParseError> {
+    let (mut g, algo) = parse_data(input)?;
+    for i in 0..=anim.last(50) {
+        if i > 0 {
+            let s = if algo[0] { i as i32 } else { 1 };
+            g = step(s, &g, &algo);
         }
+        anim.frame(i, || render(&g));
+    }
+    Ok(())
+}
+
+/// An algorithm that flips the infinite background every step, like the
+/// real one does, and a `size` square image.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let pixel = |rng: &mut Rng| if rng.chance(1, 2) { '#' } else { '.' };
+    let mut algo: Vec<char> = (0..512).map(|_| pixel(rng)).collect();
+    algo[0] = '#';
+    algo[511] = '.';
+    let rows: Vec<String> = (0..size).map(|_| (0..size).map(|_| pixel(rng)).collect()).collect();
+    return format!("{}\n\n{}", algo.into_iter().collect::<String>(), rows.join("\n"));
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = (Grid, ImageEnhanceAlgo);
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1((grid, algo): &Self::Input) -> Answer {
+        enhance(grid, algo, 2).into()
     }
-    assert_eq!(stepped2, sample3);
-
-    let mut g = sample_grid;
-    for i in 1..=51 {
-        println!(
-            "Step {} with {} lit",
-            i,
-            g.values.iter().filter(|(_, b)| { **b }).count()
-        );
-        //println!("{}\n", g);
-        g = step(1, &g, &sample_algo);
+
+    fn part2((grid, algo): &Self::Input) -> Answer {
+        enhance(grid, algo, 50).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+
+    /// The part of a stepped grid the sample pictures show.
+    fn clipped(g: &Grid) -> Grid {
+        g.iter()
+            .filter(|((x, y), _)| *x >= 0 && *y >= 0 && *x <= 14 && *y <= 14)
+            .map(|(p, b)| (p, *b))
+            .collect()
+    }
+
+    #[test]
+    fn reads_numbers() {
+        let mini = "...\n#..\n.#.";
+        assert_eq!(34, get_number(1, (1, 1), &parse_grid(mini).unwrap()));
+
+        let sample1 = parse_grid(&input::named(20, "sample1").unwrap()).unwrap();
+        assert_eq!(147, get_number(1, (5, 6), &sample1));
+    }
+
+    #[test]
+    fn steps_sample() {
+        let (_, sample_algo) = parse_data(&input::named(20, "sample").unwrap()).unwrap();
+        let sample1 = parse_grid(&input::named(20, "sample1").unwrap()).unwrap();
+        let sample2 = parse_grid(&input::named(20, "sample2").unwrap()).unwrap();
+        let sample3 = parse_grid(&input::named(20, "sample3").unwrap()).unwrap();
+
+        assert_eq!(clipped(&step(1, &sample1, &sample_algo)), sample2);
+        assert_eq!(clipped(&step(1, &sample2, &sample_algo)), sample3);
     }
 
-    // let mut g = sample_grid.clone();
-    // let mut algo = sample_algo.clone();
-    // algo[0] = true;
-    // for i in 1..4 {
-    //     println!("Step {} with {} lit", i, g.values.len());
-    //     println!("{}\n", g);
-    //     g = step(i, g, &sample_algo);
-    // }
-
-    let mut g = puzzle_grid;
-    for i in 1..=51 {
-        println!(
-            "Step {} with {} lit",
-            i,
-            g.values.iter().filter(|(_, b)| { **b }).count()
-        );
-        //println!("{}\n", g);
-        g = step(i, &g, &puzzle_algo);
+    #[test]
+    fn enhances_sample() {
+        let (sample_grid, sample_algo) = parse_data(&input::named(20, "sample").unwrap()).unwrap();
+        assert_eq!(enhance(&sample_grid, &sample_algo, 2), 35);
+        assert_eq!(enhance(&sample_grid, &sample_algo, 50), 3351);
     }
 }
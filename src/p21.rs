@@ -1,12 +1,18 @@
-use cached::proc_macro::cached;
+//! Day 21: Dirac Dice. `part1` plays with the deterministic die and
+//! `count_num_wins` counts the universes each player wins in.
+
 use std::cmp::Ordering;
+use generate::Rng;
+use memo::Memo;
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
 
 #[derive(Hash, Clone, Debug, PartialEq, Eq, Copy)]
 pub struct Game {
-    p1_place: u64,
-    p1_score: u64,
-    p2_place: u64,
-    p2_score: u64,
+    pub p1_place: u64,
+    pub p1_score: u64,
+    pub p2_place: u64,
+    pub p2_score: u64,
 }
 
 impl Ord for Game {
@@ -40,6 +46,7 @@ pub fn advance(mut g: Game, is_player_ones_turn: bool, n: u64) -> Game {
     g
 }
 
+/// The losing score times the number of rolls with the deterministic die.
 pub fn part1(a: u64, b: u64) -> u64 {
     let mut g = Game {
         p1_place: a,
@@ -75,23 +82,26 @@ pub fn part1(a: u64, b: u64) -> u64 {
             break;
         }
     }
-    dbg!(&g);
-    dbg!(dice);
-    dbg!(rolls);
+    debug!("{:?} after {} rolls, dice at {}", g, rolls, dice);
     std::cmp::min(g.p1_score, g.p2_score) * rolls
 }
 
-#[cached]
-pub fn count_num_wins(g: Game, is_player_ones_turn: bool) -> (u64, u64) {
+/// How many universes each player wins in from `g`, remembering the counts
+/// for every (game, whose turn) in `memo`.
+pub fn count_num_wins(g: Game, is_player_ones_turn: bool, memo: &mut Memo<(Game, bool), (u64, u64)>) -> (u64, u64) {
+    memo.get_or_insert_with((g, is_player_ones_turn), |memo| count_wins_from(g, is_player_ones_turn, memo))
+}
+
+fn count_wins_from(g: Game, is_player_ones_turn: bool, memo: &mut Memo<(Game, bool), (u64, u64)>) -> (u64, u64) {
     if g.p1_score >= 21 {
-        println!(
+        trace!(
             "({:2} {:2}) ({:2} {:2}) {} {}",
             g.p1_score, g.p1_place, g.p2_score, g.p2_place, 1, 0
         );
         return (1, 0);
     }
     if g.p2_score >= 21 {
-        println!(
+        trace!(
             "({:2} {:2}) ({:2} {:2}) {} {}",
             g.p1_score, g.p1_place, g.p2_score, g.p2_place, 0, 1
         );
@@ -103,20 +113,21 @@ pub fn count_num_wins(g: Game, is_player_ones_turn: bool) -> (u64, u64) {
         for j in 1..=3 {
             for k in 1..=3 {
                 let next = advance(g, is_player_ones_turn, i + j + k);
-                let (n1, n2) = count_num_wins(next, !is_player_ones_turn);
+                let (n1, n2) = count_num_wins(next, !is_player_ones_turn, memo);
                 p1_wins += n1;
                 p2_wins += n2;
             }
         }
     }
 
-    println!(
+    trace!(
         "({:2} {:2}) ({:2} {:2}) {} {}",
         g.p1_score, g.p1_place, g.p2_score, g.p2_place, p1_wins, p2_wins
     );
     return (p1_wins, p2_wins);
 }
 
+/// How many universes each player wins in with the Dirac die.
 pub fn part2(a: u64, b: u64) -> (u64, u64) {
     let g = Game {
         p1_place: a,
@@ -124,21 +135,85 @@ pub fn part2(a: u64, b: u64) -> (u64, u64) {
         p2_place: b,
         p2_score: 0,
     };
-    return count_num_wins(g, true);
+    let mut memo = Memo::new();
+    let wins = count_num_wins(g, true, &mut memo);
+    debug!("memo: {}", memo.stats());
+    return wins;
+}
+
+/// `Player 1 starting position: 4` and the same for player 2.
+pub fn parse_data(s: &str) -> Result<(u64, u64), ParseError> {
+    let mut positions = [0; 2];
+    let mut lines = parser::lines(s.trim_end());
+    for (i, p) in positions.iter_mut().enumerate() {
+        let mut c = lines.next().ok_or_else(|| ParseError::at_end(s, "a starting position"))?;
+        c.expect(&format!("Player {} starting position: ", i + 1))?;
+        *p = c.int::<u64>()?;
+        c.end()?;
+    }
+    if let Some(c) = lines.next() {
+        return Err(c.error("end of input"));
+    }
+    return Ok((positions[0], positions[1]));
+}
+
+/// Two random starting positions. The size is ignored.
+pub fn generate(rng: &mut Rng, _size: usize) -> String {
+    format!(
+        "Player 1 starting position: {}\nPlayer 2 starting position: {}",
+        rng.range(1, 10),
+        rng.range(1, 10)
+    )
 }
 
-pub fn main() {
-    //assert_eq!(part1(4, 8), 739785);
-    //assert_eq!(part1(5, 10), 711480);
-    let a: u64 = 444356092776315;
-    let b: u64 = 341960390180808;
-    assert_eq!(part2(4, 8), (a, b));
-    let a: u64 = 0;
-    let b: u64 = 0;
-    assert_eq!(part2(5, 10), (a, b));
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = (u64, u64);
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1(&(a, b): &Self::Input) -> Answer {
+        part1(a, b).into()
+    }
+
+    fn part2(&(a, b): &Self::Input) -> Answer {
+        let (wins1, wins2) = part2(a, b);
+        std::cmp::max(wins1, wins2).into()
+    }
 }
 
 // number of times seen
 // add on the multiple
 //3 4 5 6 7 8 9
 //1 3 6 7 6 3 1
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plays_deterministic_dice() {
+        assert_eq!(part1(4, 8), 739785);
+        assert_eq!(part1(5, 10), 711480);
+    }
+
+    #[test]
+    fn counts_dirac_wins() {
+        assert_eq!(part2(4, 8), (444356092776315, 341960390180808));
+        assert_eq!(part2(5, 10), (265845890886828, 261020999052381));
+    }
+
+    #[test]
+    fn counts_each_game_afresh() {
+        let first = part2(4, 8);
+        let swapped = part2(8, 4);
+        assert_ne!(swapped, first);
+        assert_ne!(swapped, (first.1, first.0));
+        let g = Game { p1_place: 8, p1_score: 0, p2_place: 4, p2_score: 0 };
+        assert_eq!(count_num_wins(g, true, &mut Memo::new()), swapped);
+        assert_eq!(part2(4, 8), first);
+    }
+}
@@ -1,55 +1,62 @@
-use colored::Colorize;
-const EXAMPLE: &str = "on x=10..12,y=10..12,z=10..12
-on x=11..13,y=11..13,z=11..13
-off x=9..11,y=9..11,z=9..11
-on x=10..10,y=10..10,z=10..10";
-
-pub fn pdd(s: &str) -> (i64, i64) {
-    let (l, r) = s[2..].split_once("..").unwrap();
-    (l.parse::<i64>().unwrap(), r.parse::<i64>().unwrap())
+//! Day 22 again, by coordinate compression: every `Instruction` marks cells of
+//! a grid cut along the `Compression`, and `count_cubes` adds up their volumes.
+
+use geometry::{Cuboid, Point3};
+use parser::{self, Cursor, ParseError};
+use solution::{Answer, Solution};
+
+/// `x=10..12`, with `axis` in place of `x`.
+pub fn pdd(c: &mut Cursor, axis: &str) -> Result<(i64, i64), ParseError> {
+    c.expect(axis)?;
+    c.expect("=")?;
+    let l = c.int::<i64>()?;
+    c.expect("..")?;
+    let r = c.int::<i64>()?;
+    Ok((l, r))
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct Region {
-    x: (i64, i64),
-    y: (i64, i64),
-    z: (i64, i64),
+/// `on x=10..12,y=10..12,z=10..12`
+pub fn parse_instruction(c: &mut Cursor) -> Result<Instruction, ParseError> {
+    let on = if c.eat("on ") {
+        true
+    } else if c.eat("off ") {
+        false
+    } else {
+        return Err(c.error("`on` or `off`"));
+    };
+    let x = pdd(c, "x")?;
+    c.expect(",")?;
+    let y = pdd(c, "y")?;
+    c.expect(",")?;
+    let z = pdd(c, "z")?;
+    let region = Region {
+        min: Point3::new(x.0, y.0, z.0),
+        max: Point3::new(x.1, y.1, z.1),
+    };
+    Ok(Instruction { on, region })
 }
 
+pub type Region = Cuboid<i64>;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Instruction {
-    on: bool,
-    region: Region,
+    pub on: bool,
+    pub region: Region,
 }
 
 #[derive(Debug, Clone)]
 pub struct Compression {
-    x: Vec<i64>,
-    y: Vec<i64>,
-    z: Vec<i64>,
+    pub x: Vec<i64>,
+    pub y: Vec<i64>,
+    pub z: Vec<i64>,
 }
 
-pub fn parse_data(s: &str) -> (Vec<Instruction>, Compression) {
-    let data = s
-        .lines()
-        .map(|l| {
-            let (toggle, coords) = l.split_once(" ").unwrap();
-            let on = toggle == "on";
-            let (xs, yzs) = coords.split_once(",").unwrap();
-            let (ys, zs) = yzs.split_once(",").unwrap();
-            let x = pdd(xs);
-            let y = pdd(ys);
-            let z = pdd(zs);
-            Instruction {
-                on,
-                region: Region { x, y, z },
-            }
-        })
-        .collect::<Vec<Instruction>>();
+pub fn parse_data(s: &str) -> Result<(Vec<Instruction>, Compression), ParseError> {
+    let data = parser::each_line(s, parse_instruction)?;
     let mut x_list = data
         .iter()
         .flat_map(|int| {
-            let (a, b) = int.region.x;
+            let (a, b) = (int.region.min.x, int.region.max.x);
             return vec![a, b, a+1, b+1];
         })
         .collect::<Vec<i64>>();
@@ -59,7 +66,7 @@ pub fn parse_data(s: &str) -> (Vec<Instruction>, Compression) {
     let mut y_list = data
         .iter()
         .flat_map(|int| {
-            let (a, b) = int.region.y;
+            let (a, b) = (int.region.min.y, int.region.max.y);
             return vec![a, b, a+1, b+1];
         })
         .collect::<Vec<i64>>();
@@ -69,7 +76,7 @@ pub fn parse_data(s: &str) -> (Vec<Instruction>, Compression) {
     let mut z_list = data
         .iter()
         .flat_map(|int| {
-            let (a, b) = int.region.z;
+            let (a, b) = (int.region.min.z, int.region.max.z);
             return vec![a, b, a+1, b+1];
         })
         .collect::<Vec<i64>>();
@@ -80,27 +87,27 @@ pub fn parse_data(s: &str) -> (Vec<Instruction>, Compression) {
     // }
     let mut instructions: Vec<Instruction> = vec![];
     for old_int in data {
-        let mut int = old_int.clone();
+        let mut int = old_int;
 
-        int.region.x.0 = x_list.iter().position(|v| *v == int.region.x.0).unwrap() as i64;
-        int.region.x.1 = x_list.iter().position(|v| *v == int.region.x.1).unwrap() as i64;
+        int.region.min.x = x_list.iter().position(|v| *v == int.region.min.x).unwrap() as i64;
+        int.region.max.x = x_list.iter().position(|v| *v == int.region.max.x).unwrap() as i64;
 
-        int.region.y.0 = y_list.iter().position(|v| *v == int.region.y.0).unwrap() as i64;
-        int.region.y.1 = y_list.iter().position(|v| *v == int.region.y.1).unwrap() as i64;
+        int.region.min.y = y_list.iter().position(|v| *v == int.region.min.y).unwrap() as i64;
+        int.region.max.y = y_list.iter().position(|v| *v == int.region.max.y).unwrap() as i64;
 
-        int.region.z.0 = z_list.iter().position(|v| *v == int.region.z.0).unwrap() as i64;
-        int.region.z.1 = z_list.iter().position(|v| *v == int.region.z.1).unwrap() as i64;
+        int.region.min.z = z_list.iter().position(|v| *v == int.region.min.z).unwrap() as i64;
+        int.region.max.z = z_list.iter().position(|v| *v == int.region.max.z).unwrap() as i64;
 
         instructions.push(int);
     }
-    (
+    Ok((
         instructions,
         Compression {
             x: x_list,
             y: y_list,
             z: z_list,
         },
-    )
+    ))
 }
 
 pub fn clamp(l: i64, n: i64, r: i64) -> i64 {
@@ -113,16 +120,32 @@ pub fn clamp(l: i64, n: i64, r: i64) -> i64 {
     }
 }
 
+/// Width of the compressed cell `i` along one axis, only counting the part
+/// inside `-bound..=bound` when there is one.
+pub fn span(c: &[i64], i: usize, bound: Option<i64>) -> i64 {
+    match bound {
+        Some(b) => std::cmp::max(0, std::cmp::min(c[i + 1], b + 1) - std::cmp::max(c[i], -b)),
+        None => c[i + 1] - c[i],
+    }
+}
+
 pub fn count_cubes(ints: Vec<Instruction>, compression: Compression) -> i64 {
+    return count_cubes_within(ints, compression, None);
+}
+
+pub fn count_cubes_within(ints: Vec<Instruction>, compression: Compression, bound: Option<i64>) -> i64 {
     //println!("{:?}", &compression);
+    if ints.is_empty() {
+        return 0;
+    }
     let mut cubes =
         vec![vec![vec![false; compression.z.len()]; compression.y.len()]; compression.x.len()];
 
     for int in ints {
         //println!("Doing {:?}", int);
-        for x in int.region.x.0..=int.region.x.1 {
-            for y in int.region.y.0..=int.region.y.1 {
-                for z in int.region.z.0..=int.region.z.1 {
+        for x in int.region.min.x..=int.region.max.x {
+            for y in int.region.min.y..=int.region.max.y {
+                for z in int.region.min.z..=int.region.max.z {
                     cubes[x as usize][y as usize][z as usize] = int.on;
                 }
             }
@@ -132,38 +155,21 @@ pub fn count_cubes(ints: Vec<Instruction>, compression: Compression) -> i64 {
     let mut total = 0;
     let mut xd_total = 0;
     for (xi, yz) in cubes.iter().enumerate().take(cubes.iter().len() - 1) {
-        let x1 = clamp(-50, compression.x[xi + 1], 50);
-        let x2 = clamp(-50, compression.x[xi], 50);
-
-        let x1 = compression.x[xi + 1];
-        let x2 = compression.x[xi];
-
-        let xd = x1 - x2;
+        let xd = span(&compression.x, xi, bound);
         if yz.iter().any(|z| z.iter().any(|on| *on)) {
             //println!("x@{}: ({}, {}) = {}", xi, x2, x1, xd);
             xd_total += xd;
         }
         let mut yd_total = 0;
         for (yi, z) in yz.iter().enumerate().take(yz.iter().len() - 1) {
-            let y1 = clamp(-50, compression.y[yi + 1], 50);
-            let y2 = clamp(-50, compression.y[yi], 50);
-
-            let y1 = compression.y[yi + 1];
-            let y2 = compression.y[yi];
-            let yd = y1 - y2;
+            let yd = span(&compression.y, yi, bound);
             if z.iter().any(|on| *on) {
                 //println!(" . y@{}: ({}, {}) = {}", yi, y2, y1, yd);
                 yd_total += yd;
             }
             let mut zd_total = 0;
             for (zi, on) in z.iter().enumerate().take(z.iter().len() - 1) {
-                let z1 = clamp(-50, compression.z[zi + 1], 50);
-                let z2 = clamp(-50, compression.z[zi], 50);
-
-                let z1 = compression.z[zi + 1];
-                let z2 = compression.z[zi];
-
-                let zd = z1 - z2;
+                let zd = span(&compression.z, zi, bound);
                 if *on {
                     zd_total += zd;
                     let product = xd * yd * zd;
@@ -186,37 +192,54 @@ pub fn count_cubes(ints: Vec<Instruction>, compression: Compression) -> i64 {
     return total;
 }
 
-pub fn main() {
-    // let (instructions, compressions) = parse_data(EXAMPLE);
-    // let sizes = vec![27, 46, 38, 39];
-    // for i in 0..sizes.len() {
-    //     assert_eq!(
-    //         sizes[i],
-    //         count_cubes(instructions[0..i + 1].to_vec(), compressions.clone())
-    //     );
-    // }
+pub struct Solver;
 
-    // let sizes = vec![
-    //     139590, 210918, 225476, 328328, 387734, 420416, 436132, 478727, 494759, 494804, 492164,
-    //     534936, 534936, 567192, 567150, 592167, 588567, 592902, 590029, 590784, 590784,
-    // ];
-    // for i in 11..sizes.len() {
-    //         dbg!(i);
-    //         let (instructions, compressions) =
-    //             parse_data(include_str!("../inputs/day22sample.txt"));
-    //         dbg!(
-    //             count_cubes(instructions[0..i + 1].to_vec(), compressions.clone()),
-    //             sizes[i]
-    //         );
-    //         //println!("\n")
-    // }
+impl Solution for Solver {
+    type Input = (Vec<Instruction>, Compression);
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
 
-    //let (instructions, compressions) = parse_data(include_str!("../inputs/day22puzzle.txt"));
-    //assert_eq!(543306, count_cubes(instructions, compressions));
+    fn part1((ints, compression): &Self::Input) -> Answer {
+        count_cubes_within(ints.clone(), compression.clone(), Some(50)).into()
+    }
 
-    //let (instructions, compressions) =parse_data(include_str!("../inputs/day22bsample.txt"));
-    //assert_eq!(2758514936282235, count_cubes(instructions, compressions));
+    fn part2((ints, compression): &Self::Input) -> Answer {
+        count_cubes(ints.clone(), compression.clone()).into()
+    }
+}
 
-    let (instructions, compressions) = parse_data(include_str!("../inputs/day22puzzle.txt"));
-    assert_eq!(543306, count_cubes(instructions, compressions));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+
+    #[test]
+    fn counts_example_steps() {
+        let (instructions, compression) = parse_data(&input::named(22, "example").unwrap()).unwrap();
+        let sizes = [27, 46, 38, 39];
+        for (i, size) in sizes.iter().enumerate() {
+            assert_eq!(*size, count_cubes(instructions[0..i + 1].to_vec(), compression.clone()));
+        }
+    }
+
+    #[test]
+    fn counts_within_bounds() {
+        let (instructions, compression) = parse_data(&input::named(22, "sample").unwrap()).unwrap();
+        assert_eq!(590784, count_cubes_within(instructions, compression, Some(50)));
+    }
+
+    #[test]
+    fn counts_bsample() {
+        let (instructions, compression) = parse_data(&input::named(22, "bsample").unwrap()).unwrap();
+        assert_eq!(2758514936282235, count_cubes(instructions, compression));
+    }
+
+    #[test]
+    fn counts_nothing_without_steps() {
+        let input = Solver::parse("").unwrap();
+        assert_eq!(Solver::part1(&input), 0i64.into());
+        assert_eq!(Solver::part2(&input), 0i64.into());
+    }
 }
@@ -1,35 +1,37 @@
-use cached::proc_macro::cached;
-use maplit::hashmap;
-use std::collections::HashMap;
+//! Day 23: Amphipod. A `Game` is the hallway and the rooms, and
+//! `find_lowest_actions` searches for the cheapest way to sort them.
+
 use std::fmt;
+use generate::Rng;
+use memo::Memo;
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Game {
-    hallway: [char; 11],
-    rooms: [[char; 2]; 4],
-    cost: i32,
+    pub hallway: [char; 11],
+    pub rooms: [Vec<char>; 4],
+    pub cost: i32,
 }
 
 impl fmt::Display for Game {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut s: String = "".to_string();
-        s += &"#############\n".to_string();
+        s += "#############\n";
         s += &format!("#{}#\n", self.hallway.iter().collect::<String>());
-        s += &format!(
-            "###{}#{}#{}#{}###\n",
-            self.rooms[ti(Letter::A)][0],
-            self.rooms[ti(Letter::B)][0],
-            self.rooms[ti(Letter::C)][0],
-            self.rooms[ti(Letter::D)][0]
-        );
-        s += &format!(
-            "  #{}#{}#{}#{}#  \n",
-            self.rooms[ti(Letter::A)][1],
-            self.rooms[ti(Letter::B)][1],
-            self.rooms[ti(Letter::C)][1],
-            self.rooms[ti(Letter::D)][1]
-        );
-        s += &"  #########\n".to_string();
+        for depth in 0..self.rooms[0].len() {
+            let (l, r) = if depth == 0 { ("###", "###") } else { ("  #", "#  ") };
+            s += &format!(
+                "{}{}#{}#{}#{}{}\n",
+                l,
+                self.rooms[ti(Letter::A)][depth],
+                self.rooms[ti(Letter::B)][depth],
+                self.rooms[ti(Letter::C)][depth],
+                self.rooms[ti(Letter::D)][depth],
+                r
+            );
+        }
+        s += "  #########\n";
         s += &format!("Cost: {}", self.cost);
         write!(f, "{}", s)
     }
@@ -51,14 +53,14 @@ pub enum Places {
 
 #[derive(Clone, Debug, Copy)]
 pub struct Location {
-    position: usize,
-    place: Places,
+    pub position: usize,
+    pub place: Places,
 }
 
 #[derive(Clone, Debug, Copy)]
 pub struct Action {
-    start: Location,
-    stop: Location,
+    pub start: Location,
+    pub stop: Location,
 }
 
 pub fn cost_of_letter(c: char) -> i32 {
@@ -89,6 +91,15 @@ pub fn location(l: Letter) -> i32 {
     }
 }
 
+pub fn letter_to_char(l: Letter) -> char {
+    match l {
+        Letter::A => 'A',
+        Letter::B => 'B',
+        Letter::C => 'C',
+        Letter::D => 'D',
+    }
+}
+
 pub fn char_to_letter(c: char) -> Letter {
     match c {
         'A' => Letter::A,
@@ -101,8 +112,8 @@ pub fn char_to_letter(c: char) -> Letter {
 
 pub fn move_thing(g: &Game, Action { start, stop }: &Action) -> Game {
     let mut new_game = g.clone();
-    let mut old_char = '.';
-    match start.clone() {
+    let old_char;
+    match *start {
         Location {
             place: Places::Room(letter),
             position: x,
@@ -119,7 +130,7 @@ pub fn move_thing(g: &Game, Action { start, stop }: &Action) -> Game {
         }
     }
 
-    match stop.clone() {
+    match *stop {
         Location {
             place: Places::Room(letter),
             position: x,
@@ -146,7 +157,7 @@ pub fn move_thing(g: &Game, Action { start, stop }: &Action) -> Game {
             },
         ) => {
             let distance =
-                ((location(*room1) - location(*room2)) as i32).abs() + *p1 as i32 + *p2 as i32 + 2;
+                (location(*room1) - location(*room2)).abs() + *p1 as i32 + *p2 as i32 + 2;
             let cost = distance * cost_of_letter(old_char);
             new_game.cost += cost;
         }
@@ -198,18 +209,65 @@ pub fn move_thing(g: &Game, Action { start, stop }: &Action) -> Game {
 }
 
 pub fn is_done(g: &Game) -> bool {
+    let depth = g.rooms[0].len();
     let done = Game {
         hallway: ['.'; 11],
-        rooms: [['A', 'A'], ['B', 'B'], ['C', 'C'], ['D', 'D']],
+        rooms: [vec!['A'; depth], vec!['B'; depth], vec!['C'; depth], vec!['D'; depth]],
         cost: 0,
     };
     done.hallway == g.hallway && done.rooms == g.rooms
 }
 
+pub fn parse_data(s: &str) -> Result<Game, ParseError> {
+    let mut lines = parser::lines(s.trim_end());
+    let mut top = lines.next().ok_or_else(|| ParseError::at_end(s, "the burrow"))?;
+    top.expect("#############")?;
+    top.end()?;
+
+    let mut c = lines.next().ok_or_else(|| ParseError::at_end(s, "the hallway"))?;
+    let mut hallway = ['.'; 11];
+    c.expect("#")?;
+    for spot in hallway.iter_mut() {
+        *spot = c.one_of(".ABCD")?;
+    }
+    c.expect("#")?;
+    c.end()?;
+
+    let mut rooms: [Vec<char>; 4] = [vec![], vec![], vec![], vec![]];
+    for mut c in lines {
+        if c.rest().trim().chars().all(|x| x == '#') {
+            break;
+        }
+        c.take_while(|x| x == ' ' || x == '#');
+        for room in rooms.iter_mut() {
+            room.push(c.one_of(".ABCD")?);
+            c.expect("#")?;
+        }
+        c.take_while(|x| x == '#');
+        c.end()?;
+    }
+    if rooms[0].is_empty() {
+        return Err(ParseError::at_end(s, "at least one row of rooms"));
+    }
+    Ok(Game { hallway, rooms, cost: 0 })
+}
+
+/// Part two unfolds the burrow, putting two extra rows between the ones we were
+/// given.
+pub fn unfold(g: &Game) -> Game {
+    let mut unfolded = g.clone();
+    let extra = [['D', 'D'], ['C', 'B'], ['B', 'A'], ['A', 'C']];
+    for (room, chars) in unfolded.rooms.iter_mut().zip(extra.iter()) {
+        room.insert(1, chars[1]);
+        room.insert(1, chars[0]);
+    }
+    unfolded
+}
+
 pub fn example_test() {
     let mut example = Game {
         hallway: ['.'; 11],
-        rooms: [['B', 'A'], ['C', 'D'], ['B', 'C'], ['D', 'A']],
+        rooms: [vec!['B', 'A'], vec!['C', 'D'], vec!['B', 'C'], vec!['D', 'A']],
         cost: 0,
     };
 
@@ -333,9 +391,7 @@ pub fn path_clear(g: &Game, act: &Action) -> bool {
             place: Places::Hallway,
         } => {
             if g.hallway[x] == '.' {
-                if let (Places::Room(_), Places::Room(_)) = (start.place,stop.place) {
-                println!("That doesn't exist");
-                }
+                trace!("{:?}: nobody there to move", act);
                 return false;
             }
         }
@@ -344,9 +400,7 @@ pub fn path_clear(g: &Game, act: &Action) -> bool {
             place: Places::Room(letter),
         } => {
             if g.rooms[ti(letter)][x] == '.' {
-                if let (Places::Room(_), Places::Room(_)) = (start.place,stop.place) {
-                println!("That doesn't exist");
-                }
+                trace!("{:?}: nobody there to move", act);
                 return false;
             }
         }
@@ -401,16 +455,11 @@ pub fn path_clear(g: &Game, act: &Action) -> bool {
     };
 
     if start_hallway > end_hallway {
-        let tmp = start_hallway;
-        start_hallway = end_hallway;
-        end_hallway = tmp;
+        std::mem::swap(&mut start_hallway, &mut end_hallway);
     }
     for i in start_hallway + 1..end_hallway {
         if g.hallway[i as usize] != '.' {
-            if let (Places::Room(_), Places::Room(_)) = (start.place,stop.place) {
-                dbg!(act);
-            println!("Somebody in the way");
-            }
+            trace!("{:?}: somebody in the way", act);
             return false;
         }
     }
@@ -433,81 +482,84 @@ pub fn path_clear(g: &Game, act: &Action) -> bool {
             } => g.rooms[ti(l)][x],
         };
         if char_to_letter(amigo_type) != letter {
-            if let (Places::Room(_), Places::Room(_)) = (start.place,stop.place) {
-
-                dbg!(act);
-            println!("Different letter already in room");
-            }
+            trace!("{:?}: not their room", act);
             return false;
         }
     }
     return true;
 }
 
+/// The position of the amphipod at the top of a room, if there is one.
+pub fn room_top(g: &Game, room: Letter) -> Option<usize> {
+    g.rooms[ti(room)].iter().position(|c| *c != '.')
+}
+
+/// Amphipods only leave a room when it still holds someone who belongs elsewhere.
+pub fn room_has_strangers(g: &Game, room: Letter) -> bool {
+    let own = letter_to_char(room);
+    g.rooms[ti(room)].iter().any(|c| *c != '.' && *c != own)
+}
+
+/// The deepest free spot of a room, as long as only its own kind is in there.
+pub fn room_open(g: &Game, room: Letter) -> Option<usize> {
+    if room_has_strangers(g, room) {
+        return None;
+    }
+    g.rooms[ti(room)].iter().rposition(|c| *c == '.')
+}
+
 pub fn generate_all_possible_actions(g: &Game) -> Vec<Action> {
     let mut actions: Vec<Action> = vec![];
-    // assuming unobstructed;
-    //every top element of room can move out of hallway;
-    //
     let rooms = &[Letter::A, Letter::B, Letter::C, Letter::D];
     let hallway_spots = &[0, 1, 3, 5, 7, 9, 10];
-    //move top elements into hallway spots;
-    for room in rooms {
-        for spot in hallway_spots {
-            let top = if g.rooms[ti(*room)][0] != '.' {
-                0
-            } else if g.rooms[ti(*room)][1] != '.' {
-                1
-            } else {
-                continue;
-            };
-            let act = Action {
-                start: Location {
-                    position: top,
-                    place: Places::Room(*room),
-                },
-                stop: Location {
-                    position: *spot,
-                    place: Places::Hallway,
-                },
-            };
-            if path_clear(g, &act) {
-                actions.push(act);
-            }
-        }
-    }
-    
-    // move across rooms
+
+    // Only moving out of rooms that need emptying and into rooms that are
+    // ready keeps every amphipod to one trip out and one trip in, so the
+    // states can't loop back on themselves.
     for room1 in rooms {
-        for room2 in rooms {
-            let top1 = if g.rooms[ti(*room1)][0] != '.' {
-                0
-            } else if g.rooms[ti(*room1)][1] != '.' {
-                1
-            } else {
-                continue;
-            };
+        if !room_has_strangers(g, *room1) {
+            continue;
+        }
+        let top1 = match room_top(g, *room1) {
+            Some(top) => top,
+            None => continue,
+        };
 
-            let top2 = if g.rooms[ti(*room2)][0] != '.' {
-                0
-            } else if g.rooms[ti(*room2)][1] != '.' {
-                1
-            } else {
+        // move across rooms
+        for room2 in rooms {
+            if room1 == room2 {
                 continue;
-            };
+            }
+            if let Some(open) = room_open(g, *room2) {
+                let act = Action {
+                    start: Location {
+                        position: top1,
+                        place: Places::Room(*room1),
+                    },
+                    stop: Location {
+                        position: open,
+                        place: Places::Room(*room2),
+                    },
+                };
+                if path_clear(g, &act) {
+                    actions.push(act);
+                }
+            }
+        }
 
+        //move top elements into hallway spots;
+        for spot in hallway_spots {
             let act = Action {
                 start: Location {
                     position: top1,
                     place: Places::Room(*room1),
                 },
                 stop: Location {
-                    position: top2,
-                    place: Places::Room(*room2),
+                    position: *spot,
+                    place: Places::Hallway,
                 },
             };
             if path_clear(g, &act) {
-                println!("room -> room");
                 actions.push(act);
             }
         }
@@ -515,22 +567,19 @@ pub fn generate_all_possible_actions(g: &Game) -> Vec<Action> {
 
     //every element in hallway can move into destination room if it's open for them
     for spot in hallway_spots {
-        for room in rooms {
-            let top = if g.rooms[ti(*room)][0] != '.' {
-                0
-            } else if g.rooms[ti(*room)][1] != '.' {
-                1
-            } else {
-                continue;
-            };
+        if g.hallway[*spot] == '.' {
+            continue;
+        }
+        let room = char_to_letter(g.hallway[*spot]);
+        if let Some(open) = room_open(g, room) {
             let act = Action {
                 start: Location {
                     position: *spot,
                     place: Places::Hallway,
                 },
                 stop: Location {
-                    position: top,
-                    place: Places::Hallway,
+                    position: open,
+                    place: Places::Room(room),
                 },
             };
             if path_clear(g, &act) {
@@ -541,88 +590,127 @@ pub fn generate_all_possible_actions(g: &Game) -> Vec<Action> {
     return actions;
 }
 
-#[cached]
+/// The cheapest cost of finishing from `g`, with a fresh memo table.
 pub fn find_lowest_actions(g: Game) -> Option<i32> {
-    //println!("Starting with: \n{}\n", &g);
-    if is_done(&g) {
-        println!("Finished @ {}", g.cost);
-        return Some(g.cost);
-    }
-    if g.cost > 13000 {
-        //println!("Too expensive");
-        return None;
-    }
+    let mut memo = Memo::new();
+    let cost = lowest_cost(g, &mut memo);
+    debug!("memo: {}", memo.stats());
+    return cost;
+}
 
-    let possible = generate_all_possible_actions(&g);
+/// The cheapest cost of finishing from `g`, not counting what it took to get
+/// there, so equal positions share a memo entry.
+pub fn lowest_cost(g: Game, memo: &mut Memo<Game, Option<i32>>) -> Option<i32> {
+    memo.get_or_insert_with(g.clone(), |memo| lowest_cost_from(&g, memo))
+}
 
-    for p in &possible {
-        assert!(path_clear(&g, &p));
+fn lowest_cost_from(g: &Game, memo: &mut Memo<Game, Option<i32>>) -> Option<i32> {
+    if is_done(g) {
+        return Some(0);
     }
-    if possible.len() == 0 {
+
+    let possible = generate_all_possible_actions(g);
+    if possible.is_empty() {
         //println!("No moves left");
         return None;
     }
 
-    let mut best = possible
+    possible
         .iter()
-        .map(|a| {
-            let moved = move_thing(&g.clone(), a);
-            find_lowest_actions(moved)
+        .filter_map(|a| {
+            let mut moved = move_thing(g, a);
+            let step = moved.cost - g.cost;
+            moved.cost = 0;
+            lowest_cost(moved, memo).map(|rest| rest + step)
         })
-        .filter(|x| *x != None)
-        .map(|x| x.unwrap())
-        .collect::<Vec<i32>>();
-    best.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    if best.len() != 0 {
-        return Some(best.first().unwrap().clone());
-    } else {
-        return None;
+        .min()
+}
+
+/// The amphipods shuffled into the burrow, redrawn until both the burrow and
+/// its unfolded version can be sorted, since not every shuffle can, and the
+/// amphipods are not sorted already. The size is ignored.
+pub fn generate(rng: &mut Rng, _size: usize) -> String {
+    loop {
+        let mut pods: Vec<char> = "AABBCCDD".chars().collect();
+        rng.shuffle(&mut pods);
+        let text = format!(
+            "#############\n#...........#\n###{}#{}#{}#{}###\n  #{}#{}#{}#{}#\n  #########",
+            pods[0], pods[1], pods[2], pods[3], pods[4], pods[5], pods[6], pods[7]
+        );
+        if let Ok(g) = parse_data(&text) {
+            if find_lowest_actions(unfold(&g)).is_some() && find_lowest_actions(g).is_some_and(|cost| cost > 0) {
+                return text;
+            }
+        }
     }
 }
 
-pub fn testing_things() {
-    let mut testing = Game {
-        hallway: ['.'; 11],
-        rooms: [['B', 'A'], ['C', 'D'], ['B', 'C'], ['D', 'A']], //change to variants someday
-        cost: 0,
-    };
+pub struct Solver;
 
-    testing.hallway[0] = 'B'; 
-    testing.rooms[2][0] = '.';
+impl Solution for Solver {
+    type Input = Game;
 
-    let right = Action {
-        start: Location {
-            place: Places::Room(Letter::B),
-            position: 0
-        },
-        stop: Location {
-            place: Places::Room(Letter::C),
-            position: 0
-        }
-    };
-    assert!(path_clear(&testing, &right));
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
 
-    let wrong = Action {
-        start: Location {
-            place: Places::Room(Letter::A),
-            position: 0
-        },
-        stop: Location {
-            place: Places::Room(Letter::C),
-            position: 0
-        }
-    };
+    fn part1(g: &Self::Input) -> Answer {
+        find_lowest_actions(g.clone()).unwrap().into()
+    }
 
-    assert!(path_clear(&testing, &wrong));
+    fn part2(g: &Self::Input) -> Answer {
+        find_lowest_actions(unfold(g)).unwrap().into()
+    }
 }
 
-pub fn main() {
-    testing_things();
-    // example_test();
-    // let example = Game {
-    //     hallway: ['.'; 11],
-    //     rooms: [['B', 'A'], ['C', 'D'], ['B', 'C'], ['D', 'A']],
-    //     cost: 0,
-    // };
-    // dbg!(find_lowest_actions(example));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+
+    #[test]
+    fn checks_paths() {
+        let mut testing = Game {
+            hallway: ['.'; 11],
+            rooms: [vec!['B', 'A'], vec!['C', 'D'], vec!['B', 'C'], vec!['D', 'A']],
+            cost: 0,
+        };
+
+        testing.hallway[0] = 'B';
+        testing.rooms[2][0] = '.';
+
+        let right = Action {
+            start: Location {
+                place: Places::Room(Letter::B),
+                position: 0
+            },
+            stop: Location {
+                place: Places::Room(Letter::C),
+                position: 0
+            }
+        };
+        assert!(path_clear(&testing, &right));
+
+        let wrong = Action {
+            start: Location {
+                place: Places::Room(Letter::A),
+                position: 0
+            },
+            stop: Location {
+                place: Places::Room(Letter::C),
+                position: 0
+            }
+        };
+
+        assert!(!path_clear(&testing, &wrong));
+    }
+
+    #[test]
+    fn finds_cost_with_any_memo() {
+        let g = parse_data(&input::named(23, "sample").unwrap()).unwrap();
+        assert_eq!(find_lowest_actions(g.clone()), Some(12521));
+        let mut bounded = Memo::bounded(500);
+        assert_eq!(lowest_cost(g, &mut bounded), Some(12521));
+        assert!(bounded.len() <= 500);
+    }
 }
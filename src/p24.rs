@@ -1,8 +1,13 @@
-use cached::proc_macro::cached;
-use itertools::Itertools;
+//! Day 24: Arithmetic Logic Unit. `parse_data` reads a MONAD program of
+//! `Instruction`s that `execute` runs; the solver skips running it by reading the
+//! three numbers that differ between its sections.
+
 use num_format::{Locale, ToFormattedString};
-use rayon::prelude::*;
-use std::time::{Duration, Instant};
+use std::fmt;
+use std::time::Instant;
+use generate::Rng;
+use parser::{self, Cursor, ParseError};
+use solution::{Answer, Solution};
 
 #[derive(Hash, Clone, Debug, Eq, PartialEq, Copy)]
 pub enum Op {
@@ -30,49 +35,117 @@ pub enum Arg {
 
 #[derive(Hash, Clone, Debug, Eq, PartialEq)]
 pub struct Instruction {
-    op: Op,
-    args: Vec<Arg>,
+    pub op: Op,
+    pub args: Vec<Arg>,
 }
 
 #[derive(Hash, Clone, Debug, Eq, PartialEq, Copy)]
 pub struct State {
-    w: i64,
-    x: i64,
-    y: i64,
-    z: i64,
+    pub w: i64,
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
 }
 
-pub fn parse_op(s: &str) -> Op {
-    match s {
-        "inp" => Op::Inp,
-        "add" => Op::Add,
-        "mul" => Op::Mul,
-        "div" => Op::Div,
-        "mod" => Op::Mod,
-        "eql" => Op::Eql,
-        _ => unreachable!(),
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Op::Inp => "inp",
+            Op::Add => "add",
+            Op::Mul => "mul",
+            Op::Div => "div",
+            Op::Mod => "mod",
+            Op::Eql => "eql",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Register::W => "w",
+            Register::X => "x",
+            Register::Y => "y",
+            Register::Z => "z",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for Arg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arg::Register(r) => write!(f, "{}", r),
+            Arg::Value(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.op)?;
+        for arg in &self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_op(c: &mut Cursor) -> Result<Op, ParseError> {
+    let start = c.clone();
+    match c.word()? {
+        "inp" => Ok(Op::Inp),
+        "add" => Ok(Op::Add),
+        "mul" => Ok(Op::Mul),
+        "div" => Ok(Op::Div),
+        "mod" => Ok(Op::Mod),
+        "eql" => Ok(Op::Eql),
+        _ => Err(start.error("one of `inp`, `add`, `mul`, `div`, `mod` or `eql`")),
+    }
+}
+
+pub fn parse_register(c: &mut Cursor) -> Result<Register, ParseError> {
+    match c.one_of("wxyz")? {
+        'w' => Ok(Register::W),
+        'x' => Ok(Register::X),
+        'y' => Ok(Register::Y),
+        _ => Ok(Register::Z),
     }
 }
 
-pub fn parse_arg(s: &str) -> Arg {
-    match s {
-        "w" => Arg::Register(Register::W),
-        "x" => Arg::Register(Register::X),
-        "y" => Arg::Register(Register::Y),
-        "z" => Arg::Register(Register::Z),
-        _ => Arg::Value(s.parse::<i64>().unwrap()),
+pub fn parse_arg(c: &mut Cursor) -> Result<Arg, ParseError> {
+    if let Ok(r) = parse_register(c) {
+        return Ok(Arg::Register(r));
+    }
+    match c.int::<i64>() {
+        Ok(v) => Ok(Arg::Value(v)),
+        Err(_) => Err(c.error("a register or a number")),
     }
 }
 
-pub fn parse_instruction(s: &str) -> Instruction {
-    let (ops, rest) = s.trim().split_once(" ").unwrap();
-    let op = parse_op(ops);
-    let args = rest.split(" ").map(parse_arg).collect();
-    Instruction { op, args }
+/// `inp a` or `op a b`, where `a` is always the register the result goes in.
+pub fn parse_instruction(c: &mut Cursor) -> Result<Instruction, ParseError> {
+    c.skip_spaces();
+    let op = parse_op(c)?;
+    c.expect(" ")?;
+    let mut args = vec![Arg::Register(parse_register(c)?)];
+    if op != Op::Inp {
+        c.expect(" ")?;
+        args.push(parse_arg(c)?);
+    }
+    c.skip_spaces();
+    Ok(Instruction { op, args })
 }
 
-pub fn parse_data(s: &str) -> Vec<Instruction> {
-    s.lines().map(parse_instruction).collect()
+pub fn parse_data(s: &str) -> Result<Vec<Instruction>, ParseError> {
+    parser::each_line(s, parse_instruction)
+}
+
+/// Writes a program back out one instruction per line, the way it is read.
+pub fn print_data(ints: &[Instruction]) -> String {
+    let lines: Vec<String> = ints.iter().map(|i| i.to_string()).collect();
+    return lines.join("\n");
 }
 
 pub fn r_to_u(v: Arg) -> usize {
@@ -85,6 +158,7 @@ pub fn r_to_u(v: Arg) -> usize {
     }
 }
 
+/// Runs a program from `state`, reading `inputs` in order.
 pub fn execute(state: State, ints: Vec<Instruction>, mut inputs: Vec<i64>) -> State {
     let mut state = [state.w, state.x, state.y, state.z];
     inputs.reverse();
@@ -140,7 +214,6 @@ pub fn execute(state: State, ints: Vec<Instruction>, mut inputs: Vec<i64>) -> St
                 };
                 state[r_to_u(left)] = if state[r_to_u(left)] == right { 1 } else { 0 }
             }
-            _ => unimplemented!(),
         }
         //println!("{:?}", state);
     }
@@ -152,92 +225,11 @@ pub fn execute(state: State, ints: Vec<Instruction>, mut inputs: Vec<i64>) -> St
     }
 }
 
-pub fn testing() {
-    // let sample1 = "inp x
-    // mul x -1";
-    // let ints = parse_data(sample1);
-    // assert_eq!(
-    //     State {
-    //         x: -1,
-    //         y: 0,
-    //         z: 0,
-    //         w: 0
-    //     },
-    //     execute(ints.clone(), vec![1])
-    // );
-    // assert_eq!(
-    //     State {
-    //         x: 1,
-    //         y: 0,
-    //         z: 0,
-    //         w: 0
-    //     },
-    //     execute(ints, vec![-1])
-    // );
-
-    // let sample2 = "inp z
-    // inp x
-    // mul z 3
-    // eql z x";
-    // let ints = parse_data(sample2);
-
-    // assert_eq!(
-    //     State {
-    //         x: 2,
-    //         y: 0,
-    //         z: 0,
-    //         w: 0
-    //     },
-    //     execute(ints.clone(), vec![1, 2])
-    // );
-
-    // assert_eq!(
-    //     State {
-    //         x: 9,
-    //         y: 0,
-    //         z: 1,
-    //         w: 0
-    //     },
-    //     execute(ints.clone(), vec![3, 9])
-    // );
-    println!("Doing binsample");
-    let binsample = "inp w
-    add z w
-    mod z 2
-    div w 2
-    add y w
-    mod y 2
-    div w 2
-    add x w
-    mod x 2
-    div w 2
-    mod w 2";
-    let ints = parse_data(binsample);
-    assert_eq!(
-        State {
-            x: 0,
-            y: 0,
-            z: 1,
-            w: 1
-        },
-        execute(
-            State {
-                x: 0,
-                y: 0,
-                z: 0,
-                w: 0
-            },
-            ints.clone(),
-            vec![9]
-        )
-    );
-}
-
 pub fn get_sections(mints: Vec<Instruction>) -> Vec<Vec<Instruction>> {
     let mut sections: Vec<Vec<Instruction>> = vec![];
     let mut current: Vec<Instruction> = vec![mints[0].clone()];
-    for i in 1..mints.len() {
-        let mint = mints[i].clone();
+    for mint in &mints[1..] {
+        let mint = mint.clone();
         if mint
             == (Instruction {
                 op: Op::Inp,
@@ -273,7 +265,7 @@ pub fn func(input: i64, previous_z: i64, variables: (bool, i64, i64)) -> i64 {
     let mut z = previous_z;
 
     if variables.0 {
-        z = z / 26; //only way to become zero
+        z /= 26; //only way to become zero
     }
 
     if (previous_z % 26) + variables.1 != input {
@@ -284,103 +276,184 @@ pub fn func(input: i64, previous_z: i64, variables: (bool, i64, i64)) -> i64 {
     z
 }
 
-pub fn test_same(sections: Vec<Vec<Instruction>>, vs: Vec<(bool, i64, i64)>) {
-    for i in 0..sections.len() {
-        for z in 1..1000 {
-            for digit in 1..10 {
-                let state = State {
-                    x: 0,
-                    y: 0,
-                    w: 0,
-                    z: z,
-                };
-                let execed = execute(state, sections[i].clone(), vec![digit]);
-                let funced = func(digit, z, vs[i as usize].clone());
-                assert_eq!(execed.z, funced);
-            }
-        }
-    }
-}
-
 #[derive(Hash, Clone, Debug, Eq, PartialEq, Copy)]
 struct Compute {
     digits: [u8; 14],
     z: i64,
 }
 
-pub fn main() {
-    testing();
-
-    let monad = include_str!("../inputs/day24puzzle.txt");
-    let mints: Vec<Instruction> = parse_data(monad);
-
-    let sections = get_sections(mints);
-    let vs = get_variables(sections.clone());
-    test_same(sections, vs.clone());
-
-    for v in &vs {
-        println!("{:?}", v);
+impl Compute {
+    fn value(&self) -> i64 {
+        self.digits.iter().fold(0, |acc, d| acc * 10 + *d as i64)
     }
+}
 
+/// Every model number the MONAD accepts, smallest first. Numbers whose z has
+/// grown past what the remaining sections can divide away get dropped early.
+fn valid_numbers(vs: &[(bool, i64, i64)]) -> Vec<Compute> {
     let mut numbers = vec![Compute {
         z: 0,
         digits: [0; 14],
     }];
-    let limit = (26 as i64).pow(4); //4?
-    let limits = [7, 7, 7, 7, 7, 6, 6, 5, 5, 4, 3, 3, 2, 1];
-
-    dbg!(limit);
     for i in 0..14 {
         let start = Instant::now();
-        let new_limit = (26 as i64).pow(limits[i as usize]);
+        let pops_left = vs[i as usize + 1..].iter().filter(|v| v.0).count();
+        let new_limit = 26_i64.pow(pops_left as u32);
 
-        println!(
+        info!(
             "step {}, testing {} of {}",
             i,
             numbers.len().to_formatted_string(&Locale::en),
-            (9 as i64).pow(i).to_formatted_string(&Locale::en)
+            9_i64.pow(i).to_formatted_string(&Locale::en)
         );
         let mut new_numbers = vec![];
         for number in &numbers {
             let z_previous = if i == 0 { 0 } else { number.z };
             for digit in 1..=9 {
-                let computed_z = func(digit, z_previous, vs[i as usize].clone());
-                let mut new_number = number.clone();
+                let computed_z = func(digit, z_previous, vs[i as usize]);
+                let mut new_number = *number;
                 new_number.digits[i as usize] = digit as u8;
                 new_number.z = computed_z;
-                if computed_z <= new_limit {
+                if computed_z < new_limit {
                     new_numbers.push(new_number)
                 }
             }
         }
         numbers = new_numbers;
-        println!("Time elapsed in this step is: {:?}\n", start.elapsed());
+        info!("step {} took {:?}", i, start.elapsed());
     }
-    for number in numbers.iter().rev() {
-        if number.z == 0 {
-            println!("{:?}", number.digits);
+    numbers.into_iter().filter(|n| n.z == 0).collect()
+}
+
+/// One section of MONAD, which reads a digit and either pushes it onto `z`
+/// as a base 26 digit or pops one off and checks it against the new digit.
+fn generate_section(pop: bool, check: i64, offset: i64) -> String {
+    let lines = [
+        "inp w".to_string(),
+        "mul x 0".to_string(),
+        "add x z".to_string(),
+        "mod x 26".to_string(),
+        format!("div z {}", if pop { 26 } else { 1 }),
+        format!("add x {}", check),
+        "eql x w".to_string(),
+        "eql x 0".to_string(),
+        "mul y 0".to_string(),
+        "add y 25".to_string(),
+        "mul y x".to_string(),
+        "add y 1".to_string(),
+        "mul z y".to_string(),
+        "mul y 0".to_string(),
+        "add y w".to_string(),
+        format!("add y {}", offset),
+        "mul y x".to_string(),
+        "add z y".to_string(),
+    ];
+    return lines.join("\n");
+}
+
+/// A MONAD program with seven digits pushed and seven popped in a random
+/// order, so there are always model numbers it accepts. The size is ignored.
+pub fn generate(rng: &mut Rng, _size: usize) -> String {
+    let mut sections = vec![];
+    let mut stack = vec![];
+    let mut pushes = 7;
+    for _ in 0..14 {
+        if stack.is_empty() || (pushes > 0 && rng.chance(1, 2)) {
+            let offset = rng.range(1, 15);
+            stack.push(offset);
+            pushes -= 1;
+            sections.push(generate_section(false, rng.range(10, 16), offset));
+        } else {
+            let pushed = stack.pop().unwrap();
+            sections.push(generate_section(true, rng.range(-8, 8) - pushed, rng.range(1, 15)));
         }
     }
+    return sections.join("\n");
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Vec<(bool, i64, i64)>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Ok(get_variables(get_sections(parse_data(input)?)))
+    }
+
+    fn part1(vs: &Self::Input) -> Answer {
+        valid_numbers(vs).last().unwrap().value().into()
+    }
+
+    fn part2(vs: &Self::Input) -> Answer {
+        valid_numbers(vs).first().unwrap().value().into()
+    }
+}
 
-    // for d1 in 1..9 {
-    //     let mut v = func(d1, 0, vs[0]);
-    //     dbg!(v);
-    //     for d2 in 1..9 {
-    //         dbg!(v);
-    //         v = func(d2, v, vs[1]);
-    //         for d3 in 1..9 {
-    //             dbg!(v);
-    //             v = func(d3, v, vs[2]);
-    //             for d4 in 1..9 {
-    //                 v = func(d4, v, vs[3]);
-    //                 for d5 in 1..9 {
-    //                     v = func(d5, v, vs[4]);
-    //                     for d6 in 1..9 {
-    //                         v = func(d6, v, vs[5]);
-    //                     }
-    //                 }
-    //             }
-    //         }
-    //     }
-    // }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+    use proptest::prelude::*;
+
+    const ZERO: State = State { w: 0, x: 0, y: 0, z: 0 };
+
+    fn register() -> impl Strategy<Value = Register> {
+        prop::sample::select(vec![Register::W, Register::X, Register::Y, Register::Z])
+    }
+
+    fn instruction() -> impl Strategy<Value = Instruction> {
+        let op = prop::sample::select(vec![Op::Inp, Op::Add, Op::Mul, Op::Div, Op::Mod, Op::Eql]);
+        let arg = prop_oneof![register().prop_map(Arg::Register), any::<i64>().prop_map(Arg::Value)];
+        (op, register(), arg).prop_map(|(op, r, arg)| {
+            let args = if op == Op::Inp { vec![Arg::Register(r)] } else { vec![Arg::Register(r), arg] };
+            Instruction { op, args }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn print_data_round_trips(ints in prop::collection::vec(instruction(), 1..50)) {
+            prop_assert_eq!(parse_data(&print_data(&ints)).unwrap(), ints);
+        }
+    }
+
+    #[test]
+    fn executes_samples() {
+        let ints = parse_data("inp x\nmul x -1").unwrap();
+        assert_eq!(State { x: -1, ..ZERO }, execute(ZERO, ints.clone(), vec![1]));
+        assert_eq!(State { x: 1, ..ZERO }, execute(ZERO, ints, vec![-1]));
+
+        let ints = parse_data("inp z\ninp x\nmul z 3\neql z x").unwrap();
+        assert_eq!(State { x: 2, z: 0, ..ZERO }, execute(ZERO, ints.clone(), vec![1, 2]));
+        assert_eq!(State { x: 9, z: 1, ..ZERO }, execute(ZERO, ints, vec![3, 9]));
+
+        let binsample = "inp w
+        add z w
+        mod z 2
+        div w 2
+        add y w
+        mod y 2
+        div w 2
+        add x w
+        mod x 2
+        div w 2
+        mod w 2";
+        let ints = parse_data(binsample).unwrap();
+        assert_eq!(State { x: 0, y: 0, z: 1, w: 1 }, execute(ZERO, ints, vec![9]));
+    }
+
+    /// Every section of the puzzle does what `func` says it does.
+    #[test]
+    fn sections_match_func() {
+        let sections = get_sections(parse_data(&input::named(24, "puzzle").unwrap()).unwrap());
+        let vs = get_variables(sections.clone());
+        for i in 0..sections.len() {
+            for z in 1..1000 {
+                for digit in 1..10 {
+                    let execed = execute(State { z, ..ZERO }, sections[i].clone(), vec![digit]);
+                    assert_eq!(execed.z, func(digit, z, vs[i]));
+                }
+            }
+        }
+    }
 }
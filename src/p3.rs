@@ -1,141 +1,348 @@
-use std::fs;
-use core::cmp::max;
-
-static reading_len: usize = 12; 
-
-pub fn day_three_input() -> Vec<Vec<u32>> {
-    let contents = fs::read_to_string("inputs/day3puzzle1.txt")
-    //let contents = fs::read_to_string("inputs/day3sample.txt")
-    .expect("Something went wrong reading the file");
-    let lines = contents.lines();
-    let ints = lines.map(|line| {
-        return line.chars().map(|v| {
-            if v == '1' {
-                return 1;
-            }
-            else {
-                return 0;
-            }
-        }).collect();
-    }).collect();
-    return ints;
+//! Day 3: Binary Diagnostic. The readings are packed into `u64` words, as many
+//! per reading as its width needs, with the width taken from the input.
+//! `rates` finds the gamma and epsilon rates and `rating` the oxygen and CO2
+//! ratings, which `d3p1` and `d3p2` multiply together.
+
+use std::convert::TryFrom;
+
+use generate::Rng;
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
+
+/// How wide the generated readings are, as in the real input.
+const WIDTH: usize = 12;
+
+/// Readings of the same width, each packed into `words` words with the most
+/// significant first, so a reading's words compare the way its bits do.
+/// Readings up to 64 bits wide take a single word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Readings {
+    pub width: usize,
+    words: usize,
+    packed: Vec<u64>,
 }
 
-pub fn get_greeks(ints: Vec<Vec<u32>>) -> (Vec<u32>, Vec<u32>) {
-    let mut sums : Vec<u32> = vec![];
-    for i in 0..reading_len {
-        let mut index_sum = 0;
-        for b in ints.clone() {
-            index_sum += b[i]
+impl Readings {
+    pub fn new(width: usize) -> Readings {
+        Readings {
+            width,
+            words: width.div_ceil(64).max(1),
+            packed: vec![],
         }
-        sums.push(index_sum)
-    }
-    println!("Ints: {:?}", ints);
-    println!("Sums: {:?}",sums);
-    println!("Len: {}", ints.len());
-    let halfway: f32 = ((ints.len() as f32)/2.0) as f32;
-
-    let mut gamma: Vec<u32> = vec![];
-    let mut epsilon: Vec<u32> = vec![];
-    for s in sums {
-        let sf = s as f32;
-        println!("{} {}", sf, halfway);
-        if sf >= halfway  {
-            gamma.push(1);
-            epsilon.push(0);
+    }
+
+    /// Adds a reading of `width` bits, first bit first. Panics if there are
+    /// more or fewer bits than that, leaving the readings as they were.
+    pub fn push<I: IntoIterator<Item = bool>>(&mut self, bits: I) {
+        let mut reading = vec![0; self.words];
+        let mut count = 0;
+        for bit in bits {
+            assert!(count < self.width, "a reading has {} bits, not more", self.width);
+            let (word, mask) = self.locate(count);
+            if bit {
+                reading[word] |= mask;
+            }
+            count += 1;
         }
-        else  {
-            gamma.push(0);
-            epsilon.push(1);
+        assert_eq!(count, self.width, "a reading has {} bits", self.width);
+        self.packed.extend(reading);
+    }
+
+    pub fn len(&self) -> usize {
+        self.packed.len() / self.words
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packed.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> &[u64] {
+        &self.packed[i * self.words..(i + 1) * self.words]
+    }
+
+    /// The word and mask holding bit `i`, counting from the first bit.
+    fn locate(&self, i: usize) -> (usize, u64) {
+        let from_end = self.width - 1 - i;
+        (self.words - 1 - from_end / 64, 1 << (from_end % 64))
+    }
+
+    /// Bit `i` of a reading, counting from the first bit.
+    pub fn bit(&self, reading: &[u64], i: usize) -> bool {
+        let (word, mask) = self.locate(i);
+        reading[word] & mask != 0
+    }
+
+    /// How many readings have a 1 at each position, in one pass over them.
+    pub fn ones(&self) -> Vec<usize> {
+        let mut ones = vec![0; self.width];
+        for r in 0..self.len() {
+            let reading = self.get(r);
+            for (i, count) in ones.iter_mut().enumerate() {
+                if self.bit(reading, i) {
+                    *count += 1;
+                }
+            }
         }
+        return ones;
+    }
+
+    /// A reading as its binary digits.
+    pub fn to_binary(&self, reading: &[u64]) -> String {
+        (0..self.width).map(|i| if self.bit(reading, i) { '1' } else { '0' }).collect()
+    }
+}
+
+/// The value of a packed reading, if it fits in one word.
+pub fn to_u64(reading: &[u64]) -> Option<u64> {
+    let (last, rest) = reading.split_last()?;
+    if rest.iter().any(|w| *w != 0) {
+        return None;
     }
-    return (gamma, epsilon);
+    return Some(*last);
 }
 
-pub fn binarray_to_int(arr: Vec<u32>) -> isize {
-    let mut s = "".to_owned(); 
-    for a in arr {
-        s.push_str(&a.to_string());
+/// Every reading has to be as wide as the first.
+pub fn parse_data(s: &str) -> Result<Readings, ParseError> {
+    let mut width = None;
+    let bits = parser::each_line(s, |c| {
+        let start = c.clone();
+        let mut bits = vec![];
+        while !c.is_empty() {
+            bits.push(c.one_of("01")? == '1');
+        }
+        match width {
+            None => width = Some(bits.len()),
+            Some(w) if w != bits.len() => return Err(start.error(&format!("a reading of {} bits", w))),
+            Some(_) => {}
+        }
+        return Ok(bits);
+    })?;
+    let mut readings = Readings::new(width.ok_or_else(|| ParseError::at_end(s, "a reading"))?);
+    for b in bits {
+        readings.push(b);
     }
-    let i = isize::from_str_radix(&s,2).unwrap();
-    return i;
+    return Ok(readings);
 }
 
-pub fn d3p1() {
-    let ints = day_three_input().clone();
-    let (gamma, epsilon) = get_greeks(ints);
-    //println!("{:?}", ints.clone());
-    println!("Gamma: {:?}", gamma.clone());
-    println!("Epsilon: {:?}", epsilon.clone());
-    let gi = binarray_to_int(gamma);
-    let ei = binarray_to_int(epsilon);
-    println!("Gamma: {}", gi);
-    println!("Epsilon: {}", ei);
-    println!("Together: {}", ei*gi);
+/// The gamma and epsilon rates, from the most and least common bit at each
+/// position. Where there are as many ones as zeros gamma takes the 1, as the
+/// oxygen rating does.
+pub fn rates(readings: &Readings) -> (Vec<u64>, Vec<u64>) {
+    let ones = readings.ones();
+    trace!("ones: {:?} of {}", ones, readings.len());
+    let mut gamma = Readings::new(readings.width);
+    gamma.push(ones.iter().map(|n| 2 * n >= readings.len()));
+    let mut epsilon = Readings::new(readings.width);
+    epsilon.push(ones.iter().map(|n| 2 * n < readings.len()));
+    return (gamma.packed, epsilon.packed);
 }
 
-pub fn d3p2() {
-    let ints = day_three_input().clone();
-    //  
-    let mut oxys = ints.clone();
-    for i in 0..reading_len {
-        let gamma = get_greeks(oxys.clone()).0; 
-        let mut new_oxys = vec![];
-        println!("Gamma: {:?}", gamma);
-        println!("Should be {} at {}", gamma[i], i);
-        for oxy in oxys.clone() {
-            if gamma[i] == oxy[i] {
-                new_oxys.push(oxy)
-            }
+/// Which readings a rating keeps at each bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criterion {
+    /// The oxygen generator rating keeps the more common bit, and 1 on a tie.
+    MostCommon,
+    /// The CO2 scrubber rating keeps the less common bit, and 0 on a tie.
+    LeastCommon,
+}
+
+impl Criterion {
+    /// Whether to keep the readings with a 1, or `None` when every reading
+    /// left has the same bit and there is nothing to choose between.
+    pub fn keeps_ones(self, ones: usize, zeros: usize) -> Option<bool> {
+        if ones == 0 || zeros == 0 {
+            return None;
         }
-        if new_oxys.len() == 0 {
-            break;
+        match self {
+            Criterion::MostCommon => Some(ones >= zeros),
+            Criterion::LeastCommon => Some(ones < zeros),
         }
-        oxys = new_oxys;
-        println!("{:?}", oxys);
-        println!("");
-        if oxys.len() == 1 {
+    }
+}
+
+/// Filters the readings bit by bit until one is left. The readings are sorted
+/// once, after which the ones still in the running are always a run of them
+/// with their zeros before their ones, so each bit only needs a binary search.
+/// Bits every remaining reading shares are passed over, and if readings are
+/// left once the bits run out they are all the same reading. There is no
+/// rating without readings.
+pub fn rating(readings: &Readings, criterion: Criterion) -> Option<Vec<u64>> {
+    if readings.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<&[u64]> = (0..readings.len()).map(|i| readings.get(i)).collect();
+    sorted.sort_unstable();
+    let (mut lo, mut hi) = (0, sorted.len());
+    for i in 0..readings.width {
+        if hi - lo <= 1 {
             break;
         }
+        let split = lo + sorted[lo..hi].partition_point(|r| !readings.bit(r, i));
+        match criterion.keeps_ones(hi - split, split - lo) {
+            Some(true) => lo = split,
+            Some(false) => hi = split,
+            None => trace!("{:?}: all {} left share bit {}", criterion, hi - lo, i),
+        }
+    }
+    return Some(sorted[lo].to_vec());
+}
+
+/// `x` times `y`, exact as text if it is too big for an `Int`.
+pub fn answer(x: u64, y: u64) -> Answer {
+    let p = x as u128 * y as u128;
+    match i64::try_from(p) {
+        Ok(i) => Answer::Int(i),
+        Err(_) => Answer::Str(p.to_string()),
     }
+}
+
+/// `a` times `b`, spelled out in binary if the readings are wider than 64
+/// bits.
+fn product(readings: &Readings, a: &[u64], b: &[u64]) -> Answer {
+    match (to_u64(a), to_u64(b)) {
+        (Some(x), Some(y)) => answer(x, y),
+        _ => Answer::Str(format!("{} * {}", readings.to_binary(a), readings.to_binary(b))),
+    }
+}
+
+/// The power consumption, gamma times epsilon.
+pub fn d3p1(readings: &Readings) -> Answer {
+    let (gamma, epsilon) = rates(readings);
+    debug!("gamma: {}", readings.to_binary(&gamma));
+    debug!("epsilon: {}", readings.to_binary(&epsilon));
+    return product(readings, &gamma, &epsilon);
+}
+
+/// The life support rating, the oxygen generator rating times the CO2 scrubber
+/// rating, with ratings of 0 if there are no readings.
+pub fn d3p2(readings: &Readings) -> Answer {
+    let zero = || vec![0; readings.words];
+    let oxygen = rating(readings, Criterion::MostCommon).unwrap_or_else(zero);
+    debug!("oxygen generator rating: {}", readings.to_binary(&oxygen));
+    let co2 = rating(readings, Criterion::LeastCommon).unwrap_or_else(zero);
+    debug!("CO2 scrubber rating: {}", readings.to_binary(&co2));
+    return product(readings, &oxygen, &co2);
+}
+
+/// `size` different readings, or every reading there is if that is fewer.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut readings: Vec<usize> = (0..1 << WIDTH).collect();
+    rng.shuffle(&mut readings);
+    readings.truncate(size);
+    let lines: Vec<String> = readings.iter().map(|r| format!("{:0width$b}", r, width = WIDTH)).collect();
+    return lines.join("\n");
+}
+
+pub struct Solver;
 
-    println!("Oxys: {:?}", oxys);
-    let first_oxys = &oxys[0];
-    println!("First oxys: {:?}\n", first_oxys);
+impl Solution for Solver {
+    type Input = Readings;
 
-    let mut scrubbers = ints.clone();
-    for i in 0..reading_len {
-        let epsilon = get_greeks(scrubbers.clone()).1; 
-        let mut new_scrubbers = vec![];
-        println!("Epi: {:?}", epsilon);
-        println!("Should be {} at {}", epsilon[i], i);
-        for scrubber in scrubbers.clone() {
-            if epsilon[i] == scrubber[i] {
-                new_scrubbers.push(scrubber)
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1(readings: &Self::Input) -> Answer {
+        d3p1(readings)
+    }
+
+    fn part2(readings: &Self::Input) -> Answer {
+        d3p2(readings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+    use proptest::prelude::*;
+
+    /// The rating the way the puzzle tells it, filtering strings of digits.
+    fn filter(lines: &[String], most_common: bool) -> String {
+        let mut left: Vec<&String> = lines.iter().collect();
+        let mut i = 0;
+        while left.len() > 1 {
+            let ones = left.iter().filter(|l| l.as_bytes()[i] == b'1').count();
+            let zeros = left.len() - ones;
+            if ones > 0 && zeros > 0 {
+                let keep = if most_common { ones >= zeros } else { ones < zeros };
+                let bit = if keep { b'1' } else { b'0' };
+                left.retain(|l| l.as_bytes()[i] == bit);
+            }
+            i += 1;
+            if i == lines[0].len() {
+                break;
             }
         }
-        if new_scrubbers.len() == 0 {
-            break;
-        }
-        scrubbers = new_scrubbers;
-        println!("{:?}", scrubbers);
-        println!("");
-        if scrubbers.len() == 1 {
-            break;
-        }
+        return left[0].clone();
+    }
+
+    #[test]
+    fn answers_sample() {
+        let readings = parse_data(&input::named(3, "sample").unwrap()).unwrap();
+        assert_eq!(readings.width, 5);
+        assert_eq!(d3p1(&readings), Answer::Int(198));
+        assert_eq!(d3p2(&readings), Answer::Int(230));
+    }
+
+    #[test]
+    fn rejects_ragged_readings() {
+        assert_eq!(parse_data("0101\n011"), Err(ParseError::new(2, 1, "a reading of 4 bits")));
+        assert_eq!(parse_data("01\n0a").unwrap_err().column, 2);
+        assert!(parse_data("").is_err());
+    }
+
+    #[test]
+    fn passes_over_shared_bits() {
+        let readings = parse_data("0110\n0111\n0111").unwrap();
+        assert_eq!(to_u64(&rating(&readings, Criterion::MostCommon).unwrap()), Some(0b0111));
+        assert_eq!(to_u64(&rating(&readings, Criterion::LeastCommon).unwrap()), Some(0b0110));
+        let same = parse_data("101\n101").unwrap();
+        assert_eq!(to_u64(&rating(&same, Criterion::LeastCommon).unwrap()), Some(0b101));
+    }
+
+    #[test]
+    fn rates_nothing_without_readings() {
+        let empty = Readings::new(5);
+        assert_eq!(rating(&empty, Criterion::MostCommon), None);
+        assert_eq!(d3p2(&empty), Answer::Int(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "a reading has 4 bits, not more")]
+    fn refuses_long_readings() {
+        Readings::new(4).push([true; 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "a reading has 4 bits")]
+    fn refuses_short_readings() {
+        Readings::new(4).push([true; 3]);
     }
 
-    println!("{:?}", oxys);
-    let first_oxy = &oxys[0];
-    println!("First oxys: {:?}\n", first_oxy);
-    let oi = binarray_to_int(first_oxy.to_vec());
-    println!("First oxys: {:?}\n", oi);
+    #[test]
+    fn handles_wide_readings() {
+        let lines = ["1".repeat(70), "0".repeat(70), format!("1{}", "0".repeat(69))];
+        let readings = parse_data(&lines.join("\n")).unwrap();
+        assert_eq!(readings.get(0).len(), 2);
+        assert_eq!(to_u64(readings.get(0)), None);
+        assert_eq!(d3p2(&readings), Answer::Str(format!("{} * {}", lines[0], lines[1])));
+    }
 
-    println!("{:?}", scrubbers);
-    let first_scruber = &scrubbers[0];
-    println!("First scrubber: {:?}\n", first_scruber);
-    let si = binarray_to_int(first_scruber.to_vec());
-    println!("First scrubber: {:?}\n", si);
+    fn lines() -> impl Strategy<Value = Vec<String>> {
+        let digit = prop::sample::select(vec!['0', '1']);
+        let line = move |width| prop::collection::vec(digit.clone(), width).prop_map(|ds| ds.into_iter().collect());
+        (1usize..100).prop_flat_map(move |width| prop::collection::vec(line(width), 1..40))
+    }
 
-    println!("Together: {}", oi*si);
+    proptest! {
+        #[test]
+        fn ratings_match_filtering(lines in lines()) {
+            let readings = parse_data(&lines.join("\n")).unwrap();
+            for most_common in [true, false] {
+                let criterion = if most_common { Criterion::MostCommon } else { Criterion::LeastCommon };
+                prop_assert_eq!(readings.to_binary(&rating(&readings, criterion).unwrap()), filter(&lines, most_common));
+            }
+        }
+    }
 }
@@ -1,105 +1,266 @@
-//Experimenting with using a radix sort
-//To solve Day 3 Part B of Advent of Code 2021 
-//In very few lookups
-
-const DIGITS: usize = 5;
-const LENGTH: usize = 12;
-
-pub fn get_data() -> [u8; LENGTH] {
-    // based off of 
-    // https://github.com/timvisee/advent-of-code-2021/blob/master/day03b/src/main.rs 
-    let mut readings : [u8; LENGTH] = [0; LENGTH];
-    include_str!("../inputs/day3sample.txt")
-        .lines()
-        .map(|l| u8::from_str_radix(l, 2).unwrap())
-        .enumerate()
-        .for_each(|(i,x)| {
-            //dbg!(i,x);
-            readings[i] = x;
-        });
-    return readings;
-}
+//! Day 3 again, with the readings in a bit trie. Each node counts the
+//! readings below it, so the ratings are a single walk down the tree taking
+//! the more or less common branch at every bit, and gamma comes from counts
+//! of ones kept per bit as readings go in and out.
 
-#[derive(Debug)]
-struct Tree {
-    nodes: Vec<Node>,
-    total: usize
-}
+use p3::{self, Criterion};
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
 
-#[derive(Debug)]
-struct Node {
-    idx: usize,
-    one: Option<usize>,
-    zero: Option<usize>,
-    total: i32,
-    ones_count: i32
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Node {
+    /// The children for a 0 and a 1 bit.
+    pub children: [Option<usize>; 2],
+    /// How many readings pass through the node.
+    pub total: usize,
 }
 
-impl Node {
-    pub fn new(idx: usize ) -> Node {
-        return Node{idx: idx, total: 0, ones_count: 0, one: None, zero: None};
-    }
+/// Readings of `width` bits, up to 64, first bit nearest the root. Nodes that
+/// deletions empty are unlinked and reused by later insertions.
+#[derive(Debug, Clone)]
+pub struct Tree {
+    pub width: usize,
+    pub nodes: Vec<Node>,
+    /// How many readings have a 1 at each position.
+    pub ones: Vec<usize>,
+    free: Vec<usize>,
 }
 
 impl Tree {
-    pub fn new() -> Tree {
-        let root = Node {idx: 0, one: None, zero: None, total: 0, ones_count: 0};
+    pub fn new(width: usize) -> Tree {
+        assert!((1..=64).contains(&width), "readings are 1 to 64 bits wide");
         Tree {
-            nodes: vec![root],
-            total: 1
+            width,
+            nodes: vec![Node::default()],
+            ones: vec![0; width],
+            free: vec![],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes[0].total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bit `i` of `value`, counting from the first bit.
+    fn bit(&self, value: u64, i: usize) -> usize {
+        (value >> (self.width - 1 - i) & 1) as usize
+    }
+
+    fn count(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |n| self.nodes[n].total)
+    }
+
+    fn new_node(&mut self) -> usize {
+        match self.free.pop() {
+            Some(n) => {
+                self.nodes[n] = Node::default();
+                n
+            }
+            None => {
+                self.nodes.push(Node::default());
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    pub fn insert(&mut self, value: u64) {
+        assert!(self.width == 64 || value >> self.width == 0, "{} is wider than {} bits", value, self.width);
+        let mut node = 0;
+        self.nodes[0].total += 1;
+        for i in 0..self.width {
+            let b = self.bit(value, i);
+            self.ones[i] += b;
+            node = match self.nodes[node].children[b] {
+                Some(child) => child,
+                None => {
+                    let child = self.new_node();
+                    self.nodes[node].children[b] = Some(child);
+                    child
+                }
+            };
+            self.nodes[node].total += 1;
         }
     }
-    
-    pub fn insert(&mut self, new_value: u8) {
-        let mut leaf_idx = 0;
-        for digit in (0..DIGITS).rev() {
-            let nth_digit_of_val = (new_value & 1 << digit) >> digit;
-            //dbg!(nth_digit_of_val);
-            match (nth_digit_of_val, &self.nodes[leaf_idx]) {
-                (1, Node{one: None, ..}) => {
-                    let new_idx = self.total; 
-                    let new_leaf = Node::new(new_idx);
-                    self.nodes.push(new_leaf);
-                    self.total += 1;
-                    let mut leaf = &mut self.nodes[leaf_idx];
-                    leaf.one = Some(new_idx);
-                    leaf.total += 1; 
-                    leaf.ones_count += 1;
-                    leaf_idx = new_idx;
-                },
-                (0, Node{zero: None, ..}) => {
-                    let new_idx = self.total; 
-                    let new_leaf = Node::new(new_idx); 
-                    self.nodes.push(new_leaf);
-                    self.total += 1;
-                    let mut leaf = &mut self.nodes[leaf_idx];
-                    leaf.zero = Some(new_idx);
-                    leaf.total += 1; 
-                    leaf_idx = new_idx;
-                },
-                (1, _) => {
-                    let mut leaf = &mut self.nodes[leaf_idx];
-                    leaf.total += 1; 
-                    leaf.ones_count += 1;
-                    leaf_idx = leaf.one.unwrap();
-                },
-                (0, _) => {
-                    let mut leaf = &mut self.nodes[leaf_idx];
-                    leaf.total += 1; 
-                    leaf_idx = leaf.zero.unwrap();
-                },
-                (_, _) => unreachable!()
+
+    /// How many times `value` was inserted and not removed since.
+    pub fn count_of(&self, value: u64) -> usize {
+        self.prefix_count(value, self.width)
+    }
+
+    /// Takes out one copy of `value`, or gives back false if there is none.
+    pub fn remove(&mut self, value: u64) -> bool {
+        if self.count_of(value) == 0 {
+            return false;
+        }
+        let mut node = 0;
+        self.nodes[0].total -= 1;
+        for i in 0..self.width {
+            let b = self.bit(value, i);
+            self.ones[i] -= b;
+            let child = self.nodes[node].children[b].expect("a counted value has a path");
+            self.nodes[child].total -= 1;
+            if self.nodes[child].total == 0 {
+                self.nodes[node].children[b] = None;
+                self.free.push(child);
             }
+            node = child;
+        }
+        return true;
+    }
+
+    /// How many readings start with the `len` bits of `prefix`, the last of
+    /// which is its lowest bit.
+    pub fn prefix_count(&self, prefix: u64, len: usize) -> usize {
+        assert!(len <= self.width, "a prefix is at most {} bits", self.width);
+        let mut node = Some(0);
+        for i in 0..len {
+            let b = (prefix >> (len - 1 - i) & 1) as usize;
+            node = node.and_then(|n| self.nodes[n].children[b]);
+        }
+        return self.count(node);
+    }
+
+    /// Walks down the tree taking the branch `criterion` keeps at every bit,
+    /// the only branch there is where the readings below all share the bit.
+    /// Takes one step per bit.
+    pub fn rating(&self, criterion: Criterion) -> Option<u64> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut node = 0;
+        let mut value = 0;
+        for _ in 0..self.width {
+            let [zero, one] = self.nodes[node].children;
+            let take_one = match criterion.keeps_ones(self.count(one), self.count(zero)) {
+                Some(take_one) => take_one,
+                None => one.is_some(),
+            };
+            value = value << 1 | take_one as u64;
+            node = if take_one { one } else { zero }.expect("a non-empty node has a child");
         }
+        return Some(value);
     }
+
+    /// Gamma and epsilon from the ones counted at each bit, gamma taking the
+    /// 1 on a tie.
+    pub fn greeks(&self) -> (u64, u64) {
+        let mut gamma = 0;
+        for ones in &self.ones {
+            gamma = gamma << 1 | (2 * ones >= self.len()) as u64;
+        }
+        let mask = u64::MAX >> (64 - self.width);
+        return (gamma, !gamma & mask);
+    }
+}
+
+/// Every reading has to be as wide as the first, and no wider than 64 bits.
+pub fn parse_data(s: &str) -> Result<Tree, ParseError> {
+    let mut width = None;
+    let readings = parser::each_line(s, |c| {
+        let start = c.clone();
+        let digits = c.take_while(|d| d == '0' || d == '1');
+        match width {
+            None if digits.is_empty() || digits.len() > 64 => return Err(start.error("1 to 64 binary digits")),
+            None => width = Some(digits.len()),
+            Some(w) if w != digits.len() => return Err(start.error(&format!("a reading of {} bits", w))),
+            Some(_) => {}
+        }
+        return Ok(u64::from_str_radix(digits, 2).unwrap());
+    })?;
+    let mut tree = Tree::new(width.ok_or_else(|| ParseError::at_end(s, "a reading"))?);
+    for r in readings {
+        tree.insert(r);
+    }
+    return Ok(tree);
 }
 
-pub fn radix() {
-    let mut tree = Tree::new();
-    let readings = get_data();
-    for reading in &readings {
-        tree.insert(*reading)
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Tree;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1(tree: &Self::Input) -> Answer {
+        let (gamma, epsilon) = tree.greeks();
+        p3::answer(gamma, epsilon)
+    }
+
+    fn part2(tree: &Self::Input) -> Answer {
+        let oxygen = tree.rating(Criterion::MostCommon).unwrap_or(0);
+        let co2 = tree.rating(Criterion::LeastCommon).unwrap_or(0);
+        p3::answer(oxygen, co2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+    use proptest::prelude::*;
+
+    #[test]
+    fn answers_sample() {
+        let tree = parse_data(&input::named(3, "sample").unwrap()).unwrap();
+        assert_eq!(tree.width, 5);
+        assert_eq!(Solver::part1(&tree), Answer::Int(198));
+        assert_eq!(Solver::part2(&tree), Answer::Int(230));
+    }
+
+    #[test]
+    fn removes_and_counts_prefixes() {
+        let mut tree = Tree::new(4);
+        for v in [0b0110, 0b0111, 0b0111, 0b1000] {
+            tree.insert(v);
+        }
+        assert_eq!(tree.prefix_count(0b0, 1), 3);
+        assert_eq!(tree.prefix_count(0b011, 3), 3);
+        assert_eq!(tree.prefix_count(0, 0), 4);
+        assert_eq!(tree.count_of(0b0111), 2);
+
+        assert!(tree.remove(0b0111));
+        assert!(!tree.remove(0b0101));
+        assert_eq!(tree.count_of(0b0111), 1);
+        assert!(tree.remove(0b1000));
+        assert_eq!(tree.prefix_count(0b1, 1), 0);
+        assert_eq!(tree.ones, vec![0, 2, 2, 1]);
+        assert_eq!(tree.rating(Criterion::LeastCommon), Some(0b0110));
+
+        let size = tree.nodes.len();
+        tree.insert(0b1111);
+        assert_eq!(tree.nodes.len(), size);
+        assert!(tree.remove(0b0110) && tree.remove(0b0111) && tree.remove(0b1111));
+        assert!(tree.is_empty());
+        assert_eq!(tree.rating(Criterion::MostCommon), None);
+    }
+
+    #[test]
+    fn rejects_wide_readings() {
+        assert_eq!(parse_data(&"1".repeat(65)).unwrap_err().expected, "1 to 64 binary digits");
+        let widest = parse_data(&"1".repeat(64)).unwrap();
+        assert_eq!(Solver::part2(&widest), Answer::Str((u64::MAX as u128).pow(2).to_string()));
+    }
+
+    fn lines() -> impl Strategy<Value = Vec<String>> {
+        let digit = prop::sample::select(vec!['0', '1']);
+        let line = move |width| prop::collection::vec(digit.clone(), width).prop_map(|ds| ds.into_iter().collect());
+        (1usize..=64).prop_flat_map(move |width| prop::collection::vec(line(width), 1..60))
+    }
+
+    proptest! {
+        #[test]
+        fn matches_filtering(lines in lines()) {
+            let text = lines.join("\n");
+            let readings = p3::parse_data(&text).unwrap();
+            let tree = parse_data(&text).unwrap();
+            prop_assert_eq!(Solver::part1(&tree), p3::d3p1(&readings));
+            prop_assert_eq!(Solver::part2(&tree), p3::d3p2(&readings));
+        }
     }
-    println!("{:?}", tree);
-    dbg!(tree);
 }
@@ -1,127 +1,493 @@
-use std::fmt;
+//! Day 4: Giant Squid. Boards of any size are ranked by the round they win in
+//! under a set of `Rules`; `first_winner` and `last_winner` answer the two
+//! parts from the top and bottom of the ranking. `Game` plays the same draws
+//! one at a time instead, telling what happened on each as `Event`s.
+
 use std::collections::HashMap;
-use std::cmp::min;
+use std::fmt;
 
-#[derive(Clone, Copy)]
+use animate::Animation;
+use colored::*;
+use generate::Rng;
+use parser::{self, ParseError};
+use solution::{Answer, Solution};
+
+/// A board of `width` by `height` numbers, stored a row at a time.
+#[derive(Clone, PartialEq, Eq)]
 pub struct Bingo {
-    values: [[usize; 5]; 5]
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<usize>,
+}
+
+impl Bingo {
+    pub fn new(width: usize, height: usize, values: Vec<usize>) -> Bingo {
+        assert_eq!(values.len(), width * height, "a {}x{} board has {} numbers", width, height, width * height);
+        Bingo { width, height, values }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[usize]> {
+        self.values.chunks(self.width)
+    }
 }
 
 impl fmt::Debug for Bingo {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut s = "\n".to_owned();
-        let mut i = 0;
-        for line in &self.values {
-            for number in line {
-                s += &format!("{} ", number).to_owned();
-            }
-            i+= 1;
-            if i != 5 {
-                s += "\n";
-            }
+        for row in self.rows() {
+            let numbers: Vec<String> = row.iter().map(|n| format!("{:2}", n)).collect();
+            write!(f, "\n{}", numbers.join(" "))?;
         }
-        return write!(f,"{}", s);
+        Ok(())
     }
 }
 
-pub fn get_data() -> (Vec<usize>, Vec<Bingo>) {
-    let chunks: Vec<&str> = include_str!("../inputs/day4puzzle.txt").split("\n\n").collect();
-    let numbers: Vec<usize> = chunks[0].split(",").map(|x| {x.parse::<usize>().unwrap()}).collect();
-    let mut bingos: Vec<Bingo> = vec![];
+/// Which lines of marked numbers win a board. The puzzle plays with rows and
+/// columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub rows: bool,
+    pub columns: bool,
+    /// Both diagonals, on square boards only.
+    pub diagonals: bool,
+    /// Every number on the board.
+    pub full_card: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            rows: true,
+            columns: true,
+            diagonals: false,
+            full_card: false,
+        }
+    }
+}
+
+impl Rules {
+    /// The cells of every winning line on a `width` by `height` board, as
+    /// indices into its values.
+    pub fn patterns(&self, width: usize, height: usize) -> Vec<Vec<usize>> {
+        let mut patterns = vec![];
+        if self.rows {
+            patterns.extend((0..height).map(|y| (0..width).map(|x| y * width + x).collect()));
+        }
+        if self.columns {
+            patterns.extend((0..width).map(|x| (0..height).map(|y| y * width + x).collect()));
+        }
+        if self.diagonals && width == height {
+            patterns.push((0..width).map(|i| i * width + i).collect());
+            patterns.push((0..width).map(|i| i * width + width - 1 - i).collect());
+        }
+        if self.full_card {
+            patterns.push((0..width * height).collect());
+        }
+        return patterns;
+    }
+}
+
+/// The drawn numbers, then the boards, each as wide as its first row.
+pub fn parse_data(s: &str) -> Result<(Vec<usize>, Vec<Bingo>), ParseError> {
+    let mut blocks = parser::blocks(s).into_iter();
+    let mut first = blocks.next().unwrap_or_default().into_iter();
+    let mut draws = first.next().ok_or_else(|| ParseError::at_end(s, "the drawn numbers"))?;
+    let numbers = draws.separated(",", |c| c.int::<usize>())?;
+    draws.end()?;
+    if let Some(c) = first.next() {
+        return Err(c.error("a blank line"));
+    }
 
-    for number in &chunks[1..] {
-        let mut values = [[0; 5]; 5];
-        for (i, line) in number.lines().enumerate() {
-            for (j, number) in line.split_ascii_whitespace().enumerate(){
-                values[i][j] = number.parse::<usize>().unwrap();
+    let mut bingos: Vec<Bingo> = vec![];
+    for block in blocks {
+        let mut values = vec![];
+        let mut width = None;
+        for mut c in block {
+            let start = c.clone();
+            let mut row = vec![];
+            c.skip_spaces();
+            while !c.is_empty() {
+                row.push(c.int::<usize>()?);
+                c.skip_spaces();
+            }
+            match width {
+                None if row.is_empty() => return Err(start.error("a row of numbers")),
+                None => width = Some(row.len()),
+                Some(w) if w != row.len() => return Err(start.error(&format!("a row of {} numbers", w))),
+                Some(_) => {}
             }
+            values.append(&mut row);
         }
-        bingos.push(Bingo{values})
+        let width = width.expect("blocks are never empty");
+        bingos.push(Bingo::new(width, values.len() / width, values));
     }
-    return (numbers, bingos);
+    return Ok((numbers, bingos));
+}
+
+/// A board's win: the round it happened in, counting from 0, the number that
+/// was called and the board's score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win {
+    pub board: usize,
+    pub round: usize,
+    pub number: usize,
+    pub score: usize,
+}
+
+/// Every board that wins, in the order they win, with boards that win in the
+/// same round in board order, then the boards that never win.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ranking {
+    pub winners: Vec<Win>,
+    pub losers: Vec<usize>,
 }
 
-pub fn make_number_to_round(numbers: Vec<usize>) -> HashMap<usize,usize> {
-    let mut converter: HashMap<usize,usize> = HashMap::new();
-    for (i, number) in numbers.iter().enumerate() {
-        converter.insert(*number, i);
+/// When each number is first called. Numbers never called are missing.
+fn rounds(numbers: &[usize]) -> HashMap<usize, usize> {
+    let mut rounds = HashMap::new();
+    for (round, n) in numbers.iter().enumerate() {
+        rounds.entry(*n).or_insert(round);
     }
-    return converter;
+    return rounds;
 }
 
-const INFINITE_ROUND: usize = 666;
+/// How `board` wins, if it ever does: the earliest round in which every cell
+/// of some pattern has been called.
+fn play(board: usize, bingo: &Bingo, numbers: &[usize], rounds: &HashMap<usize, usize>, rules: &Rules) -> Option<Win> {
+    let called: Vec<Option<usize>> = bingo.values.iter().map(|n| rounds.get(n).copied()).collect();
+    let round = rules
+        .patterns(bingo.width, bingo.height)
+        .iter()
+        .filter_map(|p| p.iter().map(|i| called[*i]).collect::<Option<Vec<usize>>>())
+        .filter_map(|p| p.into_iter().max())
+        .min()?;
+    let unmarked: usize = bingo
+        .values
+        .iter()
+        .zip(&called)
+        .filter(|(_, c)| !matches!(c, Some(r) if *r <= round))
+        .map(|(n, _)| n)
+        .sum();
+    let number = numbers[round];
+    trace!("board {} wins in round {} with {} left unmarked", board, round, unmarked);
+    Some(Win {
+        board,
+        round,
+        number,
+        score: unmarked * number,
+    })
+}
+
+/// Every board ranked by the round it wins in under `rules`.
+pub fn rank(numbers: &[usize], bingos: &[Bingo], rules: &Rules) -> Ranking {
+    let rounds = rounds(numbers);
+    let mut winners = vec![];
+    let mut losers = vec![];
+    for (board, bingo) in bingos.iter().enumerate() {
+        match play(board, bingo, numbers, &rounds, rules) {
+            Some(win) => winners.push(win),
+            None => losers.push(board),
+        }
+    }
+    winners.sort_by_key(|w| (w.round, w.board));
+    if !losers.is_empty() {
+        debug!("boards {:?} never win", losers);
+    }
+    return Ranking { winners, losers };
+}
+
+/// The score of the board that wins first, if any board wins.
+pub fn first_winner(numbers: &[usize], bingos: &[Bingo]) -> Option<usize> {
+    rank(numbers, bingos, &Rules::default()).winners.first().map(|w| w.score)
+}
+
+/// The score of the board that wins last, leaving out boards that never win.
+pub fn last_winner(numbers: &[usize], bingos: &[Bingo]) -> Option<usize> {
+    rank(numbers, bingos, &Rules::default()).winners.last().map(|w| w.score)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A number was called, in a round counting from 0.
+    Called { round: usize, number: usize },
+    /// The number was on `board`, at these indices into its values.
+    Marked { board: usize, cells: Vec<usize> },
+    /// `board` completed a pattern for the first time.
+    Won(Win),
+}
+
+/// The draws played one at a time. Each step calls the next number and gives
+/// back what happened, so the game can be watched as it goes. Boards keep
+/// being marked after they win but only win once.
+pub struct Game<'a> {
+    numbers: &'a [usize],
+    bingos: &'a [Bingo],
+    patterns: Vec<Vec<Vec<usize>>>,
+    marked: Vec<Vec<bool>>,
+    won: Vec<bool>,
+    round: usize,
+}
 
-pub fn make_round_board(converter: HashMap<usize,usize>, bingo: Bingo) -> Bingo {
-    let mut round_board = [[INFINITE_ROUND; 5] ; 5];
-    for x in 0..5 {
-        for y in 0..5 {
-            round_board[x][y] = converter[&bingo.values[x][y]]
+impl<'a> Game<'a> {
+    pub fn new(numbers: &'a [usize], bingos: &'a [Bingo], rules: &Rules) -> Game<'a> {
+        Game {
+            numbers,
+            bingos,
+            patterns: bingos.iter().map(|b| rules.patterns(b.width, b.height)).collect(),
+            marked: bingos.iter().map(|b| vec![false; b.values.len()]).collect(),
+            won: vec![false; bingos.len()],
+            round: 0,
         }
     }
-    return Bingo{values: round_board};
+
+    /// How many numbers have been called.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    pub fn bingos(&self) -> &[Bingo] {
+        self.bingos
+    }
+
+    /// Which cells of `board` have been called.
+    pub fn marked(&self, board: usize) -> &[bool] {
+        &self.marked[board]
+    }
+
+    pub fn has_won(&self, board: usize) -> bool {
+        self.won[board]
+    }
+
+    /// Whether every number has been called.
+    pub fn is_over(&self) -> bool {
+        self.round >= self.numbers.len()
+    }
+
+    fn score(&self, board: usize, number: usize) -> usize {
+        let unmarked: usize = self.bingos[board]
+            .values
+            .iter()
+            .zip(&self.marked[board])
+            .filter(|(_, m)| !**m)
+            .map(|(n, _)| n)
+            .sum();
+        return unmarked * number;
+    }
 }
 
-pub fn get_winning_round(round_board: Bingo) -> usize {
-    let mut winning_round = INFINITE_ROUND;
-    for x in 0..5 {
-        let mut rows: Vec<usize> = vec![];
-        let mut columns: Vec<usize> = vec![];
-        for y in 0..5 {
-            rows.push(round_board.values[x][y]);
-            columns.push(round_board.values[y][x])
+impl<'a> Iterator for Game<'a> {
+    /// The events of one draw, starting with the call.
+    type Item = Vec<Event>;
+
+    fn next(&mut self) -> Option<Vec<Event>> {
+        let number = *self.numbers.get(self.round)?;
+        let round = self.round;
+        self.round += 1;
+        let mut events = vec![Event::Called { round, number }];
+        for (board, bingo) in self.bingos.iter().enumerate() {
+            let cells: Vec<usize> = (0..bingo.values.len())
+                .filter(|i| bingo.values[*i] == number && !self.marked[board][*i])
+                .collect();
+            if cells.is_empty() {
+                continue;
+            }
+            for i in &cells {
+                self.marked[board][*i] = true;
+            }
+            events.push(Event::Marked { board, cells });
+            let marked = &self.marked[board];
+            if !self.won[board] && self.patterns[board].iter().any(|p| p.iter().all(|i| marked[*i])) {
+                self.won[board] = true;
+                let score = self.score(board, number);
+                events.push(Event::Won(Win { board, round, number, score }));
+            }
         }
-        winning_round = min(winning_round, *rows.iter().max().unwrap());
-        winning_round = min(winning_round, *columns.iter().max().unwrap());
+        return Some(events);
     }
-    return winning_round;
+}
 
+/// Every board side by side, five to a band, with the called numbers in bold
+/// and the boards that have won headed with a star.
+pub fn render(game: &Game) -> String {
+    let bingos = game.bingos();
+    let mut bands = vec![];
+    for start in (0..bingos.len()).step_by(5) {
+        let boards: Vec<usize> = (start..(start + 5).min(bingos.len())).collect();
+        let height = boards.iter().map(|b| bingos[*b].height).max().unwrap_or(0);
+        let mut lines = vec![];
+        let heading: Vec<String> = boards
+            .iter()
+            .map(|b| {
+                let name = format!("{}{}", if game.has_won(*b) { "*" } else { " " }, b);
+                format!("{:<width$}", name, width = 3 * bingos[*b].width - 1)
+            })
+            .collect();
+        lines.push(heading.join("   "));
+        for y in 0..height {
+            let row: Vec<String> = boards
+                .iter()
+                .map(|b| {
+                    let bingo = &bingos[*b];
+                    let cells: Vec<String> = (0..bingo.width)
+                        .map(|x| match bingo.values.get(y * bingo.width + x) {
+                            Some(n) if game.marked(*b)[y * bingo.width + x] => format!("{:2}", n).bold().to_string(),
+                            Some(n) => format!("{:2}", n),
+                            None => "  ".to_string(),
+                        })
+                        .collect();
+                    cells.join(" ")
+                })
+                .collect();
+            lines.push(row.join("   "));
+        }
+        bands.push(lines.join("\n"));
+    }
+    return bands.join("\n\n");
 }
 
-pub fn calculate_score(round: usize, numbers: Vec<usize>, round_board: Bingo, board: Bingo) -> usize {
-    let mut leftovers:  usize = 0; 
-    for x in 0..5 {
-        for y in 0..5 {
-            if round_board.values[x][y] > round {
-                leftovers += board.values[x][y];
-            } 
+/// Plays the draws, showing the boards after every call. Stops once every
+/// board that is going to win has.
+pub fn animate(input: &str, anim: &mut Animation) -> Result<(), ParseError> {
+    let (numbers, bingos) = parse_data(input)?;
+    let winners = rank(&numbers, &bingos, &Rules::default()).winners;
+    let last = anim.last(winners.last().map_or(0, |w| w.round + 1));
+    let mut game = Game::new(&numbers, &bingos, &Rules::default());
+    anim.frame(0, || render(&game));
+    let mut won = 0;
+    while let Some(events) = game.next() {
+        let mut story = vec![];
+        for e in &events {
+            match e {
+                Event::Called { number, .. } => story.push(format!("called {}", number)),
+                Event::Won(w) => {
+                    won += 1;
+                    story.push(format!("board {} wins with a score of {} ({} of {})", w.board, w.score, won, winners.len()));
+                }
+                Event::Marked { .. } => {}
+            }
+        }
+        anim.frame(game.round(), || format!("{}\n\n{}", story.join("\n"), render(&game)));
+        if game.round() >= last {
+            break;
         }
     }
-    dbg!(leftovers);
-    dbg!(leftovers * numbers[round]);
-    return leftovers * numbers[round]; 
-}
-
-pub fn main() {
-    let (numbers, bingos) = get_data();
-    let converter = make_number_to_round(numbers.clone());
-
-    let mut first_board = Bingo{values: [[INFINITE_ROUND; 5]; 5]};
-    let mut first_round_board = Bingo{values: [[INFINITE_ROUND; 5]; 5]};
-    let mut first_round = INFINITE_ROUND;
-    for bingo in bingos.clone() {
-        let round_board = make_round_board(converter.clone(),bingo);
-        let round = get_winning_round(round_board);
-        if round < first_round {
-            first_board = bingo; 
-            first_round_board = round_board;
-            first_round = round;
+    return Ok(());
+}
+
+/// Every number from 0 to 99 drawn once, then `size` boards, so every board
+/// wins eventually.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut draws: Vec<usize> = (0..100).collect();
+    rng.shuffle(&mut draws);
+    let mut s = draws.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",");
+    for _ in 0..size {
+        let mut numbers: Vec<usize> = (0..100).collect();
+        rng.shuffle(&mut numbers);
+        s += "\n";
+        for row in numbers[..25].chunks(5) {
+            s += "\n";
+            s += &row.iter().map(|n| format!("{:2}", n)).collect::<Vec<_>>().join(" ");
         }
     }
+    return s;
+}
+
+fn score_answer(score: Option<usize>) -> Answer {
+    match score {
+        Some(score) => score.into(),
+        None => Answer::Str("no board wins".to_string()),
+    }
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = (Vec<usize>, Vec<Bingo>);
 
-    dbg!(calculate_score(first_round, numbers.clone(), first_round_board, first_board));
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1((numbers, bingos): &Self::Input) -> Answer {
+        score_answer(first_winner(numbers, bingos))
+    }
+
+    fn part2((numbers, bingos): &Self::Input) -> Answer {
+        score_answer(last_winner(numbers, bingos))
+    }
+}
 
-    let mut last_board = Bingo{values: [[INFINITE_ROUND; 5]; 5]};
-    let mut last_round_board = Bingo{values: [[INFINITE_ROUND; 5]; 5]};
-    let mut last_round = 1;
-    for bingo in bingos {
-        let round_board = make_round_board(converter.clone(),bingo);
-        let round = get_winning_round(round_board);
-        if round > last_round {
-            last_board = bingo; 
-            last_round_board = round_board;
-            last_round = round;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+
+    #[test]
+    fn ranks_sample() {
+        let (numbers, bingos) = parse_data(&input::named(4, "sample").unwrap()).unwrap();
+        let ranking = rank(&numbers, &bingos, &Rules::default());
+        let order: Vec<(usize, usize)> = ranking.winners.iter().map(|w| (w.board, w.round)).collect();
+        assert_eq!(order, vec![(2, 11), (0, 13), (1, 14)]);
+        assert_eq!(ranking.winners[0].score, 4512);
+        assert_eq!(ranking.winners[2].score, 1924);
+        assert!(ranking.losers.is_empty());
+    }
+
+    #[test]
+    fn keeps_boards_that_never_win() {
+        let (numbers, bingos) = parse_data("1,2,3,9\n\n1 2 3\n4 5 6\n\n7 8\n9 10").unwrap();
+        assert_eq!((bingos[0].width, bingos[0].height), (3, 2));
+        let ranking = rank(&numbers, &bingos, &Rules::default());
+        assert_eq!(ranking.winners, vec![Win { board: 0, round: 2, number: 3, score: 45 }]);
+        assert_eq!(ranking.losers, vec![1]);
+        assert_eq!(last_winner(&[4], &bingos), None);
+    }
+
+    #[test]
+    fn plays_other_patterns() {
+        let (numbers, bingos) = parse_data("1,5,9,3,7,2,4,6,8\n\n1 2 3\n4 5 6\n7 8 9").unwrap();
+        let rows_only = Rules { columns: false, ..Rules::default() };
+        assert_eq!(rank(&numbers, &bingos, &rows_only).winners[0].round, 5);
+        let diagonals = Rules { diagonals: true, ..rows_only };
+        assert_eq!(rank(&numbers, &bingos, &diagonals).winners[0].round, 2);
+        let full_card = Rules { rows: false, columns: false, diagonals: false, full_card: true };
+        let win = rank(&numbers, &bingos, &full_card).winners[0];
+        assert_eq!((win.round, win.score), (8, 0));
+    }
+
+    #[test]
+    fn plays_like_it_ranks() {
+        let (numbers, bingos) = parse_data(&generate(&mut Rng::new(4), 30)).unwrap();
+        for rules in [Rules::default(), Rules { diagonals: true, full_card: true, ..Rules::default() }] {
+            let mut game = Game::new(&numbers, &bingos, &rules);
+            let wins: Vec<Win> = game
+                .by_ref()
+                .flatten()
+                .filter_map(|e| match e {
+                    Event::Won(w) => Some(w),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(wins, rank(&numbers, &bingos, &rules).winners);
+            assert!(game.is_over() && (0..bingos.len()).all(|b| game.has_won(b)));
         }
     }
 
-    dbg!(calculate_score(last_round, numbers.clone(), last_round_board, last_board));
-}
\ No newline at end of file
+    #[test]
+    fn tells_each_draw() {
+        let (numbers, bingos) = parse_data(&input::named(4, "sample").unwrap()).unwrap();
+        let mut game = Game::new(&numbers, &bingos, &Rules::default());
+        let first = game.next().unwrap();
+        assert_eq!(first[0], Event::Called { round: 0, number: 7 });
+        assert_eq!(first[1], Event::Marked { board: 0, cells: vec![14] });
+        assert_eq!(first.len(), 4);
+        let twelfth = game.nth(10).unwrap();
+        assert_eq!(twelfth.last(), Some(&Event::Won(Win { board: 2, round: 11, number: 24, score: 4512 })));
+        assert_eq!(game.marked(2).iter().filter(|m| **m).count(), 12);
+    }
+
+    #[test]
+    fn rejects_ragged_boards() {
+        let e = parse_data("1,2\n\n1 2\n3").unwrap_err();
+        assert_eq!(e, ParseError::new(4, 1, "a row of 2 numbers"));
+    }
+}
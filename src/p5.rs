@@ -1,142 +1,453 @@
-use std::num::ParseIntError;
+//! Day 5: Hydrothermal Venture. `count_overlaps` counts the points two or more
+//! vent `Line`s cover without visiting the points themselves, so coordinates
+//! can be anything up to `LIMIT` either side of 0. The `Mode` picks whether
+//! diagonal lines count.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::str::FromStr;
-use std::fmt;
 
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
-pub struct Point {
-    x: i32,
-    y: i32,
-}
+use rustc_hash::FxHashSet;
+
+use export::Image;
+use generate::Rng;
+use geometry::Point2;
+use grid::Sparse;
+use parser::{self, Cursor, ParseError};
+use solution::{Answer, Solution};
+
+pub type Point = Point2<i64>;
+
+/// How far from 0 a coordinate can be, so that sums and differences of two
+/// of them, and one past either, still fit in an `i64`.
+pub const LIMIT: i64 = 1 << 60;
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub struct Line {
-    start: Point,
-    end: Point,
+    pub start: Point,
+    pub end: Point,
 }
 
-const SIZE: usize = 1000;
+/// Which lines take part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Horizontal and vertical lines only, as in part 1.
+    Straight,
+    /// Diagonal lines as well, as in part 2.
+    All,
+}
 
-#[derive(Clone, PartialEq, Eq)]
-pub struct Grid {
-    values: Vec<Vec<usize>>
+/// The ways a line can run. Along each of them one of `x`, `y`, `y - x` or
+/// `y + x` stays the same. A line that is a single point counts as
+/// horizontal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Horizontal,
+    Vertical,
+    /// Down and to the right.
+    Diagonal,
+    /// Up and to the right.
+    AntiDiagonal,
 }
 
-impl Grid {
-    fn new() -> Grid {
-        let values = vec![vec![0; SIZE]; SIZE];
-        return Grid { values };
-    }
-    
-    fn mark(self: &mut Self, p: Point) -> &mut Grid {
-        self.values[p.x as usize][p.y as usize] += 1; 
-        return self;
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Horizontal,
+    Direction::Vertical,
+    Direction::Diagonal,
+    Direction::AntiDiagonal,
+];
+
+impl Direction {
+    /// Which row, column or diagonal `p` is on in this direction, and how far
+    /// along it.
+    fn place(self, p: Point) -> (i64, i64) {
+        match self {
+            Direction::Horizontal => (p.y, p.x),
+            Direction::Vertical => (p.x, p.y),
+            Direction::Diagonal => (p.y - p.x, p.x),
+            Direction::AntiDiagonal => (p.y + p.x, p.x),
+        }
     }
 }
 
-impl fmt::Debug for Grid {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut s = "\n".to_owned();
-        let mut i = 0;
-        for line in &self.values {
-            for number in line {
-                if *number != (0 as usize) {
-                    s += &format!("{}", number).to_owned();
-                }
-                else {
-                s += &format!(".").to_owned();
-                }
-            }
-            i += 1;
-            if i != SIZE {
-                s += "\n";
-            }
-        }
-        return write!(f, "{}", s);
+/// The grid with a `.` wherever no line goes.
+pub fn render(grid: &Sparse<usize>) -> String {
+    grid.render(|_, n| n.map_or(".".to_string(), |n| n.to_string()))
+}
+
+/// A coordinate no further than `LIMIT` from 0.
+fn parse_coordinate(c: &mut Cursor) -> Result<i64, ParseError> {
+    let start = c.clone();
+    let n = c.int::<i64>()?;
+    if !(-LIMIT..=LIMIT).contains(&n) {
+        return Err(start.error("a coordinate between -2^60 and 2^60"));
     }
+    Ok(n)
+}
+
+/// `x,y`
+fn parse_point(c: &mut Cursor) -> Result<Point, ParseError> {
+    let x = parse_coordinate(c)?;
+    c.expect(",")?;
+    let y = parse_coordinate(c)?;
+    Ok(Point::new(x, y))
 }
 
 /// taken from docs https://doc.rust-lang.org/std/str/trait.FromStr.html
-impl FromStr for Point {
-    type Err = ParseIntError;
+impl FromStr for Line {
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coords: Vec<&str> = s.trim().split(',').collect();
+        let mut c = Cursor::new(1, s);
+        let line = Line::parse(&mut c)?;
+        c.end()?;
+        Ok(line)
+    }
+}
 
-        let x = coords[0].parse::<i32>()?;
-        let y = coords[1].parse::<i32>()?;
+impl Line {
+    /// `x1,y1 -> x2,y2`, running in one of the four directions.
+    fn parse(c: &mut Cursor) -> Result<Line, ParseError> {
+        let start = parse_point(c)?;
+        c.expect(" -> ")?;
+        let end_at = c.clone();
+        let end = parse_point(c)?;
+        let line = Line { start, end };
+        if line.direction().is_none() {
+            return Err(end_at.error("an end in line with the start or at 45 degrees to it"));
+        }
+        Ok(line)
+    }
+
+    /// The way the line runs, or `None` if it is not horizontal, vertical or
+    /// at 45 degrees.
+    pub fn direction(&self) -> Option<Direction> {
+        let d = self.end - self.start;
+        if d.y == 0 {
+            Some(Direction::Horizontal)
+        } else if d.x == 0 {
+            Some(Direction::Vertical)
+        } else if d.x == d.y {
+            Some(Direction::Diagonal)
+        } else if d.x == -d.y {
+            Some(Direction::AntiDiagonal)
+        } else {
+            None
+        }
+    }
 
-        Ok(Point { x, y })
+    pub fn is_straight(&self) -> bool {
+        matches!(self.direction(), Some(Direction::Horizontal) | Some(Direction::Vertical))
+    }
+
+    /// Every point on the line, one at a time.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let diff = self.end - self.start;
+        let step = diff.signum();
+        let start = self.start;
+        (0..=diff.abs().max_component()).map(move |i| start + step.scale(i))
     }
 }
 
-impl FromStr for Line {
-    type Err = ParseIntError;
+impl Mode {
+    pub fn includes(self, line: &Line) -> bool {
+        self == Mode::All || line.is_straight()
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coords: Vec<&str> = s.trim().split("->").collect();
+pub fn parse_data(s: &str) -> Result<Vec<Line>, ParseError> {
+    parser::each_line(s, Line::parse)
+}
+
+/// A line as the stretch `lo..=hi` of the row, column or diagonal `key` in
+/// its direction.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    direction: Direction,
+    key: i64,
+    lo: i64,
+    hi: i64,
+}
 
-        let start = Point::from_str(coords[0]).unwrap();
-        let end = Point::from_str(coords[1]).unwrap();
+impl Segment {
+    fn new(line: &Line) -> Segment {
+        let direction = line.direction().expect("lines are checked when parsed");
+        let (key, a) = direction.place(line.start);
+        let (_, b) = direction.place(line.end);
+        Segment {
+            direction,
+            key,
+            lo: a.min(b),
+            hi: a.max(b),
+        }
+    }
 
-        Ok(Line { start, end })
+    fn contains(&self, p: Point) -> bool {
+        let (key, t) = self.direction.place(p);
+        key == self.key && self.lo <= t && t <= self.hi
     }
 }
 
-impl Line {
-    fn is_not_diagonal(self: &Self) -> bool {
-        self.start.x == self.end.x  || self.start.y == self.end.y
-    }
-
-    fn all_points(self: &Self) -> Vec<Point> {
-        //dbg!(self);
-        let x_diff = self.end.x - self.start.x;
-        let y_diff = self.end.y - self.start.y;
-        //dbg!(x_diff, y_diff);
-        let steps = std::cmp::max(x_diff.abs(),y_diff.abs());
-        //dbg!(steps);
-        let points: Vec<Point> = (0..steps+1).map(|i| {
-            Point{
-                x: self.start.x + i * (x_diff/steps) ,
-                y: self.start.y + i * (y_diff/steps) 
+/// Where two segments running different ways cross, if that is at a whole
+/// point on both.
+fn crossing(a: &Segment, b: &Segment) -> Option<Point> {
+    use self::Direction::*;
+    let (a, b) = if a.direction <= b.direction { (a, b) } else { (b, a) };
+    let p = match (a.direction, b.direction) {
+        (Horizontal, Vertical) => Point::new(b.key, a.key),
+        (Horizontal, Diagonal) => Point::new(a.key - b.key, a.key),
+        (Horizontal, AntiDiagonal) => Point::new(b.key - a.key, a.key),
+        (Vertical, Diagonal) => Point::new(a.key, a.key + b.key),
+        (Vertical, AntiDiagonal) => Point::new(a.key, b.key - a.key),
+        (Diagonal, AntiDiagonal) if (a.key + b.key) % 2 == 0 => Point::new((b.key - a.key) / 2, (a.key + b.key) / 2),
+        _ => return None,
+    };
+    if a.contains(p) && b.contains(p) {
+        Some(p)
+    } else {
+        None
+    }
+}
+
+/// The stretches two or more segments cover on each row, column and diagonal,
+/// in order and merged, from a sweep along each one over where segments start
+/// and stop.
+fn overlaps(segments: &[Segment]) -> HashMap<(Direction, i64), Vec<(i64, i64)>> {
+    let mut events: HashMap<(Direction, i64), Vec<(i64, i32)>> = HashMap::new();
+    for s in segments {
+        let e = events.entry((s.direction, s.key)).or_default();
+        e.push((s.lo, 1));
+        e.push((s.hi + 1, -1));
+    }
+    let mut overlaps = HashMap::new();
+    for (key, mut events) in events {
+        events.sort_unstable();
+        let mut stretches = vec![];
+        let mut depth = 0;
+        let mut start = None;
+        let mut i = 0;
+        while i < events.len() {
+            let t = events[i].0;
+            while i < events.len() && events[i].0 == t {
+                depth += events[i].1;
+                i += 1;
             }
-        }).collect();
-        //dbg!(points.clone());
-        return points;
+            match start {
+                None if depth >= 2 => start = Some(t),
+                Some(s) if depth < 2 => {
+                    stretches.push((s, t - 1));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if !stretches.is_empty() {
+            overlaps.insert(key, stretches);
+        }
+    }
+    return overlaps;
+}
+
+/// How many points two or more of the lines `mode` takes cover. Lines running
+/// the same way can only overlap along their row, column or diagonal, which
+/// a sweep along it measures. Lines running different ways meet in at most
+/// a point, found pair by pair. Those points always count, once, however many
+/// of the stretches already measured they are also on.
+/// With lines as long as they can be the count can outgrow a `u64`.
+pub fn count_overlaps(lines: &[Line], mode: Mode) -> u128 {
+    let segments: Vec<Segment> = lines.iter().filter(|l| mode.includes(l)).map(Segment::new).collect();
+    let overlaps = overlaps(&segments);
+    let mut count: i128 = overlaps.values().flatten().map(|(lo, hi)| (hi - lo + 1) as i128).sum();
+
+    let mut crossings = FxHashSet::default();
+    for (i, a) in segments.iter().enumerate() {
+        for b in &segments[i + 1..] {
+            if let Some(p) = crossing(a, b) {
+                crossings.insert(p);
+            }
+        }
+    }
+    for p in crossings {
+        let measured = DIRECTIONS
+            .iter()
+            .filter(|d| {
+                let (key, t) = d.place(p);
+                overlaps.get(&(**d, key)).is_some_and(|stretches| {
+                    let i = stretches.partition_point(|(_, hi)| *hi < t);
+                    i < stretches.len() && stretches[i].0 <= t
+                })
+            })
+            .count();
+        count += 1 - measured as i128;
+    }
+    return count as u128;
+}
+
+/// A count as an `Int`, or as text if it is too big for one.
+fn answer(count: u128) -> Answer {
+    match i64::try_from(count) {
+        Ok(i) => Answer::Int(i),
+        Err(_) => Answer::Str(count.to_string()),
+    }
+}
+
+/// How many of the lines `mode` takes cross each point, visiting every point.
+/// `None` if a line goes outside what an `i32` holds, before visiting any
+/// of its points.
+pub fn overlap_map(lines: &[Line], mode: Mode) -> Option<Sparse<usize>> {
+    let fits = |p: Point| i32::try_from(p.x).is_ok() && i32::try_from(p.y).is_ok();
+    let mut grid = Sparse::new();
+    for line in lines.iter().filter(|l| mode.includes(l)) {
+        if !fits(line.start) || !fits(line.end) {
+            return None;
+        }
+        for p in line.points() {
+            *grid.entry((p.x as i32, p.y as i32)).or_insert(0) += 1;
+        }
+    }
+    trace!("overlaps:\n{}", render(&grid));
+    return Some(grid);
+}
+
+/// The most pixels `image` draws.
+pub const MAX_PIXELS: i64 = 1 << 24;
+
+/// The overlap map for every line, in colour 1 where one line goes and 2
+/// where they overlap.
+/// The picture takes in the box around every line, which can be at most
+/// `MAX_PIXELS` big.
+pub fn image(input: &str) -> Result<Image, ParseError> {
+    let lines = parse_data(input)?;
+    let (mut min, mut max) = match lines.first() {
+        Some(l) => (l.start, l.start),
+        None => return Ok(Image::new(0, 0)),
+    };
+    for (i, line) in lines.iter().enumerate() {
+        min = min.zip(line.start, Ord::min).zip(line.end, Ord::min);
+        max = max.zip(line.start, Ord::max).zip(line.end, Ord::max);
+        let size = max - min;
+        if (size.x as i128 + 1) * (size.y as i128 + 1) > MAX_PIXELS as i128 {
+            return Err(ParseError::new(i + 1, 1, "lines that fit in a picture of 2^24 pixels"));
+        }
     }
+    let grid = overlap_map(&lines, Mode::All).expect("a picture that small fits in an i32");
+    Ok(Image::from_sparse(&grid, |n| n.map_or(0, |n| *n.min(&2))))
 }
 
-pub fn get_lines() -> Vec<Line> {
-    let data = include_str!("../inputs/day5sample.txt")
-        .lines()
-        .map(|line| Line::from_str(line).unwrap())
-        .collect::<Vec<Line>>();
-    return data;
+/// `size` lines on a 990 square, running in all four directions.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let on_square = |x: i64, y: i64| (0..=989).contains(&x) && (0..=989).contains(&y);
+    let mut lines = vec![];
+    for _ in 0..size {
+        let (x1, y1) = (rng.range(0, 989), rng.range(0, 989));
+        let len = rng.range(-400, 400);
+        let (x2, y2) = match rng.below(4) {
+            0 => (x1, (y1 + len).clamp(0, 989)),
+            1 if on_square(x1 + len, y1 + len) => (x1 + len, y1 + len),
+            2 if on_square(x1 + len, y1 - len) => (x1 + len, y1 - len),
+            _ => ((x1 + len).clamp(0, 989), y1),
+        };
+        lines.push(format!("{},{} -> {},{}", x1, y1, x2, y2));
+    }
+    return lines.join("\n");
 }
 
-pub fn compute_overlap_grid(lines: Vec<Line>) -> Grid {
-    let mut grid = Grid::new();
-    //let to_consider = lines.iter().filter(|l| {l.is_not_diagonal()});
-    let to_consider = lines;
-    for line in to_consider {
-        for p in line.all_points() {
-            grid.mark(p);
-        } 
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Vec<Line>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1(lines: &Self::Input) -> Answer {
+        answer(count_overlaps(lines, Mode::Straight))
+    }
+
+    fn part2(lines: &Self::Input) -> Answer {
+        answer(count_overlaps(lines, Mode::All))
     }
-    return grid;
 }
 
-pub fn main() {
-    let lines = get_lines();
-    let overlap_grid = compute_overlap_grid(lines);
-    //dbg!(overlap_grid.clone());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input;
+    use proptest::prelude::*;
+
+    #[test]
+    fn counts_sample() {
+        let lines = parse_data(&input::named(5, "sample").unwrap()).unwrap();
+        assert_eq!(count_overlaps(&lines, Mode::Straight), 5);
+        assert_eq!(count_overlaps(&lines, Mode::All), 12);
+    }
+
+    #[test]
+    fn counts_far_out_lines() {
+        let far = 1i64 << 40;
+        let text = format!("{0},-5 -> {0},{1}\n-{1},0 -> {1},0\n-{1},-{1} -> {1},{1}\n{0},{0} -> {0},{0}", far, far + 5);
+        let lines = parse_data(&text).unwrap();
+        assert_eq!(count_overlaps(&lines, Mode::Straight), 2);
+        assert_eq!(count_overlaps(&lines, Mode::All), 3);
+    }
+
+    #[test]
+    fn counts_lines_at_the_limit() {
+        let mut text = vec![];
+        for y in 1..5 {
+            text.push(format!("-{0},{1} -> {0},{1}\n{0},{1} -> -{0},{1}", LIMIT, y));
+        }
+        text.push(format!("-{0},-{0} -> {0},{0}\n{0},-{0} -> -{0},{0}", LIMIT));
+        let lines = parse_data(&text.join("\n")).unwrap();
+        let row = (2 * LIMIT + 1) as u128;
+        assert_eq!(count_overlaps(&lines, Mode::Straight), 4 * row);
+        assert_eq!(Solver::part2(&lines), Answer::Str((4 * row + 1).to_string()));
+
+        let e = parse_data(&format!("0,0 -> 0,{}", LIMIT + 1)).unwrap_err();
+        assert_eq!((e.column, e.expected.as_str()), (10, "a coordinate between -2^60 and 2^60"));
+        assert!(parse_data(&format!("0,0 -> 0,{}", i64::MIN)).is_err());
+    }
+
+    #[test]
+    fn refuses_to_draw_far_out_lines() {
+        assert!(image("0,0 -> 2,2").is_ok());
+        let e = image(&format!("0,0 -> 2,2\n0,0 -> {},0", 1i64 << 31)).unwrap_err();
+        assert_eq!(e.line, 2);
+        assert_eq!(image("0,0 -> 2000000000,0").unwrap_err().expected, "lines that fit in a picture of 2^24 pixels");
+        assert_eq!(image("0,0 -> 4096,0\n0,0 -> 0,4095").unwrap_err().line, 2);
+        assert_eq!(image("").unwrap(), Image::new(0, 0));
+
+        let far = parse_data(&format!("0,0 -> 0,{}", 1i64 << 31)).unwrap();
+        assert_eq!(overlap_map(&far, Mode::All), None);
+    }
+
+    #[test]
+    fn rejects_other_angles() {
+        let e = parse_data("0,0 -> 2,1").unwrap_err();
+        assert_eq!((e.line, e.column), (1, 8));
+    }
+
+    fn line() -> impl Strategy<Value = Line> {
+        let start = (-30i64..30, -30i64..30);
+        (start, 0usize..4, -20i64..20).prop_map(|((x, y), d, len)| {
+            let end = match d {
+                0 => (x + len, y),
+                1 => (x, y + len),
+                2 => (x + len, y + len),
+                _ => (x + len, y - len),
+            };
+            Line { start: Point::new(x, y), end: Point::new(end.0, end.1) }
+        })
+    }
 
-    let mut score = 0;
-    for x in 0..SIZE{
-        for y in 0..SIZE {
-            if overlap_grid.values[x][y] > 1 {
-                score += 1;
+    proptest! {
+        #[test]
+        fn matches_visiting_every_point(lines in prop::collection::vec(line(), 0..40)) {
+            for mode in [Mode::Straight, Mode::All] {
+                let visited = overlap_map(&lines, mode).unwrap().values().filter(|n| **n > 1).count();
+                prop_assert_eq!(count_overlaps(&lines, mode), visited as u128);
             }
         }
     }
-    dbg!(score);
 }
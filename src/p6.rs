@@ -1,35 +1,93 @@
-const SAMPLE: &str = "3,4,3,1,2";
-const PUZZLE: &str = "5,1,5,3,2,2,3,1,1,4,2,4,1,2,1,4,1,1,5,3,5,1,5,3,1,2,4,4,1,1,3,1,1,3,1,1,5,1,5,4,5,4,5,1,3,2,4,3,5,3,5,4,3,1,4,3,1,1,1,4,5,1,1,1,2,1,2,1,1,4,1,4,1,1,3,3,2,2,4,2,1,1,5,3,1,3,1,1,4,3,3,3,1,5,2,3,1,3,1,5,2,2,1,2,1,1,1,3,4,1,1,1,5,4,1,1,1,4,4,2,1,5,4,3,1,2,5,1,1,1,1,2,1,5,5,1,1,1,1,3,1,4,1,3,1,5,1,1,1,5,5,1,4,5,4,5,4,3,3,1,3,1,1,5,5,5,5,1,2,5,4,1,1,1,2,2,1,3,1,1,2,4,2,2,2,1,1,2,2,1,5,2,1,1,2,1,3,1,3,2,2,4,3,1,2,4,5,2,1,4,5,4,2,1,1,1,5,4,1,1,4,1,4,3,1,2,5,2,4,1,1,5,1,5,4,1,1,4,1,1,5,5,1,5,4,2,5,2,5,4,1,1,4,1,2,4,1,2,2,2,1,1,1,5,5,1,2,5,1,3,4,1,1,1,1,5,3,4,1,1,2,1,1,3,5,5,2,3,5,1,1,1,5,4,3,4,2,2,1,3";
+//! Day 6: Lanternfish. The school is a `Fishtank` of counts by timer, so
+//! `simulate` runs in time linear in the days.
+
+use animate::Animation;
+use generate::Rng;
+use parser::{Cursor, ParseError};
+use solution::{Answer, Solution};
 
 const DAYS: usize = 9;
 
-type Fishtank = [usize; DAYS];
+pub type Fishtank = [usize; DAYS];
 
-pub fn parse_data(data: &str) -> Fishtank {
+pub fn parse_data(data: &str) -> Result<Fishtank, ParseError> {
     let mut counter = [0; DAYS];
-    let counts = data.split(",").map(|x| {x.parse::<usize>().unwrap()});
+    let mut c = Cursor::new(1, data.trim());
+    let counts = c.separated(",", |c| {
+        let start = c.clone();
+        match c.int::<usize>()? {
+            n if n < DAYS => Ok(n),
+            _ => Err(start.error("a timer from 0 to 8")),
+        }
+    })?;
+    c.end()?;
     for count in counts {
         counter[count] += 1;
     }
-    return counter;
+    return Ok(counter);
 }
 
 pub fn step(fish: Fishtank) -> Fishtank {
     let mut next = [0; DAYS];
-    for i in 1..DAYS {
-        next[i-1] = fish[i]
-    }
+    next[..(DAYS - 1)].copy_from_slice(&fish[1..DAYS]);
     next[6] += fish[0];
     next[8] += fish[0];
     return next;
 }
 
-pub fn main () {
-    let mut data = parse_data(PUZZLE);
-    for _ in 0..256{ 
-        println!("{:?}", data);
+/// How many lanternfish there are after `days` days.
+pub fn simulate(mut data: Fishtank, days: usize) -> usize {
+    for _ in 0..days { 
+        trace!("{:?}", data);
         data = step(data);
     }
-    println!("{:?}", data);
-    println!("{}", data.iter().sum::<usize>());
-}
\ No newline at end of file
+    return data.iter().sum::<usize>();
+}
+
+/// One bar per timer, scaled so the fullest timer is 60 wide.
+pub fn render(fish: &Fishtank) -> String {
+    let most = *fish.iter().max().unwrap_or(&0);
+    let mut lines = vec![];
+    for (timer, count) in fish.iter().enumerate() {
+        let width = (count * 60).checked_div(most).unwrap_or(0);
+        lines.push(format!("{} {:<60} {}", timer, "#".repeat(width), count));
+    }
+    lines.push(format!("  {:<60} {}", "", fish.iter().sum::<usize>()));
+    return lines.join("\n");
+}
+
+/// Steps the school of fish for the 256 days of part 2.
+pub fn animate(input: &str, anim: &mut Animation) -> Result<(), ParseError> {
+    let mut fish = parse_data(input)?;
+    for i in 0..=anim.last(256) {
+        if i > 0 {
+            fish = step(fish);
+        }
+        anim.frame(i, || render(&fish));
+    }
+    Ok(())
+}
+
+/// `size` fish, with timers from 1 to 5 like the real school.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let timers: Vec<String> = (0..size).map(|_| rng.range(1, 5).to_string()).collect();
+    return timers.join(",");
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Fishtank;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_data(input)
+    }
+
+    fn part1(fish: &Self::Input) -> Answer {
+        simulate(*fish, 80).into()
+    }
+
+    fn part2(fish: &Self::Input) -> Answer {
+        simulate(*fish, 256).into()
+    }
+}
@@ -1,24 +1,41 @@
-use std::collections::HashMap;
+//! Day 8: Seven Segment Search. `count_uniques` counts the easy digits and
+//! `decode_sum` works out the wiring of every display.
+
 use std::collections::HashSet;
+use generate::Rng;
+use parser::{self, Cursor, ParseError};
+use solution::{Answer, Solution};
 
 pub fn hashchar(s: &str) -> HashSet<char> {
     return s.to_string().chars().collect::<HashSet<char>>();
 }
 
-const simple: &str =
-    "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
-pub fn get_data() -> Vec<(Vec<HashSet<char>>, Vec<HashSet<char>>)> {
-    let data = include_str!("../inputs/day8puzzle.txt")
-        .lines()
-        .map(|line| {
-            let (patterns, output) = line.split_once(" | ").unwrap();
-            let mut ps: Vec<HashSet<char>> = patterns.split(" ").map(hashchar).collect();
-            ps.sort_by(|a, b| a.len().cmp(&b.len()));
-            let os = output.split(" ").map(hashchar).collect();
-            return (ps, os);
-        })
-        .collect::<Vec<(Vec<HashSet<char>>, Vec<HashSet<char>>)>>();
-    return data;
+pub type Entry = (Vec<HashSet<char>>, Vec<HashSet<char>>);
+
+/// `n` space separated patterns of the segments `a` to `g`.
+fn patterns(c: &mut Cursor, n: usize) -> Result<Vec<HashSet<char>>, ParseError> {
+    let mut ps = vec![];
+    for i in 0..n {
+        if i > 0 {
+            c.expect(" ")?;
+        }
+        let start = c.clone();
+        match c.take_while(|x| ('a'..='g').contains(&x)) {
+            "" => return Err(start.error("segments `a` to `g`")),
+            p => ps.push(hashchar(p)),
+        }
+    }
+    return Ok(ps);
+}
+
+pub fn get_data(s: &str) -> Result<Vec<Entry>, ParseError> {
+    parser::each_line(s, |c| {
+        let mut ps = patterns(c, 10)?;
+        ps.sort_by_key(|a| a.len());
+        c.expect(" | ")?;
+        let os = patterns(c, 4)?;
+        return Ok((ps, os));
+    })
 }
 // # count
 // 0 5
@@ -41,12 +58,11 @@ pub fn get_data() -> Vec<(Vec<HashSet<char>>, Vec<HashSet<char>>)> {
 
 //8:
 
-pub fn main() {
-    let data = get_data();
-    //dbg!(data.clone());
-
+/// How many output digits are a 1, 4, 7 or 8, the digits with a unique number
+/// of segments.
+pub fn count_uniques(data: &[Entry]) -> usize {
     let mut num_uniques = 0;
-    for (_, output) in data.clone() {
+    for (_, output) in data {
         for signal in output {
             if [2, 3, 4, 7].contains(&signal.len()) {
                 num_uniques += 1;
@@ -54,6 +70,11 @@ pub fn main() {
         }
     }
 
+    return num_uniques;
+}
+
+/// The sum of every display's output value, after working out its wiring.
+pub fn decode_sum(data: &[Entry]) -> i32 {
     // 2: 1
     // 3: 7
     // 4: 4
@@ -61,7 +82,7 @@ pub fn main() {
     // 6: 0, 6, 9
     // 7: 8
     let mut sum = 0;
-    for (ps, output) in data.clone() {
+    for (ps, output) in data {
         let one = &ps[0];
         let seven = &ps[1];
         let four = &ps[2];
@@ -123,53 +144,88 @@ pub fn main() {
             .filter(|x| !x.is_superset(&one.clone()))
             .collect::<Vec<&&HashSet<char>>>()[0];
 
-        dbg!(zero);
-        dbg!(one);
-        dbg!(two);
-        dbg!(three);
-        dbg!(four);
-        dbg!(five);
-        dbg!(six);
-        dbg!(seven);
-        dbg!(eight);
-        dbg!(nine);
+        trace!(
+            "digits: {:?}",
+            [zero, one, two, three, four, five, six, seven, eight, nine]
+        );
         let mut s = "".to_owned();
         for o in output {
-            if o == *zero {
+            if o == zero {
                 s += "0"
             }
-            else if o == *one {
+            else if o == one {
                 s += "1"
             }
-            else if o == **two {
+            else if o == *two {
                 s += "2"
             }
-            else if o == **three {
+            else if o == *three {
                 s += "3"
             }
-            else if o == *four {
+            else if o == four {
                 s += "4"
             }
-            else if o == *five {
+            else if o == five {
                 s += "5"
             }
-            else if o == **six {
+            else if o == *six {
                 s += "6"
             }
-            else if o == *seven {
+            else if o == seven {
                 s += "7"
             }
-            else if o == *eight {
+            else if o == eight {
                 s += "8"
             }
-            else if o == **nine {
+            else if o == *nine {
                 s += "9"
             }
         }
-        dbg!(s.clone());
+        debug!("output {}", s);
         sum += s.parse::<i32>().unwrap();
     }
-    dbg!(sum);
+    return sum;
+}
+
+/// The segments each digit lights, before the wires get mixed up.
+const SEGMENTS: [&str; 10] = ["abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg"];
+
+/// `size` displays, each with its own wiring and four digits to read.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut lines = vec![];
+    for _ in 0..size {
+        let mut wires: Vec<char> = "abcdefg".chars().collect();
+        rng.shuffle(&mut wires);
+        let wired = |digit: usize, rng: &mut Rng| {
+            let mut pattern: Vec<char> = SEGMENTS[digit].bytes().map(|b| wires[(b - b'a') as usize]).collect();
+            rng.shuffle(&mut pattern);
+            pattern.into_iter().collect::<String>()
+        };
+        let mut digits: Vec<usize> = (0..10).collect();
+        rng.shuffle(&mut digits);
+        let patterns: Vec<String> = digits.iter().map(|d| wired(*d, rng)).collect();
+        let output: Vec<String> = (0..4).map(|_| wired(rng.index(10), rng)).collect();
+        lines.push(format!("{} | {}", patterns.join(" "), output.join(" ")));
+    }
+    return lines.join("\n");
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Vec<Entry>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_data(input)
+    }
+
+    fn part1(data: &Self::Input) -> Answer {
+        count_uniques(data).into()
+    }
+
+    fn part2(data: &Self::Input) -> Answer {
+        decode_sum(data).into()
+    }
 }
 
 // aaaa
@@ -1,99 +1,150 @@
+//! Day 9: Smoke Basin. `risk_level` sums the low points of the `Heatmap` and
+//! `largest_basins` multiplies the sizes of the three biggest basins.
+
 use std::collections::HashSet;
-type Heatmap = Vec<Vec<i32>>;
 use colored::*;
+use export::Image;
+use generate::Rng;
+use grid::{self, Dense, Pos};
+use parser::ParseError;
+
+pub type Heatmap = Dense<i32>;
+use solution::{Answer, Solution};
 
-pub fn get_data() -> Heatmap {
-    let heat = include_str!("../inputs/day9puzzle.txt")
-        .lines()
-        .map(|line| {
-            return line
-                .chars()
-                .map(|x| x.to_string().parse::<i32>().unwrap())
-                .collect::<Vec<i32>>();
-        })
-        .collect::<Heatmap>();
-    return heat;
+pub fn get_data(s: &str) -> Result<Heatmap, ParseError> {
+    grid::parse_digits(s)
 }
 
-pub fn get_mins() -> Vec<(usize, usize)> {
-    let data = get_data();
-    dbg!(data.clone());
-    let max_x = data.len() - 1;
-    let max_y = data[0].len() - 1;
-    let mut mins: Vec<(usize, usize)> = vec![];
-    for (x, line) in data.iter().enumerate() {
-        for (y, value) in line.iter().enumerate() {
-            let mut local_min = true;
-            if x > 0 && data[x - 1][y] <= *value {
-                local_min = false;
-            }
-            if x < max_x && data[x + 1][y] <= *value {
-                local_min = false;
-            }
-            if y > 0 && data[x][y - 1] <= *value {
-                local_min = false;
-            }
-            if y < max_y && data[x][y + 1] <= *value {
-                local_min = false;
-            }
-            if local_min {
-                //dbg!(x,y,value);
-                //dbg!(local_min);
-                mins.push((x, y));
-                print!("{}", value.to_string().red());
-            } else {
-                print!("{}", value);
-            }
+/// The heatmap with the low points picked out in red.
+fn render_mins(data: &Heatmap, mins: &[Pos]) -> String {
+    let map = data.render(|p, value| {
+        if mins.contains(&p) {
+            value.to_string().red().to_string()
+        } else {
+            value.to_string()
         }
-        println!("");
-    }
+    });
+    return format!("\n{}", map);
+}
+
+pub fn get_mins(data: &Heatmap) -> Vec<Pos> {
+    let mins: Vec<Pos> = data
+        .iter()
+        .filter(|&(p, value)| data.neighbours4(p).all(|n| data[n] > *value))
+        .map(|(p, _)| p)
+        .collect();
+    trace!("low points:{}", render_mins(data, &mins));
     return mins;
-    //dbg!(mins.clone());
-    //let risk = mins.iter().fold(0, |acc, x| acc + x + 1);
-    //dbg!(risk);
 }
 
-pub fn calculate_size(loc: (usize, usize), data: Heatmap) -> usize {
-    let mut basin: HashSet<(usize, usize)> = HashSet::new();
-    let mut neighbors: HashSet<(usize, usize)> = HashSet::new();
-
-    let max_x = data.len() - 1;
-    let max_y = data[0].len() - 1;
-
-    neighbors.insert(loc);
-    while neighbors.len() != basin.len() {
-        dbg!(neighbors.clone());
-        dbg!(basin.clone());
-        basin = basin.union(&mut neighbors).map(|x| *x).collect();
-        for (x, y) in basin.clone() {
-            if x > 0 && data[x - 1][y] != 9 {
-                neighbors.insert((x-1,y));
-            }
-            if x < max_x && data[x + 1][y] !=9 {
-                neighbors.insert((x+1,y));
-            }
-            if y > 0 && data[x][y - 1] != 9 {
-                neighbors.insert((x,y-1));
-            }
-            if y < max_y && data[x][y + 1] != 9 {
-                neighbors.insert((x,y+1));
+pub fn calculate_size(loc: Pos, data: &Heatmap) -> usize {
+    let mut basin: HashSet<Pos> = HashSet::new();
+    let mut edge: Vec<Pos> = vec![loc];
+    basin.insert(loc);
+    while let Some(p) = edge.pop() {
+        for n in data.neighbours4(p) {
+            if data[n] != 9 && basin.insert(n) {
+                edge.push(n);
             }
         }
     }
+    trace!("basin from {:?}: {:?}", loc, basin);
     return basin.len();
 }
 
-pub fn main() {
-    let data = get_data();
-    let mins = get_mins();
-    let max_x = data.len() - 1;
-    let max_y = data[0].len() - 1;
+/// The sum of the risk levels of every low point.
+pub fn risk_level(data: &Heatmap) -> i32 {
+    return get_mins(data).iter().map(|&p| data[p] + 1).sum();
+}
+
+/// The sizes of the three largest basins multiplied together.
+pub fn largest_basins(data: &Heatmap) -> usize {
+    let mins = get_mins(data);
     let mut basin_sizes: Vec<usize> = vec![];
     for min in mins {
-        let r = calculate_size(min, data.clone());
+        let r = calculate_size(min, data);
         basin_sizes.push(r);
-        dbg!(r);
+        debug!("basin at {:?} has size {}", min, r);
     }
     basin_sizes.sort();
-    dbg!(basin_sizes);
+    debug!("basin sizes {:?}", basin_sizes);
+    return basin_sizes.iter().rev().take(3).product();
+}
+
+/// The basin number of every spot, counting from 1 in the order of the low
+/// points, with 0 on the ridges of 9s.
+pub fn basins(data: &Heatmap) -> Dense<usize> {
+    let mut labels = data.map(|_| 0);
+    for (i, low) in get_mins(data).into_iter().enumerate() {
+        let mut edge = vec![low];
+        labels[low] = i + 1;
+        while let Some(p) = edge.pop() {
+            for n in data.neighbours4(p) {
+                if data[n] != 9 && labels[n] == 0 {
+                    labels[n] = i + 1;
+                    edge.push(n);
+                }
+            }
+        }
+    }
+    return labels;
+}
+
+/// The ridges in colour 1 and each basin in a colour of its own from 2 on.
+pub fn image(input: &str) -> Result<Image, ParseError> {
+    let data = get_data(input)?;
+    let labels = basins(&data);
+    Ok(Image::from_dense(&labels, |b| if *b == 0 { 1 } else { b + 1 }))
+}
+
+/// A `size` square heightmap of basins walled in by 9s, like the real ones.
+/// Each basin has a random low point and rises with the distance from it, and
+/// wherever two basins meet is a 9.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut lows: Vec<Pos> = (0..(size * size / 50).max(1))
+        .map(|_| (rng.index(size) as i32, rng.index(size) as i32))
+        .collect();
+    lows.sort_unstable();
+    lows.dedup();
+    let distance = |p: Pos, q: Pos| (p.0 - q.0).abs() + (p.1 - q.1).abs();
+    let mut basin: Dense<Option<usize>> = Dense::new(size, size, None);
+    for p in basin.positions().collect::<Vec<Pos>>() {
+        let mut nearest = (i32::MAX, None);
+        for (i, low) in lows.iter().enumerate() {
+            let d = distance(p, *low);
+            if d < nearest.0 {
+                nearest = (d, Some(i));
+            } else if d == nearest.0 {
+                nearest.1 = None;
+            }
+        }
+        basin[p] = nearest.1;
+    }
+    let mut heights: Heatmap = Dense::new(size, size, 9);
+    for p in basin.positions() {
+        if let Some(b) = basin[p] {
+            if basin.neighbours4(p).all(|n| basin[n] == Some(b)) {
+                heights[p] = distance(p, lows[b]).min(8);
+            }
+        }
+    }
+    return grid::render_digits(&heights);
+}
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Input = Heatmap;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        get_data(input)
+    }
+
+    fn part1(data: &Self::Input) -> Answer {
+        risk_level(data).into()
+    }
+
+    fn part2(data: &Self::Input) -> Answer {
+        largest_basins(data).into()
+    }
 }
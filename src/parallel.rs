@@ -0,0 +1,211 @@
+//! Runs many solvers at once on a thread pool. Each one is timed and has its
+//! panics caught, so one broken day shows up in the summary instead of taking
+//! the rest down with it.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+
+use bench::{fmt_duration, json_string};
+use registry::Day;
+use solution::Answer;
+
+/// A solver and the input to run it on.
+pub struct Job {
+    pub day: &'static Day,
+    pub input: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Answered,
+    /// The input did not parse, with the error pointing into it.
+    Unparsed(String),
+    /// The solver panicked, with the panic's message.
+    Panicked(String),
+}
+
+pub struct Report {
+    pub solver: String,
+    pub day: u32,
+    pub input: String,
+    /// The answers given before anything went wrong, as (part, answer).
+    pub answers: Vec<(usize, Answer)>,
+    pub elapsed: Duration,
+    pub outcome: Outcome,
+}
+
+impl Report {
+    pub fn answer(&self, part: usize) -> Option<&Answer> {
+        self.answers.iter().find(|(p, _)| *p == part).map(|(_, a)| a)
+    }
+}
+
+/// What a panic said, from its payload.
+pub fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        return s.to_string();
+    }
+    if let Some(s) = payload.downcast_ref::<String>() {
+        return s.clone();
+    }
+    return "panicked with a non-string payload".to_string();
+}
+
+/// Parses the job's input and answers `part`, or both parts without one.
+pub fn run_job(job: &Job, part: Option<usize>) -> Report {
+    let mut answers = vec![];
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let parsed = job.day.prepare(&job.text).map_err(|e| e.render(&job.text))?;
+        if part != Some(2) {
+            answers.push((1, parsed.part1()));
+        }
+        if part != Some(1) {
+            answers.push((2, parsed.part2()));
+        }
+        Ok(())
+    }));
+    let elapsed = start.elapsed();
+    let outcome = match result {
+        Ok(Ok(())) => Outcome::Answered,
+        Ok(Err(e)) => Outcome::Unparsed(e),
+        Err(payload) => Outcome::Panicked(panic_message(payload)),
+    };
+    Report {
+        solver: job.day.name(),
+        day: job.day.day,
+        input: job.input.clone(),
+        answers,
+        elapsed,
+        outcome,
+    }
+}
+
+/// Runs every job on `threads` threads, or one per core without a count, and
+/// gives back their reports ordered by day. A panicking job still prints its
+/// message through the usual panic hook, and its report keeps the message from
+/// the panic's payload.
+pub fn run_all(jobs: &[Job], part: Option<usize>, threads: Option<usize>) -> Result<Vec<Report>, String> {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads.unwrap_or(0))
+        .build()
+        .map_err(|e| format!("could not start the thread pool: {}", e))?;
+    let mut reports: Vec<Report> = pool.install(|| jobs.par_iter().map(|job| run_job(job, part)).collect());
+    reports.sort_by_key(|r| r.day);
+    return Ok(reports);
+}
+
+/// How an answer fits in one table cell.
+fn cell(answer: Option<&Answer>) -> String {
+    match answer {
+        None => "-".to_string(),
+        Some(Answer::Multiline(s)) => format!("({} line picture)", s.lines().count()),
+        Some(a) => a.text(),
+    }
+}
+
+/// Prints a row per report, with what went wrong under the table, and how the
+/// time summed over every solver compares to `wall`.
+pub fn print_table(reports: &[Report], wall: Duration) {
+    println!(
+        "{:<16} {:<10} {:<18} {:<18} {:>10}  status",
+        "solver", "input", "part 1", "part 2", "time"
+    );
+    for r in reports {
+        let status = match r.outcome {
+            Outcome::Answered => "ok",
+            Outcome::Unparsed(_) => "bad input",
+            Outcome::Panicked(_) => "panicked",
+        };
+        println!(
+            "{:<16} {:<10} {:<18} {:<18} {:>10}  {}",
+            r.solver,
+            r.input,
+            cell(r.answer(1)),
+            cell(r.answer(2)),
+            fmt_duration(r.elapsed),
+            status
+        );
+    }
+    for r in reports {
+        match &r.outcome {
+            Outcome::Answered => {}
+            Outcome::Unparsed(e) => println!("\n{} could not parse {}:\n{}", r.solver, r.input, e),
+            Outcome::Panicked(e) => println!("\n{} panicked on {}: {}", r.solver, r.input, e),
+        }
+    }
+    let summed: Duration = reports.iter().map(|r| r.elapsed).sum();
+    println!(
+        "\nran {} solvers in {} ({} summed)",
+        reports.len(),
+        fmt_duration(wall),
+        fmt_duration(summed)
+    );
+}
+
+fn json_answer(answer: Option<&Answer>) -> String {
+    match answer {
+        None => "null".to_string(),
+        Some(Answer::Int(i)) => i.to_string(),
+        Some(a) => json_string(&a.text()),
+    }
+}
+
+pub fn to_json(reports: &[Report]) -> String {
+    let rows: Vec<String> = reports
+        .iter()
+        .map(|r| {
+            let (status, error) = match &r.outcome {
+                Outcome::Answered => ("ok", "null".to_string()),
+                Outcome::Unparsed(e) => ("bad input", json_string(e)),
+                Outcome::Panicked(e) => ("panicked", json_string(e)),
+            };
+            format!(
+                "  {{\"solver\": {}, \"day\": {}, \"input\": {}, \"part1\": {}, \"part2\": {}, \"elapsed_ns\": {}, \"status\": {}, \"error\": {}}}",
+                json_string(&r.solver),
+                r.day,
+                json_string(&r.input),
+                json_answer(r.answer(1)),
+                json_answer(r.answer(2)),
+                r.elapsed.as_nanos(),
+                json_string(status),
+                error
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", rows.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parser::ParseError;
+    use solution::Parsed;
+
+    fn explode(input: &str) -> Result<Box<dyn Parsed>, ParseError> {
+        panic!("no solving {}", input);
+    }
+
+    fn refuse(_: &str) -> Result<Box<dyn Parsed>, ParseError> {
+        Err(ParseError::new(1, 1, "something else"))
+    }
+
+    static EXPLODES: Day = Day { day: 2, variant: None, inputs: &[], parse: explode };
+    static REFUSES: Day = Day { day: 3, variant: None, inputs: &[], parse: refuse };
+
+    #[test]
+    fn keeps_panics_to_their_job() {
+        let jobs = vec![
+            Job { day: &EXPLODES, input: "x".to_string(), text: "this".to_string() },
+            Job { day: &REFUSES, input: "x".to_string(), text: "that".to_string() },
+        ];
+        let reports = run_all(&jobs, None, Some(2)).unwrap();
+        assert_eq!(reports[0].outcome, Outcome::Panicked("no solving this".to_string()));
+        assert!(matches!(reports[1].outcome, Outcome::Unparsed(_)));
+        assert!(reports.iter().all(|r| r.answers.is_empty()));
+    }
+}
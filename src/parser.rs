@@ -0,0 +1,290 @@
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Why an input could not be parsed: what was expected and where. Lines and
+/// columns count from 1, columns in characters. The day is filled in by the
+/// registry, since the parsers themselves don't care which day they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: Option<u32>,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, expected: &str) -> ParseError {
+        ParseError {
+            day: None,
+            line,
+            column,
+            expected: expected.to_string(),
+        }
+    }
+
+    /// An error for input that stopped before something it needed, pointing
+    /// just past the end of the last line.
+    pub fn at_end(input: &str, expected: &str) -> ParseError {
+        let last = input.lines().last().unwrap_or("");
+        let line = input.lines().count().max(1);
+        ParseError::new(line, last.chars().count() + 1, expected)
+    }
+
+    pub fn in_day(self, day: u32) -> ParseError {
+        ParseError { day: Some(day), ..self }
+    }
+
+    /// The error followed by the offending line of `input` with a caret under
+    /// the column it went wrong at.
+    pub fn render(&self, input: &str) -> String {
+        let text = input.lines().nth(self.line - 1).unwrap_or("");
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        format!(
+            "error: {}\n{} |\n{} | {}\n{} | {}^ expected {}",
+            self,
+            gutter,
+            number,
+            text,
+            gutter,
+            " ".repeat(self.column.saturating_sub(1)),
+            self.expected
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {}, ", day)?;
+        }
+        write!(f, "line {}, column {}: expected {}", self.line, self.column, self.expected)
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Walks along one line of input, handing out the pieces parsers ask for and
+/// a `ParseError` pointing at the spot whenever the next piece isn't there.
+#[derive(Debug, Clone)]
+pub struct Cursor<'a> {
+    line: usize,
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(line: usize, text: &'a str) -> Cursor<'a> {
+        Cursor { line, text, pos: 0 }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.text[..self.pos].chars().count() + 1
+    }
+
+    /// Everything not consumed yet.
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.text.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    pub fn error(&self, expected: &str) -> ParseError {
+        ParseError::new(self.line, self.column(), expected)
+    }
+
+    pub fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    pub fn skip_spaces(&mut self) {
+        self.take_while(|c| c == ' ');
+    }
+
+    /// Consumes `token` if it comes next.
+    pub fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            return true;
+        }
+        return false;
+    }
+
+    pub fn expect(&mut self, token: &str) -> Result<(), ParseError> {
+        if self.eat(token) {
+            return Ok(());
+        }
+        Err(self.error(&format!("`{}`", token)))
+    }
+
+    /// A run of letters and digits.
+    pub fn word(&mut self) -> Result<&'a str, ParseError> {
+        let start = self.clone();
+        match self.take_while(|c| c.is_ascii_alphanumeric()) {
+            "" => Err(start.error("a word")),
+            w => Ok(w),
+        }
+    }
+
+    /// An integer, with a sign if `T` can hold one.
+    pub fn int<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.clone();
+        let sign = if self.eat("-") { "-" } else { "" };
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            *self = start;
+            return Err(self.error("a number"));
+        }
+        match format!("{}{}", sign, digits).parse::<T>() {
+            Ok(n) => Ok(n),
+            Err(_) => {
+                *self = start;
+                Err(self.error("a number that fits"))
+            }
+        }
+    }
+
+    pub fn digit(&mut self) -> Result<u32, ParseError> {
+        match self.peek().and_then(|c| c.to_digit(10)) {
+            Some(d) => {
+                self.pos += 1;
+                Ok(d)
+            }
+            None => Err(self.error("a digit")),
+        }
+    }
+
+    /// One character `f` accepts, called `expected` in the error otherwise.
+    pub fn char_where<F: Fn(char) -> bool>(&mut self, f: F, expected: &str) -> Result<char, ParseError> {
+        match self.peek() {
+            Some(c) if f(c) => {
+                self.pos += c.len_utf8();
+                Ok(c)
+            }
+            _ => Err(self.error(expected)),
+        }
+    }
+
+    /// One character out of `allowed`.
+    pub fn one_of(&mut self, allowed: &str) -> Result<char, ParseError> {
+        self.char_where(|c| allowed.contains(c), &format!("one of `{}`", allowed))
+    }
+
+    /// Items parsed by `f`, each followed by `sep` except the last.
+    pub fn separated<T, F>(&mut self, sep: &str, mut f: F) -> Result<Vec<T>, ParseError>
+    where
+        F: FnMut(&mut Cursor<'a>) -> Result<T, ParseError>,
+    {
+        let mut items = vec![f(self)?];
+        while self.eat(sep) {
+            items.push(f(self)?);
+        }
+        return Ok(items);
+    }
+
+    pub fn end(&self) -> Result<(), ParseError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        Err(self.error("end of line"))
+    }
+}
+
+/// A cursor for every line of `s`, numbered from 1.
+pub fn lines(s: &str) -> impl Iterator<Item = Cursor<'_>> {
+    s.lines().enumerate().map(|(i, l)| Cursor::new(i + 1, l))
+}
+
+/// The lines of `s` grouped into blocks wherever there is a blank line.
+pub fn blocks(s: &str) -> Vec<Vec<Cursor<'_>>> {
+    let mut blocks = vec![vec![]];
+    for c in lines(s.trim_end()) {
+        if !c.rest().trim().is_empty() {
+            blocks.last_mut().unwrap().push(c);
+        } else if !blocks.last().unwrap().is_empty() {
+            blocks.push(vec![]);
+        }
+    }
+    return blocks;
+}
+
+/// A line made of nothing but digits.
+pub fn digits(c: &mut Cursor) -> Result<Vec<i32>, ParseError> {
+    let mut ds = vec![];
+    while !c.is_empty() {
+        ds.push(c.digit()? as i32);
+    }
+    return Ok(ds);
+}
+
+/// Parses each line with `f`, which has to use the whole line. Trailing blank
+/// lines are ignored.
+pub fn each_line<T, F>(s: &str, mut f: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&mut Cursor) -> Result<T, ParseError>,
+{
+    lines(s.trim_end())
+        .map(|mut c| {
+            let t = f(&mut c)?;
+            c.end()?;
+            Ok(t)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puts_the_caret_under_the_column() {
+        let input = "1,2\n3;4";
+        let e = each_line(input, |c| c.separated(",", |c| c.int::<i32>())).unwrap_err();
+        assert_eq!(e, ParseError::new(2, 2, "end of line"));
+        assert_eq!(e.render(input), "error: line 2, column 2: expected end of line\n  |\n2 | 3;4\n  |  ^ expected end of line");
+
+        let first = ParseError::new(1, 1, "a number").in_day(7).render("x");
+        assert_eq!(first.lines().last(), Some("  | ^ expected a number"));
+        assert!(first.starts_with("error: day 7, line 1, column 1"));
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        let mut c = Cursor::new(1, "é→x");
+        c.char_where(|_| true, "anything").unwrap();
+        c.one_of("→").unwrap();
+        let e = c.expect("y").unwrap_err();
+        assert_eq!(e.column, 3);
+        assert_eq!(e.render("é→x").lines().last(), Some("  |   ^ expected `y`"));
+    }
+
+    #[test]
+    fn points_past_the_end() {
+        let e = ParseError::at_end("ab\ncde", "more");
+        assert_eq!((e.line, e.column), (2, 4));
+        assert_eq!(e.render("ab\ncde").lines().nth(2), Some("2 | cde"));
+        assert_eq!(e.render("ab\ncde").lines().last(), Some("  |    ^ expected more"));
+        assert_eq!(ParseError::at_end("", "a reading"), ParseError::new(1, 1, "a reading"));
+    }
+
+    #[test]
+    fn widens_the_gutter_for_long_line_numbers() {
+        let input = "\n".repeat(11) + "oops";
+        let rendered = ParseError::new(12, 3, "x").render(&input);
+        assert_eq!(rendered.lines().skip(1).collect::<Vec<_>>(), vec!["   |", "12 | oops", "   |   ^ expected x"]);
+    }
+}
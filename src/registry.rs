@@ -0,0 +1,90 @@
+use p1;
+use p10;
+use p11;
+use p12;
+use p13;
+use p14;
+use p15;
+use p16;
+use p16_take2;
+use p17;
+use p18;
+use p19;
+use p2;
+use p20;
+use p21;
+use p22;
+use p22cc;
+use p23;
+use p24;
+use p3;
+use p3radix;
+use p4;
+use p5;
+use p6;
+use p7;
+use p8;
+use p9;
+use parser::ParseError;
+use solution::{parse, Parsed};
+
+/// One runnable solver: a day, an optional variant name for alternative
+/// implementations, the named inputs it handles (the first one is what it runs
+/// on unless told otherwise), and how to parse an input into something both
+/// parts can be answered from.
+pub struct Day {
+    pub day: u32,
+    pub variant: Option<&'static str>,
+    pub inputs: &'static [&'static str],
+    pub parse: fn(&str) -> Result<Box<dyn Parsed>, ParseError>,
+}
+
+impl Day {
+    pub fn name(&self) -> String {
+        match self.variant {
+            Some(v) => format!("day {} ({})", self.day, v),
+            None => format!("day {}", self.day),
+        }
+    }
+
+    /// Parses an input for this solver, saying which day it was on failure.
+    pub fn prepare(&self, input: &str) -> Result<Box<dyn Parsed>, ParseError> {
+        (self.parse)(input).map_err(|e| e.in_day(self.day))
+    }
+}
+
+#[rustfmt::skip]
+pub static DAYS: &[Day] = &[
+    Day { day: 1, variant: None, inputs: &["puzzle"], parse: parse::<p1::Solver> },
+    Day { day: 2, variant: None, inputs: &["puzzle"], parse: parse::<p2::Solver> },
+    Day { day: 3, variant: None, inputs: &["puzzle"], parse: parse::<p3::Solver> },
+    Day { day: 3, variant: Some("radix"), inputs: &["puzzle", "sample"], parse: parse::<p3radix::Solver> },
+    Day { day: 4, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p4::Solver> },
+    Day { day: 5, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p5::Solver> },
+    Day { day: 6, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p6::Solver> },
+    Day { day: 7, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p7::Solver> },
+    Day { day: 8, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p8::Solver> },
+    Day { day: 9, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p9::Solver> },
+    Day { day: 10, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p10::Solver> },
+    Day { day: 11, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p11::Solver> },
+    Day { day: 12, variant: None, inputs: &["puzzle"], parse: parse::<p12::Solver> },
+    Day { day: 13, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p13::Solver> },
+    Day { day: 14, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p14::Solver> },
+    Day { day: 15, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p15::Solver> },
+    Day { day: 16, variant: None, inputs: &["puzzle"], parse: parse::<p16::Solver> },
+    Day { day: 16, variant: Some("take2"), inputs: &["puzzle"], parse: parse::<p16_take2::Solver> },
+    Day { day: 17, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p17::Solver> },
+    Day { day: 18, variant: None, inputs: &["puzzle"], parse: parse::<p18::Solver> },
+    Day { day: 19, variant: None, inputs: &["puzzle", "sample2"], parse: parse::<p19::Solver> },
+    Day { day: 20, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p20::Solver> },
+    Day { day: 21, variant: None, inputs: &["puzzle", "sample"], parse: parse::<p21::Solver> },
+    Day { day: 22, variant: None, inputs: &["puzzle", "sample", "bsample"], parse: parse::<p22::Solver> },
+    Day { day: 22, variant: Some("cc"), inputs: &["puzzle", "sample", "bsample"], parse: parse::<p22cc::Solver> },
+    Day { day: 23, variant: None, inputs: &["sample"], parse: parse::<p23::Solver> },
+    Day { day: 24, variant: None, inputs: &["puzzle"], parse: parse::<p24::Solver> },
+];
+
+/// Finds a registered solver; without a variant this is the day's default implementation.
+pub fn find(day: u32, variant: Option<&str>) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day && d.variant == variant)
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+
+use parser::ParseError;
+
+/// The answer to one part of a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i64),
+    Str(String),
+    /// Answers that are pictures, e.g. the folded paper of day 13 when the
+    /// dots do not spell letters.
+    Multiline(String),
+}
+
+impl Answer {
+    /// The answer without any of the layout `Display` adds around it.
+    pub fn text(&self) -> String {
+        match self {
+            Answer::Int(i) => i.to_string(),
+            Answer::Str(s) | Answer::Multiline(s) => s.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Int(i) => write!(f, "{}", i),
+            Answer::Str(s) => write!(f, "{}", s),
+            Answer::Multiline(s) => write!(f, "\n{}", s),
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(i: i64) -> Answer {
+        Answer::Int(i)
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(i: i32) -> Answer {
+        Answer::Int(i as i64)
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(i: u32) -> Answer {
+        Answer::Int(i as i64)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(i: u64) -> Answer {
+        Answer::Int(i as i64)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(i: isize) -> Answer {
+        Answer::Int(i as i64)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(i: usize) -> Answer {
+        Answer::Int(i as i64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        Answer::Str(s)
+    }
+}
+
+/// A day's puzzle: parse the input once, then answer both parts from it.
+pub trait Solution {
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Answer;
+    fn part2(input: &Self::Input) -> Answer;
+}
+
+/// A parsed input bundled with the solution that parsed it, so days with
+/// different input types can be driven the same way.
+pub trait Parsed {
+    fn part1(&self) -> Answer;
+    fn part2(&self) -> Answer;
+}
+
+struct Prepared<S: Solution> {
+    input: S::Input,
+}
+
+impl<S: Solution> Parsed for Prepared<S> {
+    fn part1(&self) -> Answer {
+        S::part1(&self.input)
+    }
+
+    fn part2(&self) -> Answer {
+        S::part2(&self.input)
+    }
+}
+
+pub fn parse<S: Solution + 'static>(input: &str) -> Result<Box<dyn Parsed>, ParseError> {
+    let input = S::parse(input)?;
+    Ok(Box::new(Prepared::<S> { input }))
+}
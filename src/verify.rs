@@ -0,0 +1,151 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use colored::*;
+
+use input::{self, Source};
+use manifest::{escape, Expected};
+use parallel::panic_message;
+use registry::{self, Day};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Pass,
+    /// The solver ran but gave something else.
+    Mismatch(String),
+    /// The solver could not be run, e.g. because its input is missing, or it
+    /// panicked.
+    Fail(String),
+    /// No solver is registered for the answer's input.
+    Missing,
+}
+
+pub struct Check<'a> {
+    pub solver: String,
+    pub expected: &'a Expected,
+    pub status: Status,
+}
+
+/// Runs `f`, turning a panic into the reason the check failed.
+fn guard<T, F: FnOnce() -> Result<T, String>>(f: F) -> Result<T, String> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => Err(format!("panicked: {}", panic_message(payload))),
+    }
+}
+
+/// Runs one solver against every known answer for the inputs it handles,
+/// parsing each input once for both parts. A panic in the solver fails the
+/// checks it was working on rather than the whole run.
+pub fn check_day<'a>(d: &'static Day, expected: &'a [Expected]) -> Vec<Check<'a>> {
+    let mut checks = vec![];
+    for name in d.inputs {
+        let wanted: Vec<&Expected> = expected
+            .iter()
+            .filter(|e| e.day == d.day && e.input == *name)
+            .collect();
+        if wanted.is_empty() {
+            continue;
+        }
+        let source = Source::Named(name.to_string());
+        let parsed = input::load(d.day, &source)
+            .map_err(|err| format!("could not read {}: {}", source.describe(d.day), err))
+            .and_then(|text| guard(|| d.prepare(&text).map_err(|err| err.to_string())));
+        for e in wanted {
+            let answer = parsed
+                .as_ref()
+                .map_err(|err| err.clone())
+                .and_then(|parsed| guard(|| Ok(if e.part == 1 { parsed.part1() } else { parsed.part2() })));
+            let status = match answer {
+                Err(err) => Status::Fail(err),
+                Ok(answer) if answer.text() == e.answer => Status::Pass,
+                Ok(answer) => Status::Mismatch(answer.text()),
+            };
+            checks.push(Check { solver: d.name(), expected: e, status });
+        }
+    }
+    return checks;
+}
+
+/// The known answers, for `day` or every day, that no registered solver takes
+/// the input of.
+pub fn check_missing(day: Option<u32>, expected: &[Expected]) -> Vec<Check<'_>> {
+    expected
+        .iter()
+        .filter(|e| day.is_none() || day == Some(e.day))
+        .filter(|e| !registry::DAYS.iter().any(|d| d.day == e.day && d.inputs.contains(&e.input.as_str())))
+        .map(|e| Check {
+            solver: format!("day {}", e.day),
+            expected: e,
+            status: Status::Missing,
+        })
+        .collect()
+}
+
+fn short(s: &str) -> String {
+    let s = escape(s);
+    if s.chars().count() > 30 {
+        return format!("{}...", s.chars().take(27).collect::<String>());
+    }
+    return s;
+}
+
+pub fn print_table(checks: &[Check]) {
+    println!("{:<20} {:<4} {:<10} {:<8} detail", "solver", "part", "input", "status");
+    for c in checks {
+        let (status, detail) = match &c.status {
+            Status::Pass => ("pass".green(), "".to_string()),
+            Status::Mismatch(got) => (
+                "mismatch".red(),
+                format!("expected {}, got {}", short(&c.expected.answer), short(got)),
+            ),
+            Status::Fail(why) => ("fail".red(), why.clone()),
+            Status::Missing => ("missing".red(), "no solver takes this input".to_string()),
+        };
+        println!(
+            "{:<20} {:<4} {:<10} {:<8} {}",
+            c.solver,
+            c.expected.part,
+            c.expected.input,
+            status,
+            detail
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parser::ParseError;
+    use solution::Parsed;
+
+    fn explode(_: &str) -> Result<Box<dyn Parsed>, ParseError> {
+        panic!("no solving this");
+    }
+
+    static EXPLODES: Day = Day { day: 1, variant: None, inputs: &["sample", "puzzle"], parse: explode };
+
+    fn expect(day: u32, part: usize, input: &str) -> Expected {
+        Expected { day, part, input: input.to_string(), answer: "0".to_string() }
+    }
+
+    #[test]
+    fn fails_panicking_solvers() {
+        let expected = vec![expect(1, 1, "puzzle"), expect(1, 2, "puzzle")];
+        let checks = check_day(&EXPLODES, &expected);
+        assert_eq!(checks.len(), 2);
+        for c in checks {
+            assert_eq!(c.status, Status::Fail("panicked: no solving this".to_string()));
+        }
+    }
+
+    #[test]
+    fn reports_unregistered_inputs() {
+        let expected = vec![expect(1, 1, "puzzle"), expect(1, 1, "nowhere"), expect(99, 2, "puzzle")];
+        let missing: Vec<(u32, &str)> = check_missing(None, &expected)
+            .iter()
+            .map(|c| (c.expected.day, c.expected.input.as_str()))
+            .collect();
+        assert_eq!(missing, vec![(1, "nowhere"), (99, "puzzle")]);
+        assert!(check_missing(Some(1), &expected).iter().all(|c| c.status == Status::Missing && c.solver == "day 1"));
+    }
+}
@@ -0,0 +1,27 @@
+//! The logging macros used from outside the crate, through their paths.
+
+extern crate adoc;
+
+use std::cell::Cell;
+
+use adoc::log::{self, Filter, Level};
+
+#[test]
+fn logs_through_crate_paths() {
+    log::init(Filter::parse("info").unwrap());
+    assert!(log::enabled(Level::Info, module_path!()));
+    assert!(!log::enabled(Level::Debug, module_path!()));
+
+    let formatted = Cell::new(0);
+    let count = || {
+        formatted.set(formatted.get() + 1);
+        formatted.get()
+    };
+    adoc::error!("error {}", count());
+    adoc::warn!("warn {}", count());
+    adoc::info!("info {}", count());
+    adoc::debug!("debug {}", count());
+    adoc::trace!("trace {}", count());
+    adoc::log!(Level::Trace, "log {}", count());
+    assert_eq!(formatted.get(), 3);
+}